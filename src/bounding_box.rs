@@ -0,0 +1,417 @@
+use std::borrow::Borrow;
+
+use crate::{wrap_degrees, Coordinate, CoordinateError, Precision, EARTH_RADIUS};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A rectangular area on the globe between two latitudes and two longitudes
+///
+/// If `west` is greater than `east`, the box crosses the antimeridian
+/// (e.g. west 170, east -170 is 20° wide).
+///
+/// ```
+/// # use recoord::{BoundingBox, Coordinate};
+/// let fiji = BoundingBox::new(-21., 177., -12., -178.).unwrap();
+/// assert!(fiji.crosses_antimeridian());
+/// assert!(fiji.contains(&Coordinate::new(-16.5, 179.9)));
+/// assert!(fiji.contains(&Coordinate::new(-16.5, -179.9)));
+/// assert!(!fiji.contains(&Coordinate::new(-16.5, 0.)));
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "Bounds", into = "Bounds")
+)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Southern latitude
+    pub(crate) south: f64,
+    /// Western longitude
+    pub(crate) west: f64,
+    /// Northern latitude
+    pub(crate) north: f64,
+    /// Eastern longitude
+    pub(crate) east: f64,
+}
+
+/// Unchecked representation of a bounding box for (de)serialization
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct Bounds {
+    /// Southern latitude
+    south: f64,
+    /// Western longitude
+    west: f64,
+    /// Northern latitude
+    north: f64,
+    /// Eastern longitude
+    east: f64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Bounds> for BoundingBox {
+    type Error = CoordinateError;
+    fn try_from(bounds: Bounds) -> Result<Self, Self::Error> {
+        BoundingBox::new(bounds.south, bounds.west, bounds.north, bounds.east)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<BoundingBox> for Bounds {
+    fn from(bbox: BoundingBox) -> Self {
+        Bounds {
+            south: bbox.south,
+            west: bbox.west,
+            north: bbox.north,
+            east: bbox.east,
+        }
+    }
+}
+
+/// Wrap a longitude into -180 (exclusive) - 180, so eastern edges stay at 180
+fn wrap_east(lng: f64) -> f64 {
+    -wrap_degrees(-lng)
+}
+
+impl BoundingBox {
+    /// The whole globe
+    pub const WORLD: BoundingBox = BoundingBox {
+        south: -90.,
+        west: -180.,
+        north: 90.,
+        east: 180.,
+    };
+
+    /// Create a bounding box, checking that all values are in range and south <= north
+    pub fn new(south: f64, west: f64, north: f64, east: f64) -> Result<Self, CoordinateError> {
+        let lat_range = -90.0..=90.0;
+        let lng_range = -180.0..=180.0;
+        if lat_range.contains(&south)
+            && lat_range.contains(&north)
+            && south <= north
+            && lng_range.contains(&west)
+            && lng_range.contains(&east)
+        {
+            Ok(Self {
+                south,
+                west,
+                north,
+                east,
+            })
+        } else {
+            Err(CoordinateError::InvalidValue)
+        }
+    }
+
+    /// Box around a center with a maximum deviation in each direction
+    pub fn from_center(center: &Coordinate, precision: Precision) -> Self {
+        let center = center.normalize();
+        let (west, east) = if precision.lng >= 180. {
+            (-180., 180.)
+        } else {
+            (
+                wrap_degrees(center.lng - precision.lng),
+                wrap_east(center.lng + precision.lng),
+            )
+        };
+        Self {
+            south: (center.lat - precision.lat).max(-90.),
+            west,
+            north: (center.lat + precision.lat).min(90.),
+            east,
+        }
+    }
+
+    /// Smallest box containing all points (None if there are none)
+    ///
+    /// Points on both sides of the antimeridian result in a box crossing it,
+    /// if that's smaller than going around the other way.
+    ///
+    /// ```
+    /// # use recoord::{BoundingBox, Coordinate};
+    /// let points = [Coordinate::new(-17., 178.), Coordinate::new(-14., -172.)];
+    /// let bbox = BoundingBox::from_points(&points).unwrap();
+    /// assert_eq!((bbox.west(), bbox.east()), (178., -172.));
+    /// assert_eq!(bbox.width(), 10.);
+    /// ```
+    pub fn from_points(points: impl IntoIterator<Item = impl Borrow<Coordinate>>) -> Option<Self> {
+        let mut south = f64::INFINITY;
+        let mut north = f64::NEG_INFINITY;
+        let mut lngs = vec![];
+        for point in points {
+            let point = point.borrow().normalize();
+            south = south.min(point.lat);
+            north = north.max(point.lat);
+            lngs.push(point.lng);
+        }
+        lngs.sort_by(f64::total_cmp);
+        let (first, last) = (*lngs.first()?, *lngs.last()?);
+
+        // The box is everything except the largest gap between longitudes
+        let (mut west, mut east) = (first, last);
+        let mut largest_gap = first + 360. - last;
+        for pair in lngs.windows(2) {
+            if pair[1] - pair[0] > largest_gap {
+                largest_gap = pair[1] - pair[0];
+                (west, east) = (pair[1], pair[0]);
+            }
+        }
+        Some(Self {
+            south,
+            west,
+            north,
+            east,
+        })
+    }
+
+    /// Southern latitude
+    pub fn south(&self) -> f64 {
+        self.south
+    }
+
+    /// Western longitude
+    pub fn west(&self) -> f64 {
+        self.west
+    }
+
+    /// Northern latitude
+    pub fn north(&self) -> f64 {
+        self.north
+    }
+
+    /// Eastern longitude
+    pub fn east(&self) -> f64 {
+        self.east
+    }
+
+    /// Whether the box goes across the antimeridian (west > east)
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.west > self.east
+    }
+
+    /// Height in degrees latitude
+    pub fn height(&self) -> f64 {
+        self.north - self.south
+    }
+
+    /// Width in degrees longitude
+    pub fn width(&self) -> f64 {
+        if self.crosses_antimeridian() {
+            self.east - self.west + 360.
+        } else {
+            self.east - self.west
+        }
+    }
+
+    /// Center of the box
+    pub fn center(&self) -> Coordinate {
+        Coordinate {
+            lat: (self.south + self.north) / 2.,
+            lng: wrap_degrees(self.west + self.width() / 2.),
+        }
+    }
+
+    /// Area of the box in square meters (on a sphere with the mean earth radius)
+    ///
+    /// ```
+    /// # use recoord::BoundingBox;
+    /// let world = BoundingBox::WORLD.area();
+    /// assert!((world / 510_065_600e6 - 1.).abs() < 1e-3);
+    /// ```
+    pub fn area(&self) -> f64 {
+        EARTH_RADIUS.powi(2)
+            * (self.north.to_radians().sin() - self.south.to_radians().sin())
+            * self.width().to_radians()
+    }
+
+    /// Whether the longitude lies between the western and eastern edge
+    fn contains_lng(&self, lng: f64) -> bool {
+        self.width() >= 360. || (lng - self.west).rem_euclid(360.) <= self.width()
+    }
+
+    /// Whether a coordinate lies within the box (edges included)
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        (self.south..=self.north).contains(&coordinate.lat) && self.contains_lng(coordinate.lng)
+    }
+
+    /// Whether two boxes overlap (touching edges count)
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.south <= other.north
+            && other.south <= self.north
+            && (self.contains_lng(other.west) || other.contains_lng(self.west))
+    }
+
+    /// Smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        /// Width needed when starting at the west edge of `a` to cover `b`
+        fn covering_width(a: &BoundingBox, b: &BoundingBox) -> f64 {
+            let offset = (b.west - a.west).rem_euclid(360.);
+            if offset <= a.width() {
+                a.width().max(offset + b.width())
+            } else {
+                offset + b.width()
+            }
+        }
+
+        let from_self = covering_width(self, other);
+        let from_other = covering_width(other, self);
+        let (west, width) = if from_self <= from_other {
+            (self.west, from_self)
+        } else {
+            (other.west, from_other)
+        };
+        let (west, east) = if width >= 360. {
+            (-180., 180.)
+        } else {
+            (west, wrap_east(west + width))
+        };
+        BoundingBox {
+            south: self.south.min(other.south),
+            west,
+            north: self.north.max(other.north),
+            east,
+        }
+    }
+
+    /// Grow the box by a distance in meters in every direction
+    ///
+    /// Boxes reaching a pole span all longitudes.
+    ///
+    /// ```
+    /// # use recoord::{BoundingBox, Coordinate};
+    /// let cologne = BoundingBox::from_points([Coordinate::new(50.94, 6.96)]).unwrap();
+    /// let around = cologne.expand(10_000.);
+    /// assert!(around.contains(&Coordinate::new(51.02, 6.96)));
+    /// assert!(!around.contains(&Coordinate::new(51.04, 6.96)));
+    /// ```
+    pub fn expand(&self, meters: f64) -> BoundingBox {
+        let d_lat = (meters / EARTH_RADIUS).to_degrees();
+        let south = (self.south - d_lat).max(-90.);
+        let north = (self.north + d_lat).min(90.);
+        let widest_lat = south.abs().max(north.abs());
+        if widest_lat >= 90. {
+            return BoundingBox {
+                south,
+                west: -180.,
+                north,
+                east: 180.,
+            };
+        }
+        let d_lng = d_lat / widest_lat.to_radians().cos();
+        let (west, east) = if self.width() + 2. * d_lng >= 360. {
+            (-180., 180.)
+        } else {
+            (
+                wrap_degrees(self.west - d_lng),
+                wrap_east(self.east + d_lng),
+            )
+        };
+        BoundingBox {
+            south,
+            west,
+            north,
+            east,
+        }
+    }
+
+    /// Maximum deviation of the center to the edges
+    pub fn precision(&self) -> Precision {
+        Precision {
+            lat: self.height() / 2.,
+            lng: self.width() / 2.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(BoundingBox::new(10., 170., 20., -170.).is_ok());
+        assert!(BoundingBox::new(20., 0., 10., 1.).is_err());
+        assert!(BoundingBox::new(0., 0., 91., 1.).is_err());
+        assert!(BoundingBox::new(f64::NAN, 0., 1., 1.).is_err());
+    }
+
+    #[test]
+    fn test_from_points() {
+        assert!(BoundingBox::from_points(Vec::<Coordinate>::new()).is_none());
+        let bbox = BoundingBox::from_points([
+            Coordinate::new(1., 10.),
+            Coordinate::new(-2., -20.),
+            Coordinate::new(3., 5.),
+        ])
+        .unwrap();
+        assert_eq!(bbox, BoundingBox::new(-2., -20., 3., 10.).unwrap());
+        let point = BoundingBox::from_points([Coordinate::new(1., 2.)]).unwrap();
+        assert_eq!(point.width(), 0.);
+        assert_eq!(point.center(), Coordinate::new(1., 2.));
+    }
+
+    #[test]
+    fn test_center_and_precision() {
+        let bbox = BoundingBox::new(-10., 170., 10., -150.).unwrap();
+        assert_eq!(bbox.width(), 40.);
+        assert_eq!(bbox.center(), Coordinate::new(0., -170.));
+        let precision = bbox.precision();
+        assert_eq!(BoundingBox::from_center(&bbox.center(), precision), bbox);
+        let edge =
+            BoundingBox::from_center(&Coordinate::new(0., 170.), Precision { lat: 1., lng: 10. });
+        assert_eq!((edge.west(), edge.east()), (160., 180.));
+    }
+
+    #[test]
+    fn test_intersects() {
+        let pacific = BoundingBox::new(-10., 170., 10., -170.).unwrap();
+        let east = BoundingBox::new(0., -175., 5., -160.).unwrap();
+        let europe = BoundingBox::new(40., 0., 50., 10.).unwrap();
+        let equator = BoundingBox::new(-1., -10., 1., 10.).unwrap();
+        assert!(pacific.intersects(&east));
+        assert!(east.intersects(&pacific));
+        assert!(!pacific.intersects(&europe));
+        assert!(!pacific.intersects(&equator));
+        assert!(BoundingBox::WORLD.intersects(&pacific));
+    }
+
+    #[test]
+    fn test_union() {
+        let west = BoundingBox::new(0., 170., 1., 175.).unwrap();
+        let east = BoundingBox::new(-1., -175., 0., -170.).unwrap();
+        let union = west.union(&east);
+        assert_eq!(union, BoundingBox::new(-1., 170., 1., -170.).unwrap());
+        assert_eq!(east.union(&west), union);
+
+        let inner = BoundingBox::new(0., 1., 1., 2.).unwrap();
+        let outer = BoundingBox::new(-5., -5., 5., 5.).unwrap();
+        assert_eq!(inner.union(&outer), outer);
+
+        let half = BoundingBox::new(0., -180., 1., 0.).unwrap();
+        let other_half = BoundingBox::new(0., 0., 1., 180.).unwrap();
+        let union = half.union(&other_half);
+        assert_eq!((union.west(), union.east()), (-180., 180.));
+    }
+
+    #[test]
+    fn test_expand() {
+        let bbox = BoundingBox::new(0., 179.9, 0., 179.9)
+            .unwrap()
+            .expand(50_000.);
+        assert!(bbox.crosses_antimeridian());
+        assert!(bbox.contains(&Coordinate::new(0.2, -179.9)));
+        let polar = BoundingBox::new(89.9, 0., 89.9, 0.)
+            .unwrap()
+            .expand(20_000.);
+        assert_eq!((polar.north(), polar.width()), (90., 360.));
+    }
+
+    #[test]
+    fn test_area() {
+        let bbox = BoundingBox::new(0., 0., 1., 1.).unwrap();
+        assert!((bbox.area() / 1e6 - 12_364.).abs() < 10.);
+        let crossing = BoundingBox::new(0., 179.5, 1., -179.5).unwrap();
+        assert!((crossing.area() - bbox.area()).abs() < 1.);
+    }
+}
@@ -0,0 +1,559 @@
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "format_any")]
+use std::str::FromStr;
+
+use crate::{
+    geodesy::{Ecef, Ellipsoid},
+    Coordinate, CoordinateError,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Seconds of arc in radians
+const ARC_SECOND: f64 = std::f64::consts::PI / 180. / 3600.;
+
+/// Geodetic datum a coordinate refers to
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Datum {
+    /// World Geodetic System 1984 (GPS, EPSG:4326)
+    #[default]
+    Wgs84,
+    /// European Terrestrial Reference System 1989 (EPSG:4258)
+    Etrs89,
+    /// European Datum 1950 (EPSG:4230)
+    Ed50,
+    /// Ordnance Survey Great Britain 1936 (EPSG:4277)
+    Osgb36,
+    /// North American Datum 1927 (EPSG:4267)
+    Nad27,
+    /// Deutsches Hauptdreiecksnetz / Potsdam (EPSG:4314)
+    Dhdn,
+    /// Swiss CH1903 (EPSG:4149)
+    Ch1903,
+}
+
+/// How coordinates are shifted between datums
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Method {
+    /// 7-parameter Helmert transformation of cartesian coordinates
+    #[default]
+    Helmert,
+    /// Abridged Molodensky transformation of geodetic coordinates (3 translations)
+    Molodensky,
+}
+
+/// 7-parameter Helmert transformation (position vector convention)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Helmert {
+    /// Translation along x in meters
+    pub tx: f64,
+    /// Translation along y in meters
+    pub ty: f64,
+    /// Translation along z in meters
+    pub tz: f64,
+    /// Rotation around x in arc seconds
+    pub rx: f64,
+    /// Rotation around y in arc seconds
+    pub ry: f64,
+    /// Rotation around z in arc seconds
+    pub rz: f64,
+    /// Scale change in parts per million
+    pub s: f64,
+}
+
+impl Helmert {
+    /// Transformation which doesn't change anything
+    pub const IDENTITY: Helmert = Helmert::translation(0., 0., 0.);
+
+    /// Transformation with translations only
+    pub const fn translation(tx: f64, ty: f64, tz: f64) -> Self {
+        Helmert {
+            tx,
+            ty,
+            tz,
+            rx: 0.,
+            ry: 0.,
+            rz: 0.,
+            s: 0.,
+        }
+    }
+
+    /// Transform a point
+    pub fn apply(&self, point: &Ecef) -> Ecef {
+        let scale = 1. + self.s * 1e-6;
+        let (rx, ry, rz) = (
+            self.rx * ARC_SECOND,
+            self.ry * ARC_SECOND,
+            self.rz * ARC_SECOND,
+        );
+        Ecef {
+            x: self.tx + scale * (point.x - rz * point.y + ry * point.z),
+            y: self.ty + scale * (rz * point.x + point.y - rx * point.z),
+            z: self.tz + scale * (-ry * point.x + rx * point.y + point.z),
+        }
+    }
+
+    /// Undo the transformation of a point
+    pub fn apply_inverse(&self, point: &Ecef) -> Ecef {
+        let scale = 1. + self.s * 1e-6;
+        let (rx, ry, rz) = (
+            self.rx * ARC_SECOND,
+            self.ry * ARC_SECOND,
+            self.rz * ARC_SECOND,
+        );
+        let (x, y, z) = (
+            (point.x - self.tx) / scale,
+            (point.y - self.ty) / scale,
+            (point.z - self.tz) / scale,
+        );
+        // The transposed rotation matrix inverts the rotation (up to second order terms)
+        Ecef {
+            x: x + rz * y - ry * z,
+            y: -rz * x + y + rx * z,
+            z: ry * x - rx * y + z,
+        }
+    }
+}
+
+impl Datum {
+    /// All known datums
+    pub const ALL: [Datum; 7] = [
+        Datum::Wgs84,
+        Datum::Etrs89,
+        Datum::Ed50,
+        Datum::Osgb36,
+        Datum::Nad27,
+        Datum::Dhdn,
+        Datum::Ch1903,
+    ];
+
+    /// Common name of the datum
+    pub fn name(self) -> &'static str {
+        match self {
+            Datum::Wgs84 => "WGS84",
+            Datum::Etrs89 => "ETRS89",
+            Datum::Ed50 => "ED50",
+            Datum::Osgb36 => "OSGB36",
+            Datum::Nad27 => "NAD27",
+            Datum::Dhdn => "DHDN",
+            Datum::Ch1903 => "CH1903",
+        }
+    }
+
+    /// EPSG code of the geographic coordinate system
+    pub fn epsg(self) -> u32 {
+        match self {
+            Datum::Wgs84 => 4326,
+            Datum::Etrs89 => 4258,
+            Datum::Ed50 => 4230,
+            Datum::Osgb36 => 4277,
+            Datum::Nad27 => 4267,
+            Datum::Dhdn => 4314,
+            Datum::Ch1903 => 4149,
+        }
+    }
+
+    /// Reference ellipsoid of the datum
+    pub fn ellipsoid(self) -> Ellipsoid {
+        match self {
+            Datum::Wgs84 => Ellipsoid::WGS84,
+            Datum::Etrs89 => Ellipsoid::GRS80,
+            Datum::Ed50 => Ellipsoid::INTERNATIONAL1924,
+            Datum::Osgb36 => Ellipsoid::AIRY1830,
+            Datum::Nad27 => Ellipsoid::CLARKE1866,
+            Datum::Dhdn | Datum::Ch1903 => Ellipsoid::BESSEL1841,
+        }
+    }
+
+    /// Helmert transformation from this datum to WGS84
+    ///
+    /// The parameters are the ones commonly published for the whole area of
+    /// the datum (e.g. by the Ordnance Survey for OSGB36 and the EPSG registry).
+    pub fn helmert(self) -> Helmert {
+        match self {
+            Datum::Wgs84 | Datum::Etrs89 => Helmert::IDENTITY,
+            Datum::Ed50 => Helmert::translation(-87., -98., -121.),
+            Datum::Osgb36 => Helmert {
+                tx: 446.448,
+                ty: -125.157,
+                tz: 542.06,
+                rx: 0.1502,
+                ry: 0.247,
+                rz: 0.8421,
+                s: -20.4894,
+            },
+            Datum::Nad27 => Helmert::translation(-8., 160., 176.),
+            Datum::Dhdn => Helmert {
+                tx: 598.1,
+                ty: 73.7,
+                tz: 418.2,
+                rx: 0.202,
+                ry: 0.045,
+                rz: -2.455,
+                s: 6.7,
+            },
+            Datum::Ch1903 => Helmert::translation(674.374, 15.056, 405.346),
+        }
+    }
+
+    /// Translations from this datum to WGS84 for the Molodensky transformation
+    pub fn molodensky(self) -> [f64; 3] {
+        match self {
+            Datum::Osgb36 => [375., -111., 431.],
+            Datum::Dhdn => [606., 23., 413.],
+            _ => {
+                let helmert = self.helmert();
+                [helmert.tx, helmert.ty, helmert.tz]
+            }
+        }
+    }
+
+    /// Approximate accuracy in meters of shifting between this datum and WGS84
+    ///
+    /// ETRS89 is treated as identical to WGS84, which is correct to about a
+    /// meter since both drift apart with the continental plate.
+    pub fn accuracy(self, method: Method) -> f64 {
+        match (self, method) {
+            (Datum::Wgs84, _) => 0.,
+            (Datum::Etrs89, _) => 1.,
+            (Datum::Ch1903, _) => 1.,
+            (Datum::Dhdn, Method::Helmert) => 3.,
+            (Datum::Osgb36, Method::Helmert) => 5.,
+            (Datum::Ed50 | Datum::Nad27, _) => 10.,
+            (Datum::Dhdn, Method::Molodensky) => 10.,
+            (Datum::Osgb36, Method::Molodensky) => 20.,
+        }
+    }
+}
+
+impl Display for Datum {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Datum {
+    type Err = CoordinateError;
+
+    /// Parse a datum by name (e.g. "OSGB36" or "Potsdam") or EPSG code (e.g. "EPSG:4277")
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let name: String = str
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+            .collect::<String>()
+            .to_uppercase();
+        if let Some(code) = name.strip_prefix("EPSG:") {
+            let code: u32 = code.parse().map_err(|_| CoordinateError::InvalidValue)?;
+            return Datum::ALL
+                .into_iter()
+                .find(|datum| datum.epsg() == code)
+                .ok_or(CoordinateError::Unsupported);
+        }
+        match name.as_str() {
+            "POTSDAM" => Ok(Datum::Dhdn),
+            "CH1903+" => Err(CoordinateError::Unsupported),
+            _ => Datum::ALL
+                .into_iter()
+                .find(|datum| datum.name() == name)
+                .ok_or(CoordinateError::InvalidValue),
+        }
+    }
+}
+
+/// Shift a geodetic coordinate with the abridged Molodensky formulas
+fn molodensky(
+    coordinate: &Coordinate,
+    from: Ellipsoid,
+    to: Ellipsoid,
+    shift: [f64; 3],
+) -> Coordinate {
+    let [dx, dy, dz] = shift;
+    let (lat, lng) = (coordinate.lat.to_radians(), coordinate.lng.to_radians());
+    let (da, df) = (to.a - from.a, to.f - from.f);
+    let e2 = from.e2();
+    let denominator = 1. - e2 * lat.sin().powi(2);
+    let m = from.a * (1. - e2) / denominator.powf(1.5);
+    let n = from.a / denominator.sqrt();
+    let d_lat = (-dx * lat.sin() * lng.cos() - dy * lat.sin() * lng.sin()
+        + dz * lat.cos()
+        + (from.a * df + from.f * da) * (2. * lat).sin())
+        / m;
+    // The longitude of a pole is arbitrary, so it's kept rather than divided by zero
+    let d_lng = if coordinate.lat.abs() >= 90. {
+        0.
+    } else {
+        (-dx * lng.sin() + dy * lng.cos()) / (n * lat.cos())
+    };
+    Coordinate {
+        lat: (lat + d_lat).to_degrees().clamp(-90., 90.),
+        lng: crate::wrap_degrees((lng + d_lng).to_degrees()),
+    }
+}
+
+/// A coordinate tagged with the datum it refers to
+///
+/// Heights are taken as 0 above the ellipsoid of the datum, which is precise
+/// enough for horizontal positions.
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::datum::{Datum, DatumCoordinate};
+/// // The Greenwich meridian in OSGB36 is about 100 m east of the WGS84 one
+/// let greenwich = DatumCoordinate::new(Coordinate::new(51.4778, 0.), Datum::Osgb36);
+/// let wgs84 = greenwich.to_wgs84();
+/// assert!((wgs84.coordinate.coordinate.lng + 0.0015).abs() < 0.0002);
+/// assert_eq!(wgs84.accuracy, 5.);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatumCoordinate {
+    /// Latitude and longitude in the datum
+    pub coordinate: Coordinate,
+    /// Datum of the coordinate
+    pub datum: Datum,
+}
+
+/// Result of a datum transformation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transformed {
+    /// The coordinate in the target datum
+    pub coordinate: DatumCoordinate,
+    /// Approximate accuracy in meters
+    pub accuracy: f64,
+}
+
+impl DatumCoordinate {
+    /// Tag a coordinate with a datum
+    pub fn new(coordinate: Coordinate, datum: Datum) -> Self {
+        Self { coordinate, datum }
+    }
+
+    /// Transform into WGS84 with the Helmert transformation
+    pub fn to_wgs84(&self) -> Transformed {
+        self.transform(Datum::Wgs84, Method::Helmert)
+    }
+
+    /// Transform into another datum
+    ///
+    /// Transformations between two datums other than WGS84 go through WGS84,
+    /// so their accuracies add up.
+    pub fn transform(&self, target: Datum, method: Method) -> Transformed {
+        let accuracy = if self.datum == target {
+            0.
+        } else {
+            self.datum.accuracy(method) + target.accuracy(method)
+        };
+        let coordinate = if self.datum == target {
+            self.coordinate.clone()
+        } else {
+            match method {
+                Method::Helmert => {
+                    let point = Ecef::from_geodetic(&self.coordinate, 0., &self.datum.ellipsoid());
+                    let wgs84 = self.datum.helmert().apply(&point);
+                    let target_point = target.helmert().apply_inverse(&wgs84);
+                    target_point.to_geodetic(&target.ellipsoid()).0
+                }
+                Method::Molodensky => {
+                    let wgs84 = molodensky(
+                        &self.coordinate,
+                        self.datum.ellipsoid(),
+                        Ellipsoid::WGS84,
+                        self.datum.molodensky(),
+                    );
+                    let [dx, dy, dz] = target.molodensky();
+                    molodensky(
+                        &wgs84,
+                        Ellipsoid::WGS84,
+                        target.ellipsoid(),
+                        [-dx, -dy, -dz],
+                    )
+                }
+            }
+        };
+        Transformed {
+            coordinate: DatumCoordinate {
+                coordinate,
+                datum: target,
+            },
+            accuracy,
+        }
+    }
+}
+
+impl Coordinate {
+    /// Tag the coordinate with the datum it refers to
+    pub fn with_datum(self, datum: Datum) -> DatumCoordinate {
+        DatumCoordinate::new(self, datum)
+    }
+}
+
+impl From<Coordinate> for DatumCoordinate {
+    /// Coordinates without datum are WGS84
+    fn from(coordinate: Coordinate) -> Self {
+        Self::new(coordinate, Datum::Wgs84)
+    }
+}
+
+impl Display for DatumCoordinate {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.datum, self.coordinate)
+    }
+}
+
+#[cfg(feature = "format_any")]
+impl FromStr for DatumCoordinate {
+    type Err = CoordinateError;
+
+    /// Parse a coordinate in any enabled format, optionally prefixed by a datum
+    ///
+    /// The datum is separated by a colon (e.g. "OSGB36:51.4778,0" or
+    /// "EPSG:4230:48.85,2.29"). Without a datum, WGS84 is assumed.
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str = str.trim();
+        let prefix_end = match str.get(..5) {
+            Some(epsg) if epsg.eq_ignore_ascii_case("EPSG:") => {
+                str[5..].find(':').map(|index| index + 5)
+            }
+            _ => str.find(':'),
+        };
+        if let Some(index) = prefix_end {
+            if let Ok(datum) = Datum::from_str(&str[..index]) {
+                return Coordinate::from_str(&str[index + 1..])
+                    .map(|coordinate| DatumCoordinate::new(coordinate, datum));
+            }
+        }
+        Coordinate::from_str(str).map(DatumCoordinate::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let coordinate = Coordinate::new(52.52, 13.405);
+        for datum in Datum::ALL {
+            // The abridged Molodensky formulas neglect second order terms
+            for (method, tolerance) in [(Method::Helmert, 0.01), (Method::Molodensky, 0.1)] {
+                let shifted = DatumCoordinate::new(coordinate.clone(), datum)
+                    .transform(Datum::Wgs84, method)
+                    .coordinate;
+                let back = shifted.transform(datum, method).coordinate;
+                assert!(
+                    back.coordinate.distance(&coordinate) < tolerance,
+                    "{datum} {method:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_methods_agree() {
+        let coordinate = Coordinate::new(51.5, -0.12);
+        for datum in Datum::ALL {
+            let source = DatumCoordinate::new(coordinate.clone(), datum);
+            let helmert = source.transform(Datum::Wgs84, Method::Helmert);
+            let molodensky = source.transform(Datum::Wgs84, Method::Molodensky);
+            let difference = helmert
+                .coordinate
+                .coordinate
+                .distance(&molodensky.coordinate.coordinate);
+            assert!(
+                difference <= helmert.accuracy + molodensky.accuracy,
+                "{datum}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_molodensky_poles() {
+        for datum in Datum::ALL {
+            for lat in [90., -90.] {
+                let pole = DatumCoordinate::new(Coordinate::new(lat, 12.5), datum)
+                    .transform(Datum::Wgs84, Method::Molodensky)
+                    .coordinate
+                    .coordinate;
+                assert!(pole.is_valid(), "{datum} {lat}");
+                assert_eq!(pole.lng, 12.5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_antimeridian() {
+        for datum in Datum::ALL {
+            for method in [Method::Helmert, Method::Molodensky] {
+                for lng in [179.99999, -179.99999] {
+                    let shifted = DatumCoordinate::new(Coordinate::new(10., lng), Datum::Wgs84)
+                        .transform(datum, method)
+                        .coordinate
+                        .coordinate;
+                    assert!(shifted.is_valid(), "{datum} {lng}");
+                    let distance = shifted.distance(&Coordinate::new(10., lng));
+                    assert!(distance < 1000., "{datum} {lng}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_swiss_fundamental_point() {
+        // Old observatory of Bern, origin of the Swiss grid
+        let bern = DatumCoordinate::new(
+            Coordinate::new(
+                46. + 57. / 60. + 8.66 / 3600.,
+                7. + 26. / 60. + 22.5 / 3600.,
+            ),
+            Datum::Ch1903,
+        );
+        let wgs84 = bern.to_wgs84();
+        let expected = Coordinate::new(46.9510827861, 7.4386324175);
+        assert!(wgs84.coordinate.coordinate.distance(&expected) < 2. * wgs84.accuracy);
+    }
+
+    #[test]
+    fn test_between_datums() {
+        let ed50 = DatumCoordinate::new(Coordinate::new(48.8583, 2.2945), Datum::Ed50);
+        let osgb36 = ed50.transform(Datum::Osgb36, Method::Helmert);
+        assert_eq!(osgb36.coordinate.datum, Datum::Osgb36);
+        assert_eq!(osgb36.accuracy, 15.);
+        let same = ed50.transform(Datum::Ed50, Method::Helmert);
+        assert_eq!((same.coordinate, same.accuracy), (ed50, 0.));
+    }
+
+    #[test]
+    fn test_datum_from_str() {
+        assert_eq!("osgb 36".parse::<Datum>().unwrap(), Datum::Osgb36);
+        assert_eq!("Potsdam".parse::<Datum>().unwrap(), Datum::Dhdn);
+        assert_eq!("EPSG:4230".parse::<Datum>().unwrap(), Datum::Ed50);
+        assert!(matches!(
+            "EPSG:3857".parse::<Datum>(),
+            Err(CoordinateError::Unsupported)
+        ));
+        assert!("Mars2000".parse::<Datum>().is_err());
+    }
+
+    #[cfg(feature = "format_dd")]
+    #[test]
+    fn test_datum_coordinate_from_str() {
+        let parsed: DatumCoordinate = "OSGB36:51.4778,0".parse().unwrap();
+        assert_eq!(
+            parsed,
+            Coordinate::new(51.4778, 0.).with_datum(Datum::Osgb36)
+        );
+        let parsed: DatumCoordinate = "EPSG:4230: 48.85,2.29".parse().unwrap();
+        assert_eq!(parsed.datum, Datum::Ed50);
+        let parsed: DatumCoordinate = "48.85,2.29".parse().unwrap();
+        assert_eq!(parsed.datum, Datum::Wgs84);
+        assert_eq!(
+            parsed.to_string().parse::<DatumCoordinate>().unwrap(),
+            parsed
+        );
+    }
+}
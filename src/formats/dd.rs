@@ -1,18 +1,47 @@
 use core::fmt;
 use std::{fmt::Display, str::FromStr};
 
-use crate::{Coordinate, CoordinateError};
+use crate::{formats::last_digit_precision, Coordinate, CoordinateError, Precision};
 use regex::Regex;
 
 /// A Coordinate in the floating point representation
 /// (e.g. 12.345,6.789)
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug, Clone, PartialEq)]
+///
+/// It's serialized like a [`Coordinate`], without the precision.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Coordinate", into = "Coordinate")
+)]
+#[derive(Debug, Clone)]
 pub struct DDCoordinate {
     /// Latitude of the Coordinate
     lat: f64,
     /// Longitude of the coordinate
     lng: f64,
+    /// Precision given by the written decimals
+    precision: Precision,
+}
+
+impl DDCoordinate {
+    /// Precision given by the number of decimals (e.g. ±0.005° for 12.34)
+    ///
+    /// ```
+    /// # use recoord::formats::dd::DDCoordinate;
+    /// let dd: DDCoordinate = "12.34,5.60".parse().unwrap();
+    /// assert!((dd.precision().lat - 0.005).abs() < 1e-12);
+    /// assert!((dd.precision().lng - 0.005).abs() < 1e-12);
+    /// ```
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+}
+
+impl PartialEq for DDCoordinate {
+    /// Only the values are compared, not how they were written
+    fn eq(&self, other: &Self) -> bool {
+        self.lat == other.lat && self.lng == other.lng
+    }
 }
 
 impl FromStr for DDCoordinate {
@@ -27,9 +56,15 @@ impl FromStr for DDCoordinate {
 
         if let Some(captures) = re_captures {
             if let (Some(lng), Some(lat)) = (captures.name("lng"), captures.name("lat")) {
+                let coordinate =
+                    Coordinate::try_new(lat.as_str().parse()?, lng.as_str().parse()?)?;
                 return Ok(DDCoordinate {
-                    lng: lng.as_str().parse()?,
-                    lat: lat.as_str().parse()?,
+                    lat: coordinate.lat,
+                    lng: coordinate.lng,
+                    precision: Precision {
+                        lat: last_digit_precision(lat.as_str()),
+                        lng: last_digit_precision(lng.as_str()),
+                    },
                 });
             }
         }
@@ -57,6 +92,10 @@ impl From<Coordinate> for DDCoordinate {
         Self {
             lat: coord.lat,
             lng: coord.lng,
+            precision: Precision {
+                lat: last_digit_precision(&coord.lat.to_string()),
+                lng: last_digit_precision(&coord.lng.to_string()),
+            },
         }
     }
 }
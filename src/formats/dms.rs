@@ -1,7 +1,7 @@
 use core::fmt;
 use std::{fmt::Display, str::FromStr};
 
-use crate::{Coordinate, CoordinateError};
+use crate::{formats::last_digit_precision, Coordinate, CoordinateError, Precision};
 use regex::Regex;
 
 /// Compass Direction on the horizontal axis
@@ -85,7 +85,7 @@ impl From<f64> for CompassVerticalDirection {
 }
 /// A Degree, Minute, Second unit for dms coordinates
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 struct DMSUnit {
     /// Degrees of the unit
     degrees: f64,
@@ -93,6 +93,44 @@ struct DMSUnit {
     minutes: f64,
     /// Seconds of the unit
     seconds: f64,
+    /// Precision in degrees given by the smallest unit written (0 if not parsed)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    precision: f64,
+}
+
+impl PartialEq for DMSUnit {
+    /// Only the values are compared, not how they were written
+    fn eq(&self, other: &Self) -> bool {
+        self.degrees == other.degrees
+            && self.minutes == other.minutes
+            && self.seconds == other.seconds
+    }
+}
+
+impl DMSUnit {
+    /// Whether minutes and seconds are below 60
+    fn is_valid(&self) -> bool {
+        (0.0..60.).contains(&self.minutes) && (0.0..60.).contains(&self.seconds)
+    }
+
+    /// Read the written degrees and optional minutes and seconds
+    fn parse(
+        degrees: &str,
+        minutes: Option<&str>,
+        seconds: Option<&str>,
+    ) -> Result<Self, CoordinateError> {
+        let precision = match (minutes, seconds) {
+            (_, Some(seconds)) => last_digit_precision(seconds) / 60. / 60.,
+            (Some(minutes), None) => last_digit_precision(minutes) / 60.,
+            (None, None) => last_digit_precision(degrees),
+        };
+        Ok(Self {
+            degrees: degrees.parse()?,
+            minutes: minutes.map_or(Ok(0.), str::parse)?,
+            seconds: seconds.map_or(Ok(0.), str::parse)?,
+            precision,
+        })
+    }
 }
 
 impl From<DMSUnit> for f64 {
@@ -103,10 +141,15 @@ impl From<DMSUnit> for f64 {
 
 impl From<f64> for DMSUnit {
     fn from(float: f64) -> Self {
+        let degrees = float.abs().floor();
+        let minutes = (float.abs().fract() * 60.).floor() / 60.;
+        let seconds = (float.abs().fract() * 60. * 60.).floor() / 60. / 60.;
+        // Nothing was written, so the precision is unknown
         Self {
-            degrees: float.abs().floor(),
-            minutes: (float.abs().fract() * 60.).floor() / 60.,
-            seconds: (float.abs().fract() * 60. * 60.).floor() / 60. / 60.,
+            degrees,
+            minutes,
+            seconds,
+            precision: 0.,
         }
     }
 }
@@ -122,6 +165,18 @@ pub struct DMSCoordinate {
     north_south: (DMSUnit, CompassVerticalDirection),
 }
 
+impl DMSCoordinate {
+    /// Precision given by the smallest unit used (e.g. ±0.5' for 50°10'N)
+    ///
+    /// It's zero for coordinates which weren't parsed.
+    pub fn precision(&self) -> Precision {
+        Precision {
+            lat: self.north_south.0.precision,
+            lng: self.east_west.0.precision,
+        }
+    }
+}
+
 impl FromStr for DMSCoordinate {
     type Err = CoordinateError;
     fn from_str(str: &str) -> Result<Self, Self::Err> {
@@ -149,32 +204,30 @@ impl FromStr for DMSCoordinate {
                 captures.name("long_sec"),
                 captures.name("e_w"),
             ) {
-                return Ok(DMSCoordinate {
+                let dms = DMSCoordinate {
                     north_south: (
-                        DMSUnit {
-                            degrees: lat_deg.as_str().parse()?,
-                            minutes: lat_min
-                                .map(|lat_min| lat_min.as_str().parse())
-                                .unwrap_or(Ok(0.0))?,
-                            seconds: lat_sec
-                                .map(|lat_sec| lat_sec.as_str().parse())
-                                .unwrap_or(Ok(0.0))?,
-                        },
+                        DMSUnit::parse(
+                            lat_deg.as_str(),
+                            lat_min.map(|lat_min| lat_min.as_str()),
+                            lat_sec.map(|lat_sec| lat_sec.as_str()),
+                        )?,
                         CompassVerticalDirection::try_from(n_s.as_str())?,
                     ),
                     east_west: (
-                        DMSUnit {
-                            degrees: lng_deg.as_str().parse()?,
-                            minutes: lng_min
-                                .map(|lng_min| lng_min.as_str().parse())
-                                .unwrap_or(Ok(0.0))?,
-                            seconds: lng_sec
-                                .map(|lng_sec| lng_sec.as_str().parse())
-                                .unwrap_or(Ok(0.0))?,
-                        },
+                        DMSUnit::parse(
+                            lng_deg.as_str(),
+                            lng_min.map(|lng_min| lng_min.as_str()),
+                            lng_sec.map(|lng_sec| lng_sec.as_str()),
+                        )?,
                         CompassHorizontalDirection::try_from(e_w.as_str())?,
                     ),
-                });
+                };
+                if !dms.north_south.0.is_valid() || !dms.east_west.0.is_valid() {
+                    return Err(CoordinateError::InvalidValue);
+                }
+                let coordinate = Coordinate::from(dms.clone());
+                Coordinate::try_new(coordinate.lat, coordinate.lng)?;
+                return Ok(dms);
             }
         }
         Err(CoordinateError::Malformed)
@@ -0,0 +1,195 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use super::labeled_pair;
+use crate::{
+    datum::{Datum, DatumCoordinate, Method},
+    geodesy::{Ellipsoid, TransverseMercator},
+    Coordinate, CoordinateError, Precision,
+};
+
+/// Zones (central meridians divided by 3°) covering Germany
+const ZONES: std::ops::RangeInclusive<u8> = 2..=5;
+
+/// Northings in meters covering Germany
+const NORTHINGS: std::ops::RangeInclusive<f64> = 5_200_000.0..=6_200_000.0;
+
+/// A German Gauss-Krüger coordinate (e.g. "R 3570000 H 5650000")
+///
+/// The first digit of the easting (Rechtswert) is the zone, whose central
+/// meridian lies at 3° times the zone. Coordinates are on the DHDN (Potsdam)
+/// datum and shifted to WGS84 with the Helmert transformation (accurate to
+/// about 3 m). Only the zones 2 to 5 and northings covering Germany are
+/// accepted, both when parsing and when converting a coordinate.
+///
+/// The labels "R"/"RW" and "H"/"HW" are optional, so "3570000 5650000" and
+/// "3570000/5650000" are accepted as well.
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::gauss_krueger::GaussKrueger;
+/// let gk: GaussKrueger = "R 2567397 H 5645554".parse().unwrap();
+/// assert_eq!(gk.zone(), 2);
+/// let cathedral = Coordinate::new(50.9413, 6.9583);
+/// assert!(Coordinate::from(gk).distance(&cathedral) < 10.);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct GaussKrueger {
+    /// Easting (Rechtswert) in meters, prefixed by the zone
+    pub easting: f64,
+    /// Northing (Hochwert) in meters from the equator
+    pub northing: f64,
+    /// Half a unit of the last written digit in meters (0 if not parsed)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    digits: f64,
+}
+
+impl PartialEq for GaussKrueger {
+    /// Only the values are compared, not how they were written
+    fn eq(&self, other: &Self) -> bool {
+        self.easting == other.easting && self.northing == other.northing
+    }
+}
+
+impl GaussKrueger {
+    /// Gauss-Krüger coordinate of a WGS84 coordinate in the nearest zone
+    ///
+    /// Coordinates outside the zones and northings covering Germany are
+    /// rejected, as they are when parsing.
+    pub fn from_coordinate(coordinate: &Coordinate) -> Result<Self, CoordinateError> {
+        let dhdn = DatumCoordinate::from(coordinate.clone())
+            .transform(Datum::Dhdn, Method::Helmert)
+            .coordinate
+            .coordinate;
+        // Out of range zones (and NaN) saturate to 0 or 255
+        let zone = (dhdn.lng / 3.).round() as u8;
+        if !ZONES.contains(&zone) {
+            return Err(CoordinateError::InvalidValue);
+        }
+        let (easting, northing) = projection(zone).project(&dhdn);
+        if !NORTHINGS.contains(&northing) {
+            return Err(CoordinateError::InvalidValue);
+        }
+        // Nothing was written, so only the datum shift counts
+        Ok(GaussKrueger {
+            easting,
+            northing,
+            digits: 0.,
+        })
+    }
+
+    /// Zone of the coordinate (central meridian divided by 3°)
+    pub fn zone(&self) -> u8 {
+        (self.easting / 1_000_000.).floor() as u8
+    }
+
+    /// Coordinate on the DHDN datum
+    pub fn dhdn(&self) -> Coordinate {
+        projection(self.zone()).unproject(self.easting, self.northing)
+    }
+
+    /// Precision given by the written digits and the datum shift
+    pub fn precision(&self) -> Precision {
+        let meters = self.digits + Datum::Dhdn.accuracy(Method::Helmert);
+        Precision::from_meters(meters, &self.dhdn())
+    }
+}
+
+/// Transverse Mercator projection of a zone on the Bessel ellipsoid
+fn projection(zone: u8) -> TransverseMercator {
+    TransverseMercator {
+        ellipsoid: Ellipsoid::BESSEL1841,
+        origin_lat: 0.,
+        central_meridian: zone as f64 * 3.,
+        scale: 1.,
+        false_easting: zone as f64 * 1_000_000. + 500_000.,
+        false_northing: 0.,
+    }
+}
+
+impl FromStr for GaussKrueger {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let ((easting, northing), digits) = labeled_pair(str, [&["R", "RW"], &["H", "HW"]])?;
+        let gk = GaussKrueger {
+            easting,
+            northing,
+            digits,
+        };
+        if !ZONES.contains(&gk.zone()) || !NORTHINGS.contains(&northing) {
+            return Err(CoordinateError::InvalidValue);
+        }
+        Ok(gk)
+    }
+}
+
+impl Display for GaussKrueger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "R {} H {}", self.easting, self.northing)
+    }
+}
+
+impl From<GaussKrueger> for Coordinate {
+    /// Coordinate on the WGS84 datum
+    fn from(gk: GaussKrueger) -> Self {
+        DatumCoordinate::new(gk.dhdn(), Datum::Dhdn)
+            .to_wgs84()
+            .coordinate
+            .coordinate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        for input in [
+            "R 3570000 H 5650000",
+            "RW: 3570000.0 HW: 5650000.0",
+            "3570000 5650000",
+            "3570000/5650000",
+        ] {
+            let gk: GaussKrueger = input.parse().unwrap();
+            assert_eq!(
+                (gk.easting, gk.northing),
+                (3_570_000., 5_650_000.),
+                "{input}"
+            );
+        }
+        assert!("H 5650000 R 3570000".parse::<GaussKrueger>().is_err());
+        assert!("2600000/1200000".parse::<GaussKrueger>().is_err());
+        assert!("7570000 5650000".parse::<GaussKrueger>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let gk: GaussKrueger = "R 3570000 H 5650000".parse().unwrap();
+        assert_eq!(gk.to_string(), "R 3570000 H 5650000");
+        let coordinate = Coordinate::from(gk.clone());
+        let back = GaussKrueger::from_coordinate(&coordinate).unwrap();
+        assert!((back.easting - gk.easting).abs() < 0.01);
+        assert!((back.northing - gk.northing).abs() < 0.01);
+        // 70 km east of the central meridian of zone 3 (9°E)
+        assert!((gk.dhdn().lng - 10.).abs() < 0.01);
+        assert_eq!(back.precision(), {
+            let meters = Datum::Dhdn.accuracy(Method::Helmert);
+            Precision::from_meters(meters, &back.dhdn())
+        });
+    }
+
+    #[test]
+    fn test_outside_germany() {
+        // Paris lies in zone 1, Oslo north of the accepted northings
+        for (lat, lng) in [(48.8566, 2.3522), (59.9139, 10.7522), (-33.9, 18.4)] {
+            let coordinate = Coordinate::new(lat, lng);
+            assert!(matches!(
+                GaussKrueger::from_coordinate(&coordinate),
+                Err(CoordinateError::InvalidValue)
+            ));
+        }
+        let munich = GaussKrueger::from_coordinate(&Coordinate::new(48.137, 11.575)).unwrap();
+        assert_eq!(munich.to_string().parse::<GaussKrueger>().unwrap(), munich);
+    }
+}
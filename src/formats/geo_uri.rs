@@ -0,0 +1,179 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use crate::{
+    formats::last_digit_precision, Altitude, AltitudeReference, Coordinate, CoordinateError,
+    Position, Precision,
+};
+
+/// A Geo URI as defined by RFC 5870 (e.g. geo:50.9413,6.9583,54;u=10)
+///
+/// The altitude is the height above the WGS 84 ellipsoid, so altitudes
+/// above mean sea level are dropped when writing a URI.
+///
+/// ```
+/// # use recoord::formats::geo_uri::GeoUri;
+/// # use recoord::AltitudeReference;
+/// let uri: GeoUri = "geo:50.9413,6.9583,54;u=10".parse().unwrap();
+/// assert_eq!(uri.position.coordinate.lat, 50.9413);
+/// let altitude = uri.position.altitude.unwrap();
+/// assert_eq!((altitude.meters, altitude.reference), (54., AltitudeReference::Ellipsoid));
+/// assert_eq!(uri.uncertainty, Some(10.));
+/// assert_eq!(uri.to_string(), "geo:50.9413,6.9583,54;u=10");
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoUri {
+    /// Position described by the URI
+    pub position: Position,
+    /// Uncertainty of the position in meters (`u` parameter)
+    pub uncertainty: Option<f64>,
+    /// Precision given by the written decimals
+    precision: Precision,
+}
+
+impl GeoUri {
+    /// Precision given by the number of decimals
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+}
+
+impl FromStr for GeoUri {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str = str.trim();
+        let scheme = str.get(..4).ok_or(CoordinateError::Malformed)?;
+        if !scheme.eq_ignore_ascii_case("geo:") {
+            return Err(CoordinateError::Malformed);
+        }
+        let mut parts = str[4..].split(';');
+        let fields: Vec<&str> = parts.next().unwrap_or_default().split(',').collect();
+        let values = fields
+            .iter()
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut uncertainty = None;
+        for parameter in parts {
+            let (name, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            if name.eq_ignore_ascii_case("crs") {
+                if !value.eq_ignore_ascii_case("wgs84") {
+                    return Err(CoordinateError::Unsupported);
+                }
+            } else if name.eq_ignore_ascii_case("u") {
+                uncertainty = Some(value.parse()?);
+            }
+        }
+
+        let (coordinate, altitude) = match values[..] {
+            [lat, lng] => (Coordinate::try_new(lat, lng)?, None),
+            [lat, lng, altitude] => (
+                Coordinate::try_new(lat, lng)?,
+                Some(Altitude::ellipsoidal(altitude)),
+            ),
+            _ => return Err(CoordinateError::Malformed),
+        };
+        Ok(GeoUri {
+            position: Position {
+                coordinate,
+                altitude,
+            },
+            uncertainty,
+            precision: Precision {
+                lat: last_digit_precision(fields[0]),
+                lng: last_digit_precision(fields[1]),
+            },
+        })
+    }
+}
+
+impl Display for GeoUri {
+    /// Write the URI, leaving out an altitude above mean sea level
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let coordinate = &self.position.coordinate;
+        write!(f, "geo:{},{}", coordinate.lat, coordinate.lng)?;
+        // Geo URIs can only carry ellipsoidal heights
+        if let Some(altitude) = self
+            .position
+            .altitude
+            .filter(|altitude| altitude.reference == AltitudeReference::Ellipsoid)
+        {
+            write!(f, ",{}", altitude.meters)?;
+        }
+        if let Some(uncertainty) = self.uncertainty {
+            write!(f, ";u={uncertainty}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<GeoUri> for Position {
+    fn from(uri: GeoUri) -> Self {
+        uri.position
+    }
+}
+
+impl From<GeoUri> for Coordinate {
+    fn from(uri: GeoUri) -> Self {
+        uri.position.coordinate
+    }
+}
+
+impl From<Position> for GeoUri {
+    fn from(position: Position) -> Self {
+        Self {
+            precision: Precision {
+                lat: last_digit_precision(&position.coordinate.lat.to_string()),
+                lng: last_digit_precision(&position.coordinate.lng.to_string()),
+            },
+            position,
+            uncertainty: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameters() {
+        let uri: GeoUri = "GEO:50.9413,6.9583;crs=WGS84;u=35.5;foo=bar"
+            .parse()
+            .unwrap();
+        assert_eq!(uri.position.coordinate, Coordinate::new(50.9413, 6.9583));
+        assert_eq!(uri.position.altitude, None);
+        assert_eq!(uri.uncertainty, Some(35.5));
+        assert_eq!(uri.to_string(), "geo:50.9413,6.9583;u=35.5");
+        assert!("geo:50.9413,6.9583;u=far".parse::<GeoUri>().is_err());
+    }
+
+    #[test]
+    fn test_unknown_crs() {
+        assert!(matches!(
+            "geo:50.9413,6.9583;crs=nad27".parse::<GeoUri>(),
+            Err(CoordinateError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_altitude() {
+        let uri: GeoUri = "geo:-33.8568,151.2153,-12.5".parse().unwrap();
+        assert_eq!(uri.position.altitude, Some(Altitude::ellipsoidal(-12.5)));
+        assert_eq!(uri.to_string(), "geo:-33.8568,151.2153,-12.5");
+        assert!(matches!(
+            "geo:1,2,3,4".parse::<GeoUri>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            "geo:91,0".parse::<GeoUri>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+
+        // Heights above mean sea level can't be written
+        let position = Position {
+            coordinate: Coordinate::new(50.9413, 6.9583),
+            altitude: Some(Altitude::above_sea_level(54.)),
+        };
+        assert_eq!(GeoUri::from(position).to_string(), "geo:50.9413,6.9583");
+    }
+}
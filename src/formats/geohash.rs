@@ -1,36 +1,81 @@
 use std::{fmt, fmt::Display, str::FromStr};
 
-use crate::{Coordinate, CoordinateError};
+use crate::{BoundingBox, Coordinate, CoordinateError, Precision};
+
+pub use super::Direction;
 
 /// The Geohash allows you to describe a rect on the globa.
-/// It's made up by the top left and bottom right corner of the bounding rect.
+/// It's made up by the bounding box of the rect.
 /// If you want to know the center, use the center() function.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Geohash {
-    /// Top left bounds of rect
-    bounding_top_left: Coordinate,
-    /// Top bottom right of rect
-    bounding_bottom_right: Coordinate,
+    /// Bounds of the rect
+    bounds: BoundingBox,
 }
 
 impl Geohash {
     /// Provides the center of the Geohash rect
     pub fn center(&self) -> Coordinate {
-        Coordinate {
-            lat: (self.bounding_top_left.lat + self.bounding_bottom_right.lat) / 2.,
-            lng: (self.bounding_top_left.lng + self.bounding_bottom_right.lng) / 2.,
-        }
+        self.bounds.center()
+    }
+
+    /// Bounds of the Geohash rect
+    ///
+    /// ```
+    /// # use recoord::formats::geohash::Geohash;
+    /// let hash: Geohash = "u".parse().unwrap();
+    /// let bbox = hash.bounding_box();
+    /// assert_eq!((bbox.south(), bbox.west(), bbox.north(), bbox.east()), (45., 0., 90., 45.));
+    /// ```
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.bounds
     }
 
-    /// Height of the bounding rect
+    /// Height of the bounding rect in degrees
     pub fn height(&self) -> f64 {
-        self.bounding_bottom_right.lat - self.bounding_top_left.lat
+        self.bounds.height()
     }
 
-    /// Width of the bounding rect
+    /// Width of the bounding rect in degrees
     pub fn width(&self) -> f64 {
-        self.bounding_bottom_right.lng - self.bounding_top_left.lng
+        self.bounds.width()
+    }
+
+    /// Precision of the center, which is half the size of the rect
+    pub fn precision(&self) -> Precision {
+        self.bounds.precision()
+    }
+
+    /// The adjacent rect of the same size in a direction
+    ///
+    /// Neighbours wrap around the antimeridian, but there are none beyond the poles.
+    ///
+    /// ```
+    /// # use recoord::formats::geohash::{Direction, Geohash};
+    /// let hash: Geohash = "ezs42".parse().unwrap();
+    /// let north = hash.neighbor(Direction::North).unwrap();
+    /// assert_eq!(north.hash_with_max_length(5), "ezs48");
+    /// ```
+    pub fn neighbor(&self, direction: Direction) -> Option<Geohash> {
+        let (d_lat, d_lng) = direction.offset();
+        let center = self.center();
+        let lat = center.lat + d_lat * self.height();
+        if !(-90.0..=90.0).contains(&lat) {
+            return None;
+        }
+        let center = Coordinate {
+            lat,
+            lng: center.lng + d_lng * self.width(),
+        };
+        Some(Geohash {
+            bounds: BoundingBox::from_center(&center, self.precision()),
+        })
+    }
+
+    /// All eight neighbours in the order of [`Direction::ALL`]
+    pub fn neighbors(&self) -> [Option<Geohash>; 8] {
+        Direction::ALL.map(|direction| self.neighbor(direction))
     }
 
     /// Encode a hash with a given precision in bits
@@ -149,14 +194,14 @@ impl Geohash {
     // }
 
     fn crosses_horizontal_chunks(&self) -> bool {
-        let left_cell = (self.bounding_top_left.lng / self.width()).floor() as usize;
-        let right_cell = (self.bounding_bottom_right.lng / self.width()).floor() as usize;
+        let left_cell = (self.bounds.west / self.width()).floor() as usize;
+        let right_cell = (self.bounds.east / self.width()).floor() as usize;
         left_cell == right_cell
     }
 
     fn crosses_vertical_chunks(&self) -> bool {
-        let top_cell = (self.bounding_top_left.lat / self.height()).floor() as usize;
-        let bottom_cell = (self.bounding_bottom_right.lat / self.height()).floor() as usize;
+        let top_cell = (self.bounds.north / self.height()).floor() as usize;
+        let bottom_cell = (self.bounds.south / self.height()).floor() as usize;
         top_cell == bottom_cell
     }
 }
@@ -164,14 +209,7 @@ impl Geohash {
 impl Default for Geohash {
     fn default() -> Self {
         Self {
-            bounding_top_left: Coordinate {
-                lat: 90.,
-                lng: -180.,
-            },
-            bounding_bottom_right: Coordinate {
-                lat: -90.,
-                lng: 180.,
-            },
+            bounds: BoundingBox::WORLD,
         }
     }
 }
@@ -179,8 +217,12 @@ impl Default for Geohash {
 impl From<Coordinate> for Geohash {
     fn from(coord: Coordinate) -> Self {
         Geohash {
-            bounding_top_left: coord.clone(),
-            bounding_bottom_right: coord,
+            bounds: BoundingBox {
+                south: coord.lat,
+                west: coord.lng,
+                north: coord.lat,
+                east: coord.lng,
+            },
         }
     }
 }
@@ -213,20 +255,18 @@ impl FromStr for Geohash {
                     for i in (0..=4).rev() {
                         let bit = (b32.0 >> i) & 0b1;
                         if (i + first_bit_lat) % 2 == 0 {
-                            let mid_lat =
-                                (res.bounding_top_left.lat + res.bounding_bottom_right.lat) / 2.;
+                            let mid_lat = (res.bounds.north + res.bounds.south) / 2.;
                             if bit == 0 {
-                                res.bounding_top_left.lat = mid_lat;
+                                res.bounds.north = mid_lat;
                             } else {
-                                res.bounding_bottom_right.lat = mid_lat;
+                                res.bounds.south = mid_lat;
                             }
                         } else {
-                            let mid_lng =
-                                (res.bounding_top_left.lng + res.bounding_bottom_right.lng) / 2.;
+                            let mid_lng = (res.bounds.west + res.bounds.east) / 2.;
                             if bit == 0 {
-                                res.bounding_bottom_right.lng = mid_lng;
+                                res.bounds.east = mid_lng;
                             } else {
-                                res.bounding_top_left.lng = mid_lng;
+                                res.bounds.west = mid_lng;
                             }
                         }
                     }
@@ -373,6 +413,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_geohash_neighbors() {
+        let hash = Geohash::from_str("ezs42").unwrap();
+        let neighbors: Vec<String> = hash
+            .neighbors()
+            .iter()
+            .map(|neighbor| neighbor.as_ref().unwrap().hash_with_max_length(5))
+            .collect();
+        assert_eq!(
+            neighbors,
+            ["ezs48", "ezs49", "ezs43", "ezs41", "ezs40", "ezefp", "ezefr", "ezefx"]
+        );
+    }
+
+    #[test]
+    fn test_geohash_neighbors_wrap() {
+        let hash = Geohash::from_str("0").unwrap();
+        let west = hash.neighbor(Direction::West).unwrap();
+        assert_eq!(west.hash_with_max_length(1), "p");
+        let north = hash.neighbor(Direction::North).unwrap();
+        assert_eq!(north.hash_with_max_length(1), "2");
+        assert!(hash.neighbor(Direction::South).is_none());
+        assert!(hash.neighbor(Direction::SouthWest).is_none());
+    }
+
     fn build_test_hash_with_length(length: usize, per_depth: Option<usize>) -> Vec<String> {
         match length {
             0 => vec![],
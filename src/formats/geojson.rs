@@ -0,0 +1,336 @@
+use std::{collections::HashMap, fmt, fmt::Display, str::FromStr};
+
+use serde_json::{json, value::RawValue, Map, Value};
+
+use crate::{
+    formats::last_digit_precision, geometry::Geometry, Altitude, AltitudeReference, BoundingBox,
+    Coordinate, CoordinateError, Position, Precision,
+};
+
+/// A GeoJSON geometry or feature (RFC 7946)
+///
+/// Positions are written as `[longitude, latitude]` or
+/// `[longitude, latitude, altitude]`, the altitude being the height above the
+/// WGS 84 ellipsoid. Altitudes above mean sea level aren't written, since
+/// GeoJSON has no way to mark them. Feature collections and geometry
+/// collections aren't supported.
+///
+/// ```
+/// # use recoord::{Coordinate, BoundingBox};
+/// # use recoord::formats::geojson::GeoJson;
+/// let point: GeoJson = r#"{"type": "Point", "coordinates": [6.9583, 50.9413]}"#.parse().unwrap();
+/// assert_eq!(Coordinate::try_from(point).unwrap(), Coordinate::new(50.9413, 6.9583));
+///
+/// let bbox = BoundingBox::new(50., 6., 51., 7.).unwrap();
+/// let feature = GeoJson::from(bbox).with_properties(Default::default());
+/// assert!(feature.to_string().starts_with(r#"{"geometry":{"coordinates":[[[6.0,50.0],"#));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GeoJson {
+    /// The geometry
+    pub geometry: Geometry,
+    /// Properties of a feature (None for plain geometries)
+    pub properties: Option<Map<String, Value>>,
+    /// Precision given by the written decimals of a parsed point
+    precision: Option<Precision>,
+}
+
+impl PartialEq for GeoJson {
+    /// Only the geometry and properties are compared, not how they were written
+    fn eq(&self, other: &Self) -> bool {
+        self.geometry == other.geometry && self.properties == other.properties
+    }
+}
+
+impl GeoJson {
+    /// A plain geometry
+    pub fn new(geometry: Geometry) -> Self {
+        Self {
+            geometry,
+            properties: None,
+            precision: None,
+        }
+    }
+
+    /// Turn the geometry into a feature with properties
+    pub fn with_properties(mut self, properties: Map<String, Value>) -> Self {
+        self.properties = Some(properties);
+        self
+    }
+
+    /// Read a geometry or feature from a JSON value
+    pub fn from_value(value: &Value) -> Result<Self, CoordinateError> {
+        match value.get("type").and_then(Value::as_str) {
+            Some("Feature") => {
+                let geometry = value.get("geometry").ok_or(CoordinateError::Malformed)?;
+                if geometry.is_null() {
+                    return Err(CoordinateError::Unsupported);
+                }
+                let properties = match value.get("properties") {
+                    None | Some(Value::Null) => Map::new(),
+                    Some(Value::Object(properties)) => properties.clone(),
+                    Some(_) => return Err(CoordinateError::Malformed),
+                };
+                Ok(GeoJson::new(read_geometry(geometry)?).with_properties(properties))
+            }
+            Some(_) => read_geometry(value).map(GeoJson::new),
+            None => Err(CoordinateError::Malformed),
+        }
+    }
+
+    /// JSON value of the geometry, or of the feature if there are properties
+    pub fn to_value(&self) -> Value {
+        let geometry = write_geometry(&self.geometry);
+        match &self.properties {
+            Some(properties) => json!({
+                "type": "Feature",
+                "geometry": geometry,
+                "properties": properties,
+            }),
+            None => geometry,
+        }
+    }
+
+    /// Precision given by the number of decimals of a point, or half the size of other geometries
+    pub fn precision(&self) -> Precision {
+        match &self.geometry {
+            Geometry::Point(position) => self.precision.unwrap_or_else(|| Precision {
+                lat: last_digit_precision(&position.coordinate.lat.to_string()),
+                lng: last_digit_precision(&position.coordinate.lng.to_string()),
+            }),
+            geometry => geometry
+                .bounding_box()
+                .map_or(Precision { lat: 0., lng: 0. }, |bbox| bbox.precision()),
+        }
+    }
+}
+
+/// Read a position array
+fn read_position(value: &Value) -> Result<Position, CoordinateError> {
+    let values = value.as_array().ok_or(CoordinateError::Malformed)?;
+    let number = |index: usize| {
+        values
+            .get(index)
+            .map(|value| value.as_f64().ok_or(CoordinateError::Malformed))
+            .transpose()
+    };
+    let (Some(lng), Some(lat)) = (number(0)?, number(1)?) else {
+        return Err(CoordinateError::Malformed);
+    };
+    Ok(Position {
+        coordinate: Coordinate::try_new(lat, lng)?,
+        altitude: number(2)?.map(Altitude::ellipsoidal),
+    })
+}
+
+/// Read an array with `read` applied to each element
+fn read_array<T>(
+    value: &Value,
+    read: impl Fn(&Value) -> Result<T, CoordinateError>,
+) -> Result<Vec<T>, CoordinateError> {
+    value
+        .as_array()
+        .ok_or(CoordinateError::Malformed)?
+        .iter()
+        .map(read)
+        .collect()
+}
+
+/// Read a geometry object
+fn read_geometry(value: &Value) -> Result<Geometry, CoordinateError> {
+    let coordinates = || value.get("coordinates").ok_or(CoordinateError::Malformed);
+    let positions = |value: &Value| read_array(value, read_position);
+    let lines = |value: &Value| read_array(value, positions);
+    Ok(match value.get("type").and_then(Value::as_str) {
+        Some("Point") => Geometry::Point(read_position(coordinates()?)?),
+        Some("MultiPoint") => Geometry::MultiPoint(positions(coordinates()?)?),
+        Some("LineString") => Geometry::LineString(positions(coordinates()?)?),
+        Some("MultiLineString") => Geometry::MultiLineString(lines(coordinates()?)?),
+        Some("Polygon") => Geometry::Polygon(lines(coordinates()?)?),
+        Some("MultiPolygon") => Geometry::MultiPolygon(read_array(coordinates()?, lines)?),
+        Some("GeometryCollection" | "FeatureCollection") => {
+            return Err(CoordinateError::Unsupported)
+        }
+        _ => return Err(CoordinateError::Malformed),
+    })
+}
+
+/// Position array of a position
+fn write_position(position: &Position) -> Value {
+    let coordinate = &position.coordinate;
+    match position.altitude {
+        Some(Altitude {
+            meters,
+            reference: AltitudeReference::Ellipsoid,
+        }) => json!([coordinate.lng, coordinate.lat, meters]),
+        _ => json!([coordinate.lng, coordinate.lat]),
+    }
+}
+
+/// Geometry object of a geometry
+fn write_geometry(geometry: &Geometry) -> Value {
+    let positions = |positions: &[Position]| positions.iter().map(write_position).collect();
+    let lines =
+        |lines: &[Vec<Position>]| -> Value { lines.iter().map(|line| positions(line)).collect() };
+    let coordinates: Value = match geometry {
+        Geometry::Point(position) => write_position(position),
+        Geometry::MultiPoint(points) | Geometry::LineString(points) => positions(points),
+        Geometry::MultiLineString(rings) | Geometry::Polygon(rings) => lines(rings),
+        Geometry::MultiPolygon(polygons) => polygons.iter().map(|polygon| lines(polygon)).collect(),
+    };
+    json!({ "type": geometry.kind(), "coordinates": coordinates })
+}
+
+impl FromStr for GeoJson {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str = str.trim();
+        if !str.starts_with('{') {
+            return Err(CoordinateError::Malformed);
+        }
+        let mut json = GeoJson::from_value(&serde_json::from_str(str)?)?;
+        if let Geometry::Point(_) = json.geometry {
+            json.precision = point_precision(str);
+        }
+        Ok(json)
+    }
+}
+
+/// Precision given by the written decimals of the coordinates of a point
+///
+/// Parsed values lose trailing zeros, so the numbers are taken from the text.
+fn point_precision(str: &str) -> Option<Precision> {
+    type Object<'a> = HashMap<&'a str, &'a RawValue>;
+    let object: Object = serde_json::from_str(str).ok()?;
+    let geometry: Object = match object.get("geometry") {
+        Some(geometry) => serde_json::from_str(geometry.get()).ok()?,
+        None => object,
+    };
+    let coordinates: Vec<&RawValue> =
+        serde_json::from_str(geometry.get("coordinates")?.get()).ok()?;
+    Some(Precision {
+        lat: last_digit_precision(coordinates.get(1)?.get()),
+        lng: last_digit_precision(coordinates.first()?.get()),
+    })
+}
+
+impl Display for GeoJson {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
+
+impl From<Geometry> for GeoJson {
+    fn from(geometry: Geometry) -> Self {
+        Self::new(geometry)
+    }
+}
+
+impl From<Coordinate> for GeoJson {
+    fn from(coordinate: Coordinate) -> Self {
+        Self::new(coordinate.into())
+    }
+}
+
+impl From<Position> for GeoJson {
+    fn from(position: Position) -> Self {
+        Self::new(position.into())
+    }
+}
+
+impl From<BoundingBox> for GeoJson {
+    fn from(bounds: BoundingBox) -> Self {
+        Self::new(bounds.into())
+    }
+}
+
+#[cfg(feature = "format_geohash")]
+impl From<crate::formats::geohash::Geohash> for GeoJson {
+    /// Polygon of the geohash cell
+    fn from(hash: crate::formats::geohash::Geohash) -> Self {
+        Self::new(hash.bounding_box().into())
+    }
+}
+
+impl TryFrom<GeoJson> for Coordinate {
+    type Error = CoordinateError;
+
+    /// Only points can be converted
+    fn try_from(json: GeoJson) -> Result<Self, Self::Error> {
+        json.geometry.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature() {
+        let json = r#"{
+            "type": "Feature",
+            "geometry": {"type": "LineString", "coordinates": [[6.96, 50.94, 54.0], [13.4, 52.5]]},
+            "properties": {"name": "Cologne - Berlin"}
+        }"#;
+        let feature: GeoJson = json.parse().unwrap();
+        assert_eq!(
+            feature.geometry.coordinates(),
+            [Coordinate::new(50.94, 6.96), Coordinate::new(52.5, 13.4)]
+        );
+        assert_eq!(
+            feature.geometry.positions()[0].altitude,
+            Some(Altitude::ellipsoidal(54.))
+        );
+        assert_eq!(
+            feature.properties.as_ref().unwrap()["name"],
+            "Cologne - Berlin"
+        );
+        assert_eq!(feature.to_string().parse::<GeoJson>().unwrap(), feature);
+    }
+
+    #[test]
+    fn test_polygon_round_trip() {
+        let json =
+            r#"{"type":"Polygon","coordinates":[[[0.0,0.0],[1.0,0.0],[1.0,1.0],[0.0,0.0]]]}"#;
+        let polygon: GeoJson = json.parse().unwrap();
+        assert_eq!(polygon.geometry.kind(), "Polygon");
+        let value: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(polygon.to_value(), value);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for (json, unsupported) in [
+            (r#"{"type": "Point", "coordinates": [1.0]}"#, false),
+            (r#"{"type": "Point", "coordinates": ["1", "2"]}"#, false),
+            (r#"{"type": "Circle", "coordinates": [1.0, 2.0]}"#, false),
+            (r#"{"type": "FeatureCollection", "features": []}"#, true),
+            (r#"{"type": "Feature", "geometry": null}"#, true),
+        ] {
+            let result = json.parse::<GeoJson>();
+            assert_eq!(
+                matches!(result, Err(CoordinateError::Unsupported)),
+                unsupported,
+                "{json}"
+            );
+            assert!(result.is_err());
+        }
+        assert!(matches!(
+            r#"{"type": "Point", "coordinates": [2.0, 100.0]}"#.parse::<GeoJson>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!(matches!(
+            "{".parse::<GeoJson>(),
+            Err(CoordinateError::JsonError(_))
+        ));
+    }
+
+    #[cfg(feature = "format_dd")]
+    #[test]
+    fn test_parse_coordinate() {
+        let coordinate: Coordinate = r#"{"type": "Point", "coordinates": [6.96, 50.94]}"#
+            .parse()
+            .unwrap();
+        assert_eq!(coordinate, Coordinate::new(50.94, 6.96));
+    }
+}
@@ -0,0 +1,447 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use quick_xml::{
+    escape::{escape, resolve_predefined_entity},
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{
+    geometry::Geometry, Altitude, AltitudeReference, Coordinate, CoordinateError, Position,
+};
+
+/// A GPX 1.1 document with waypoints, routes and tracks
+///
+/// Only the positions, elevations, timestamps and names are kept. Elevations
+/// are read as altitudes above mean sea level, and only those are written.
+/// Timestamps are kept as written (e.g. "2024-05-01T08:30:00Z").
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::gpx::Gpx;
+/// let gpx: Gpx = r#"<gpx version="1.1" creator="eTrex">
+///   <trk><name>Morning walk</name><trkseg>
+///     <trkpt lat="50.9413" lon="6.9583"><ele>54</ele><time>2024-05-01T08:30:00Z</time></trkpt>
+///     <trkpt lat="50.9421" lon="6.9571"/>
+///   </trkseg></trk>
+/// </gpx>"#.parse().unwrap();
+/// let track = &gpx.tracks[0];
+/// assert_eq!(track.name.as_deref(), Some("Morning walk"));
+/// assert_eq!(track.coordinates()[1], Coordinate::new(50.9421, 6.9571));
+/// assert_eq!(track.segments[0][0].time.as_deref(), Some("2024-05-01T08:30:00Z"));
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gpx {
+    /// Single points of interest
+    pub waypoints: Vec<Waypoint>,
+    /// Planned routes
+    pub routes: Vec<Route>,
+    /// Recorded tracks
+    pub tracks: Vec<Track>,
+}
+
+/// A waypoint, or a point of a route or track
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waypoint {
+    /// Position with the elevation as altitude
+    pub position: Position,
+    /// Timestamp in ISO 8601 format
+    pub time: Option<String>,
+    /// Name of the point
+    pub name: Option<String>,
+}
+
+/// A route of ordered points
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Route {
+    /// Name of the route
+    pub name: Option<String>,
+    /// Points of the route
+    pub points: Vec<Waypoint>,
+}
+
+/// A track made up of segments of recorded points
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Track {
+    /// Name of the track
+    pub name: Option<String>,
+    /// Segments of continuous recording
+    pub segments: Vec<Vec<Waypoint>>,
+}
+
+impl Waypoint {
+    /// A waypoint without timestamp and name
+    pub fn new(position: impl Into<Position>) -> Self {
+        Waypoint {
+            position: position.into(),
+            time: None,
+            name: None,
+        }
+    }
+
+    /// Elevation in meters above mean sea level
+    pub fn elevation(&self) -> Option<f64> {
+        match self.position.altitude {
+            Some(Altitude {
+                meters,
+                reference: AltitudeReference::MeanSeaLevel,
+            }) => Some(meters),
+            _ => None,
+        }
+    }
+}
+
+impl Route {
+    /// Coordinates of the points
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        self.points
+            .iter()
+            .map(|point| point.position.coordinate.clone())
+            .collect()
+    }
+}
+
+impl Track {
+    /// Coordinates of the points of all segments
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        self.segments
+            .iter()
+            .flatten()
+            .map(|point| point.position.coordinate.clone())
+            .collect()
+    }
+}
+
+/// Waypoint with the position given by the attributes of a point element
+fn read_point(start: &BytesStart) -> Result<Waypoint, CoordinateError> {
+    let value = |name: &str| -> Result<f64, CoordinateError> {
+        let attribute = start
+            .try_get_attribute(name)
+            .map_err(quick_xml::Error::from)?
+            .ok_or(CoordinateError::Malformed)?;
+        attribute
+            .value
+            .trim()
+            .parse()
+            .map_err(|_| CoordinateError::Malformed)
+    };
+    let coordinate = Coordinate::try_new(value("lat")?, value("lon")?)?;
+    Ok(Waypoint::new(coordinate))
+}
+
+/// Read the text up to the end of the element just started
+///
+/// Text, CDATA sections and entity references are joined as in KML.
+fn read_text(reader: &mut Reader<&[u8]>) -> Result<String, CoordinateError> {
+    let mut text = String::new();
+    // Number of open child elements
+    let mut depth = 0usize;
+    loop {
+        match reader.read_event()? {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => match depth.checked_sub(1) {
+                Some(parent) => depth = parent,
+                None => return Ok(text),
+            },
+            Event::Text(content) => text.push_str(&content.xml10_content()),
+            Event::CData(data) => text.push_str(&data),
+            Event::GeneralRef(reference) => match reference.resolve_char_ref()? {
+                Some(char) => text.push(char),
+                None => text.push_str(
+                    resolve_predefined_entity(&reference).ok_or(CoordinateError::Malformed)?,
+                ),
+            },
+            Event::Eof => return Err(CoordinateError::Malformed),
+            _ => {}
+        }
+    }
+}
+
+impl FromStr for Gpx {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut reader = Reader::from_str(str);
+        let mut gpx = Gpx::default();
+        // Local names of the open elements
+        let mut open: Vec<String> = vec![];
+        let mut point: Option<Waypoint> = None;
+        let mut root = false;
+        loop {
+            match reader.read_event()? {
+                Event::Start(start) => {
+                    let name = start.local_name().as_ref().to_owned();
+                    match name.as_str() {
+                        "gpx" => root = true,
+                        "wpt" | "rtept" | "trkpt" => point = Some(read_point(&start)?),
+                        "rte" => gpx.routes.push(Route::default()),
+                        "trk" => gpx.tracks.push(Track::default()),
+                        "trkseg" => {
+                            if let Some(track) = gpx.tracks.last_mut() {
+                                track.segments.push(vec![]);
+                            }
+                        }
+                        "ele" | "time" | "name" => {
+                            let text = read_text(&mut reader)?.trim().to_owned();
+                            let parent = open.last().map(String::as_str);
+                            match (name.as_str(), parent, point.as_mut()) {
+                                ("ele", Some("wpt" | "rtept" | "trkpt"), Some(point)) => {
+                                    let meters =
+                                        text.parse().map_err(|_| CoordinateError::Malformed)?;
+                                    point.position.altitude =
+                                        Some(Altitude::above_sea_level(meters));
+                                }
+                                ("time", Some("wpt" | "rtept" | "trkpt"), Some(point)) => {
+                                    point.time = Some(text)
+                                }
+                                ("name", Some("wpt" | "rtept" | "trkpt"), Some(point)) => {
+                                    point.name = Some(text)
+                                }
+                                ("name", Some("rte"), _) => {
+                                    if let Some(route) = gpx.routes.last_mut() {
+                                        route.name = Some(text);
+                                    }
+                                }
+                                ("name", Some("trk"), _) => {
+                                    if let Some(track) = gpx.tracks.last_mut() {
+                                        track.name = Some(text);
+                                    }
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    open.push(name);
+                }
+                Event::Empty(start) => match start.local_name().as_ref() {
+                    name @ ("wpt" | "rtept" | "trkpt") => {
+                        let point = read_point(&start)?;
+                        gpx.add_point(name, point);
+                    }
+                    "gpx" => root = true,
+                    _ => {}
+                },
+                Event::End(_) => {
+                    let name = open.pop().unwrap_or_default();
+                    if matches!(name.as_str(), "wpt" | "rtept" | "trkpt") {
+                        if let Some(point) = point.take() {
+                            gpx.add_point(&name, point);
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        if !root {
+            return Err(CoordinateError::Malformed);
+        }
+        Ok(gpx)
+    }
+}
+
+impl Gpx {
+    /// Add a point read from an element with the given name
+    fn add_point(&mut self, element: &str, point: Waypoint) {
+        match element {
+            "wpt" => self.waypoints.push(point),
+            "rtept" => {
+                if let Some(route) = self.routes.last_mut() {
+                    route.points.push(point);
+                }
+            }
+            _ => {
+                if let Some(segment) = self
+                    .tracks
+                    .last_mut()
+                    .and_then(|track| track.segments.last_mut())
+                {
+                    segment.push(point);
+                }
+            }
+        }
+    }
+}
+
+/// Write a point element with the given name and indentation
+fn write_point(
+    f: &mut fmt::Formatter,
+    element: &str,
+    point: &Waypoint,
+    indent: &str,
+) -> fmt::Result {
+    let coordinate = &point.position.coordinate;
+    let attributes = format!(r#"lat="{}" lon="{}""#, coordinate.lat, coordinate.lng);
+    if point.elevation().is_none() && point.time.is_none() && point.name.is_none() {
+        return writeln!(f, "{indent}<{element} {attributes}/>");
+    }
+    writeln!(f, "{indent}<{element} {attributes}>")?;
+    if let Some(meters) = point.elevation() {
+        writeln!(f, "{indent}  <ele>{meters}</ele>")?;
+    }
+    if let Some(time) = &point.time {
+        writeln!(f, "{indent}  <time>{}</time>", escape(time))?;
+    }
+    if let Some(name) = &point.name {
+        writeln!(f, "{indent}  <name>{}</name>", escape(name))?;
+    }
+    writeln!(f, "{indent}</{element}>")
+}
+
+impl Display for Gpx {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<gpx version="1.1" creator="recoord" xmlns="http://www.topografix.com/GPX/1/1">"#
+        )?;
+        for waypoint in &self.waypoints {
+            write_point(f, "wpt", waypoint, "  ")?;
+        }
+        for route in &self.routes {
+            writeln!(f, "  <rte>")?;
+            if let Some(name) = &route.name {
+                writeln!(f, "    <name>{}</name>", escape(name))?;
+            }
+            for point in &route.points {
+                write_point(f, "rtept", point, "    ")?;
+            }
+            writeln!(f, "  </rte>")?;
+        }
+        for track in &self.tracks {
+            writeln!(f, "  <trk>")?;
+            if let Some(name) = &track.name {
+                writeln!(f, "    <name>{}</name>", escape(name))?;
+            }
+            for segment in &track.segments {
+                writeln!(f, "    <trkseg>")?;
+                for point in segment {
+                    write_point(f, "trkpt", point, "      ")?;
+                }
+                writeln!(f, "    </trkseg>")?;
+            }
+            writeln!(f, "  </trk>")?;
+        }
+        write!(f, "</gpx>")
+    }
+}
+
+impl From<Waypoint> for Position {
+    fn from(waypoint: Waypoint) -> Self {
+        waypoint.position
+    }
+}
+
+impl From<Waypoint> for Coordinate {
+    fn from(waypoint: Waypoint) -> Self {
+        waypoint.position.coordinate
+    }
+}
+
+impl From<Route> for Geometry {
+    /// Line of the route
+    fn from(route: Route) -> Self {
+        Geometry::LineString(route.points.into_iter().map(Position::from).collect())
+    }
+}
+
+impl From<Track> for Geometry {
+    /// Lines of the segments
+    fn from(track: Track) -> Self {
+        Geometry::MultiLineString(
+            track
+                .segments
+                .into_iter()
+                .map(|segment| segment.into_iter().map(Position::from).collect())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Export of a Garmin eTrex, shortened
+    const GARMIN: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no" ?>
+<gpx xmlns="http://www.topografix.com/GPX/1/1" xmlns:gpxx="http://www.garmin.com/xmlschemas/GpxExtensions/v3" creator="eTrex 32x" version="1.1">
+  <metadata>
+    <link href="http://www.garmin.com"><text>Garmin International</text></link>
+    <time>2024-05-01T09:12:44Z</time>
+  </metadata>
+  <wpt lat="47.377900" lon="8.540300">
+    <ele>408.52</ele>
+    <time>2024-05-01T08:02:11Z</time>
+    <name>Z&#252;rich HB &amp; Bahnhofplatz</name>
+    <sym>Flag, Blue</sym>
+  </wpt>
+  <rte>
+    <name>Uetliberg</name>
+    <rtept lat="47.377900" lon="8.540300"/>
+    <rtept lat="47.349400" lon="8.491200"><name><![CDATA[Top & Café]]></name></rtept>
+  </rte>
+  <trk>
+    <name>ACTIVE LOG</name>
+    <extensions><gpxx:TrackExtension><gpxx:DisplayColor>Cyan</gpxx:DisplayColor></gpxx:TrackExtension></extensions>
+    <trkseg>
+      <trkpt lat="47.377912" lon="8.540287"><ele>408.0</ele><time>2024-05-01T08:02:15Z</time></trkpt>
+      <trkpt lat="47.377864" lon="8.540102"><ele>409.5</ele><time>2024-05-01T08:02:20Z</time></trkpt>
+    </trkseg>
+    <trkseg>
+      <trkpt lat="47.349400" lon="8.491200"><ele>869.0</ele><time>2024-05-01T09:10:02Z</time></trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+    #[test]
+    fn test_garmin() {
+        let gpx: Gpx = GARMIN.parse().unwrap();
+        let waypoint = &gpx.waypoints[0];
+        assert_eq!(waypoint.name.as_deref(), Some("Zürich HB & Bahnhofplatz"));
+        assert_eq!(waypoint.elevation(), Some(408.52));
+        assert_eq!(waypoint.time.as_deref(), Some("2024-05-01T08:02:11Z"));
+        assert_eq!(gpx.routes[0].name.as_deref(), Some("Uetliberg"));
+        assert_eq!(gpx.routes[0].points[1].name.as_deref(), Some("Top & Café"));
+        let track = &gpx.tracks[0];
+        assert_eq!(track.name.as_deref(), Some("ACTIVE LOG"));
+        assert_eq!(track.segments.len(), 2);
+        assert_eq!(track.coordinates().len(), 3);
+        assert_eq!(track.segments[1][0].elevation(), Some(869.));
+        assert_eq!(Geometry::from(track.clone()).kind(), "MultiLineString");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let gpx: Gpx = GARMIN.parse().unwrap();
+        let written = gpx.to_string();
+        assert!(written.contains("<name>Zürich HB &amp; Bahnhofplatz</name>"));
+        assert_eq!(written.parse::<Gpx>().unwrap(), gpx);
+        let empty = Gpx::default().to_string();
+        assert_eq!(empty.parse::<Gpx>().unwrap(), Gpx::default());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            "<kml></kml>".parse::<Gpx>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            r#"<gpx><wpt lat="47.3"/></gpx>"#.parse::<Gpx>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            r#"<gpx><wpt lat="147.3" lon="8.5"/></gpx>"#.parse::<Gpx>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!(matches!(
+            r#"<gpx><wpt lat="47.3" lon="8.5"></gpx>"#.parse::<Gpx>(),
+            Err(CoordinateError::XmlError(_))
+        ));
+    }
+}
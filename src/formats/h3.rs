@@ -0,0 +1,253 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use h3o::{CellIndex, LatLng, Resolution};
+
+use crate::{geometry::Geometry, BoundingBox, Coordinate, CoordinateError, Position, Precision};
+
+/// Finest resolution of H3 cells
+pub const MAX_RESOLUTION: u8 = 15;
+
+/// A cell of Uber's hexagonal H3 grid (e.g. "89283082e73ffff")
+///
+/// Cells are hexagons, apart from twelve pentagons at each resolution. Each
+/// cell has seven children at the next resolution, whose area approximately
+/// but not exactly covers their parent. The index is written as 15
+/// hexadecimal digits.
+///
+/// H3 cells aren't tried when parsing a [`Coordinate`], since their indices
+/// can't be told apart from geohashes.
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::h3::H3Cell;
+/// let cell = H3Cell::at(&Coordinate::new(37.769377, -122.388903), 9).unwrap();
+/// assert_eq!(cell.to_string(), "89283082e73ffff");
+/// assert_eq!(cell.index(), 0x89283082e73ffff);
+/// assert_eq!(cell.parent().unwrap().resolution(), 8);
+/// assert_eq!(cell.neighbors().len(), 6);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct H3Cell {
+    /// Index of the cell
+    cell: CellIndex,
+}
+
+/// Resolution of H3 with the crate's error
+fn resolution(resolution: u8) -> Result<Resolution, CoordinateError> {
+    Resolution::try_from(resolution).map_err(|_| CoordinateError::InvalidValue)
+}
+
+impl H3Cell {
+    /// Cell at a resolution (0 to 15) containing a coordinate
+    pub fn at(coordinate: &Coordinate, resolution: u8) -> Result<Self, CoordinateError> {
+        let latlng = LatLng::new(coordinate.lat, coordinate.lng)
+            .map_err(|_| CoordinateError::InvalidValue)?;
+        Ok(H3Cell {
+            cell: latlng.to_cell(self::resolution(resolution)?),
+        })
+    }
+
+    /// Resolution of the cell, from 0 (coarsest) to 15
+    pub fn resolution(&self) -> u8 {
+        self.cell.resolution().into()
+    }
+
+    /// 64 bit index of the cell
+    pub fn index(&self) -> u64 {
+        self.cell.into()
+    }
+
+    /// Whether the cell is one of the twelve pentagons of its resolution
+    pub fn is_pentagon(&self) -> bool {
+        self.cell.is_pentagon()
+    }
+
+    /// Center of the cell
+    pub fn center(&self) -> Coordinate {
+        let center = LatLng::from(self.cell);
+        Coordinate::new(center.lat(), center.lng())
+    }
+
+    /// Corners of the cell, counterclockwise
+    ///
+    /// Cells crossing an edge of an icosahedron face have additional
+    /// vertices, so there can be up to ten.
+    pub fn boundary(&self) -> Vec<Coordinate> {
+        self.cell
+            .boundary()
+            .iter()
+            .map(|corner| Coordinate::new(corner.lat(), corner.lng()))
+            .collect()
+    }
+
+    /// Smallest box containing the corners of the cell
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_points(self.boundary()).expect("cells have corners")
+    }
+
+    /// Precision of the center, half the size of the bounding box
+    pub fn precision(&self) -> Precision {
+        self.bounding_box().precision()
+    }
+
+    /// Cell at the next coarser resolution containing the center (None at resolution 0)
+    pub fn parent(&self) -> Option<H3Cell> {
+        let resolution = self.cell.resolution().pred()?;
+        self.cell.parent(resolution).map(|cell| H3Cell { cell })
+    }
+
+    /// Cells at the next finer resolution (seven, or six for pentagons; empty at resolution 15)
+    pub fn children(&self) -> Vec<H3Cell> {
+        match self.cell.resolution().succ() {
+            Some(resolution) => self
+                .cell
+                .children(resolution)
+                .map(|cell| H3Cell { cell })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Cells within `k` steps, including the cell itself (the "k-ring")
+    pub fn disk(&self, k: u32) -> Vec<H3Cell> {
+        self.cell
+            .grid_disk::<Vec<_>>(k)
+            .into_iter()
+            .map(|cell| H3Cell { cell })
+            .collect()
+    }
+
+    /// Adjacent cells (six, or five for pentagons)
+    pub fn neighbors(&self) -> Vec<H3Cell> {
+        self.disk(1)
+            .into_iter()
+            .filter(|cell| cell != self)
+            .collect()
+    }
+}
+
+impl Display for H3Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.cell)
+    }
+}
+
+impl FromStr for H3Cell {
+    type Err = CoordinateError;
+
+    /// Read the hexadecimal index, with or without "0x"
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str = str.trim();
+        let hex = str
+            .strip_prefix("0x")
+            .or_else(|| str.strip_prefix("0X"))
+            .unwrap_or(str);
+        if hex.len() != 15 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(CoordinateError::Malformed);
+        }
+        let index = u64::from_str_radix(hex, 16).map_err(|_| CoordinateError::Malformed)?;
+        H3Cell::try_from(index)
+    }
+}
+
+impl TryFrom<u64> for H3Cell {
+    type Error = CoordinateError;
+
+    /// Only valid cell indices are accepted
+    fn try_from(index: u64) -> Result<Self, Self::Error> {
+        CellIndex::try_from(index)
+            .map(|cell| H3Cell { cell })
+            .map_err(|_| CoordinateError::InvalidValue)
+    }
+}
+
+impl From<H3Cell> for u64 {
+    fn from(cell: H3Cell) -> Self {
+        cell.index()
+    }
+}
+
+impl From<H3Cell> for Coordinate {
+    /// Center of the cell
+    fn from(cell: H3Cell) -> Self {
+        cell.center()
+    }
+}
+
+impl From<H3Cell> for Geometry {
+    /// Polygon of the cell
+    fn from(cell: H3Cell) -> Self {
+        let mut ring: Vec<Position> = cell.boundary().into_iter().map(Position::new).collect();
+        ring.push(ring[0].clone());
+        Geometry::Polygon(vec![ring])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_vectors() {
+        // Examples of the H3 documentation
+        let cell = H3Cell::at(&Coordinate::new(37.3615593, -122.0553238), 7).unwrap();
+        assert_eq!(cell.to_string(), "87283472bffffff");
+        let cell: H3Cell = "85283473fffffff".parse().unwrap();
+        let center = cell.center();
+        assert!((center.lat - 37.345793375368).abs() < 1e-9);
+        assert!((center.lng - -121.976375972551).abs() < 1e-9);
+        assert_eq!(cell.boundary().len(), 6);
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let cell = H3Cell::at(&Coordinate::new(50.9413, 6.9583), 9).unwrap();
+        let parent = cell.parent().unwrap();
+        assert!(parent.children().contains(&cell));
+        assert_eq!(parent.children().len(), 7);
+        assert_eq!(cell.disk(2).len(), 19);
+        assert!(cell
+            .neighbors()
+            .iter()
+            .all(|neighbor| neighbor.resolution() == 9));
+        let base = H3Cell::at(&Coordinate::new(50.9413, 6.9583), 0).unwrap();
+        assert_eq!(base.parent(), None);
+        let finest = H3Cell::at(&Coordinate::new(50.9413, 6.9583), MAX_RESOLUTION).unwrap();
+        assert!(finest.children().is_empty());
+    }
+
+    #[test]
+    fn test_pentagon() {
+        // Base cell 4 is a pentagon
+        let pentagon = H3Cell::try_from(0x8009fffffffffff).unwrap();
+        assert!(pentagon.is_pentagon());
+        assert_eq!(pentagon.neighbors().len(), 5);
+        assert_eq!(pentagon.children().len(), 6);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            "89283082e73fff".parse::<H3Cell>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            "89283082e73fffg".parse::<H3Cell>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            "ffffffffffffffff".parse::<H3Cell>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            H3Cell::try_from(0),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!(H3Cell::at(&Coordinate::new(0., 0.), 16).is_err());
+        assert_eq!(
+            "0x89283082e73ffff".parse::<H3Cell>().unwrap().index(),
+            0x89283082e73ffff
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use crate::{
+    formats::last_digit_precision, Altitude, AltitudeReference, Coordinate, CoordinateError,
+    Position, Precision,
+};
+use regex::Regex;
+
+/// A point in the ISO 6709 string representation (e.g. +50.9413+006.9583+54CRSWGS_84/)
+///
+/// Latitude and longitude may be given as degrees (±DD.D / ±DDD.D),
+/// degrees and minutes (±DDMM.M / ±DDDMM.M) or degrees, minutes and
+/// seconds (±DDMMSS.S / ±DDDMMSS.S).
+/// An altitude is read as ellipsoidal height for the CRS `WGS_84` or
+/// `EPSG:4979` and as height above mean sea level otherwise.
+///
+/// ```
+/// # use recoord::formats::iso6709::Iso6709;
+/// # use recoord::AltitudeReference;
+/// let point: Iso6709 = "+404015.5-0735913.1+10.5/".parse().unwrap();
+/// let coordinate = &point.position.coordinate;
+/// assert!((coordinate.lat - 40.670972).abs() < 1e-6);
+/// assert!((coordinate.lng + 73.986972).abs() < 1e-6);
+/// let altitude = point.position.altitude.unwrap();
+/// assert_eq!((altitude.meters, altitude.reference), (10.5, AltitudeReference::MeanSeaLevel));
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Iso6709 {
+    /// Position described by the string
+    pub position: Position,
+    /// Coordinate reference system (e.g. "WGS_84"), if given
+    pub crs: Option<String>,
+    /// Precision given by the written digits
+    precision: Precision,
+}
+
+impl Iso6709 {
+    /// Precision given by the written digits (e.g. ±0.5" for ±DDMMSS)
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+}
+
+/// Parse a signed ISO 6709 angle with `degree_digits` digits for the degrees
+///
+/// Returns the angle and its precision in degrees.
+fn parse_angle(str: &str, degree_digits: usize) -> Result<(f64, f64), CoordinateError> {
+    let (sign, digits) = str.split_at(1);
+    let sign = if sign == "-" { -1. } else { 1. };
+    let integer_digits = digits.find('.').unwrap_or(digits.len());
+    let (degrees, minutes, seconds, unit): (f64, f64, f64, f64) =
+        match integer_digits.checked_sub(degree_digits) {
+            Some(0) => (digits.parse()?, 0., 0., 1.),
+            Some(2) => (
+                digits[..degree_digits].parse()?,
+                digits[degree_digits..].parse()?,
+                0.,
+                60.,
+            ),
+            Some(4) => (
+                digits[..degree_digits].parse()?,
+                digits[degree_digits..degree_digits + 2].parse()?,
+                digits[degree_digits + 2..].parse()?,
+                60. * 60.,
+            ),
+            _ => return Err(CoordinateError::Malformed),
+        };
+    if minutes >= 60. || seconds >= 60. {
+        return Err(CoordinateError::InvalidValue);
+    }
+    let fraction_digits = digits.len() - (integer_digits + 1).min(digits.len());
+    let precision = 0.5 * 10f64.powi(-(fraction_digits as i32)) / unit;
+    let angle: f64 = degrees + minutes / 60. + seconds / 60. / 60.;
+    Ok((sign * angle, precision))
+}
+
+impl FromStr for Iso6709 {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let iso_re = Regex::new(
+            r"^(?P<lat>[+-]\d+(\.\d+)?)(?P<lng>[+-]\d+(\.\d+)?)(?P<alt>[+-]\d+(\.\d+)?)?(CRS(?P<crs>[^/]+))?/?$",
+        )
+        .unwrap();
+        let captures = iso_re
+            .captures(str.trim())
+            .ok_or(CoordinateError::Malformed)?;
+        let (Some(lat), Some(lng)) = (captures.name("lat"), captures.name("lng")) else {
+            return Err(CoordinateError::Malformed);
+        };
+        let (lat, lat_precision) = parse_angle(lat.as_str(), 2)?;
+        let (lng, lng_precision) = parse_angle(lng.as_str(), 3)?;
+        let crs = captures.name("crs").map(|crs| crs.as_str().to_string());
+        let reference = match crs.as_deref() {
+            Some("WGS_84" | "EPSG:4979") => AltitudeReference::Ellipsoid,
+            _ => AltitudeReference::MeanSeaLevel,
+        };
+        let altitude = captures
+            .name("alt")
+            .map(|altitude| altitude.as_str().parse())
+            .transpose()?
+            .map(|meters| Altitude { meters, reference });
+
+        Ok(Iso6709 {
+            position: Position {
+                coordinate: Coordinate::try_new(lat, lng)?,
+                altitude,
+            },
+            crs,
+            precision: Precision {
+                lat: lat_precision,
+                lng: lng_precision,
+            },
+        })
+    }
+}
+
+impl Display for Iso6709 {
+    /// Write the position in degrees with 6 decimals
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let coordinate = &self.position.coordinate;
+        write!(f, "{:+010.6}{:+011.6}", coordinate.lat, coordinate.lng)?;
+        if let Some(altitude) = self.position.altitude {
+            write!(f, "{:+}", altitude.meters)?;
+        }
+        if let Some(crs) = &self.crs {
+            write!(f, "CRS{crs}")?;
+        }
+        write!(f, "/")
+    }
+}
+
+impl From<Iso6709> for Position {
+    fn from(point: Iso6709) -> Self {
+        point.position
+    }
+}
+
+impl From<Iso6709> for Coordinate {
+    fn from(point: Iso6709) -> Self {
+        point.position.coordinate
+    }
+}
+
+impl From<Position> for Iso6709 {
+    /// Ellipsoidal altitudes are marked with the CRS `WGS_84`
+    fn from(position: Position) -> Self {
+        let crs = position
+            .altitude
+            .filter(|altitude| altitude.reference == AltitudeReference::Ellipsoid)
+            .map(|_| "WGS_84".to_string());
+        Self {
+            precision: Precision {
+                lat: last_digit_precision(&position.coordinate.lat.to_string()),
+                lng: last_digit_precision(&position.coordinate.lng.to_string()),
+            },
+            position,
+            crs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forms() {
+        for (input, lat, lng) in [
+            ("+50.5+007.25/", 50.5, 7.25),
+            ("+5030-00715/", 50.5, -7.25),
+            ("-503000+0071500/", -50.5, 7.25),
+            ("+5030.0+00715.0", 50.5, 7.25),
+        ] {
+            let point: Iso6709 = input.parse().unwrap();
+            assert_eq!(
+                point.position.coordinate,
+                Coordinate::new(lat, lng),
+                "{input}"
+            );
+            assert_eq!(point.position.altitude, None);
+        }
+    }
+
+    #[test]
+    fn test_precision() {
+        let point: Iso6709 = "+50.5+007.25/".parse().unwrap();
+        assert_eq!(
+            point.precision(),
+            Precision {
+                lat: 0.05,
+                lng: 0.005
+            }
+        );
+        let point: Iso6709 = "+5030+00715/".parse().unwrap();
+        assert_eq!(point.precision().lat, 0.5 / 60.);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("+50.5+7.25/".parse::<Iso6709>().is_err());
+        assert!("+5090+00715/".parse::<Iso6709>().is_err());
+        assert!("+95+007/".parse::<Iso6709>().is_err());
+        assert!("50.5,7.25".parse::<Iso6709>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let point: Iso6709 = "+40.20361-075.00417+350.517CRSWGS_84/".parse().unwrap();
+        let altitude = point.position.altitude.unwrap();
+        assert_eq!(altitude.reference, AltitudeReference::Ellipsoid);
+        assert_eq!(point.to_string(), "+40.203610-075.004170+350.517CRSWGS_84/");
+        let position = Position::from(point.clone());
+        assert_eq!(Iso6709::from(position).to_string(), point.to_string());
+    }
+}
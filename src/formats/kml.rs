@@ -0,0 +1,579 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use quick_xml::{
+    escape::{escape, resolve_predefined_entity},
+    events::Event,
+    Reader,
+};
+
+use crate::{
+    geometry::Geometry, Altitude, AltitudeReference, Coordinate, CoordinateError, Position,
+};
+
+/// Deepest nesting of elements within a placemark
+const MAX_DEPTH: usize = 32;
+
+/// A KML document with placemarks
+///
+/// Placemarks with a point, line string, linear ring, polygon or a multi
+/// geometry of one of these are read from anywhere in the document (e.g.
+/// nested in folders). Placemarks without geometry are skipped, and multi
+/// geometries mixing different types or nesting other multi geometries are
+/// unsupported. Elements nested deeper than 32 levels within a placemark are
+/// rejected as malformed.
+///
+/// KML writes positions as "longitude,latitude[,altitude]". Altitudes are
+/// only kept with the "absolute" altitude mode, where they are above mean sea
+/// level; all other modes are relative to the ground.
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::kml::Kml;
+/// let kml: Kml = r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document>
+///   <Placemark>
+///     <name>Cologne Cathedral</name>
+///     <Point><coordinates>6.9583,50.9413,0</coordinates></Point>
+///   </Placemark>
+/// </Document></kml>"#.parse().unwrap();
+/// let placemark = &kml.placemarks[0];
+/// assert_eq!(placemark.name.as_deref(), Some("Cologne Cathedral"));
+/// assert_eq!(placemark.geometry.coordinates(), [Coordinate::new(50.9413, 6.9583)]);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Kml {
+    /// Placemarks in document order
+    pub placemarks: Vec<Placemark>,
+}
+
+/// A named geometry
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placemark {
+    /// Name shown on the map
+    pub name: Option<String>,
+    /// Description, often HTML
+    pub description: Option<String>,
+    /// The geometry
+    pub geometry: Geometry,
+}
+
+impl Placemark {
+    /// A placemark without name and description
+    pub fn new(geometry: impl Into<Geometry>) -> Self {
+        Placemark {
+            name: None,
+            description: None,
+            geometry: geometry.into(),
+        }
+    }
+
+    /// Set the name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+/// An element of a placemark with its text and children
+#[derive(Debug, Default)]
+struct Element {
+    /// Local name of the element
+    name: String,
+    /// Text content with references resolved
+    text: String,
+    /// Child elements
+    children: Vec<Element>,
+}
+
+impl Element {
+    /// First child with the given name
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Text of the first child with the given name
+    fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name).map(|child| child.text.trim().to_owned())
+    }
+
+    /// Positions of the coordinates child
+    fn positions(&self) -> Result<Vec<Position>, CoordinateError> {
+        let absolute = self.child_text("altitudeMode").as_deref() == Some("absolute");
+        let coordinates = self
+            .child("coordinates")
+            .ok_or(CoordinateError::Malformed)?;
+        coordinates
+            .text
+            .split_whitespace()
+            .map(|tuple| {
+                let values = tuple
+                    .split(',')
+                    .map(|value| value.parse().map_err(|_| CoordinateError::Malformed))
+                    .collect::<Result<Vec<f64>, _>>()?;
+                let (lng, lat, altitude) = match values[..] {
+                    [lng, lat] => (lng, lat, None),
+                    [lng, lat, altitude] => (lng, lat, Some(altitude)),
+                    _ => return Err(CoordinateError::Malformed),
+                };
+                Ok(Position {
+                    coordinate: Coordinate::try_new(lat, lng)?,
+                    altitude: altitude.filter(|_| absolute).map(Altitude::above_sea_level),
+                })
+            })
+            .collect()
+    }
+
+    /// Rings of a polygon, the outer boundary first
+    fn rings(&self) -> Result<Vec<Vec<Position>>, CoordinateError> {
+        let boundary = |element: &Element| -> Result<Vec<Vec<Position>>, CoordinateError> {
+            element
+                .children
+                .iter()
+                .filter(|child| child.name == "LinearRing")
+                .map(Element::positions)
+                .collect()
+        };
+        let outer = self
+            .child("outerBoundaryIs")
+            .ok_or(CoordinateError::Malformed)?;
+        let mut rings = boundary(outer)?;
+        if rings.len() != 1 {
+            return Err(CoordinateError::Malformed);
+        }
+        for inner in self
+            .children
+            .iter()
+            .filter(|child| child.name == "innerBoundaryIs")
+        {
+            rings.extend(boundary(inner)?);
+        }
+        Ok(rings)
+    }
+
+    /// Geometry of a geometry element (None for other elements)
+    fn geometry(&self) -> Result<Option<Geometry>, CoordinateError> {
+        Ok(Some(match self.name.as_str() {
+            "Point" => match <[Position; 1]>::try_from(self.positions()?) {
+                Ok([position]) => Geometry::Point(position),
+                Err(_) => return Err(CoordinateError::Malformed),
+            },
+            "LineString" => Geometry::LineString(self.positions()?),
+            "LinearRing" => Geometry::Polygon(vec![self.positions()?]),
+            "Polygon" => Geometry::Polygon(self.rings()?),
+            "MultiGeometry" => {
+                let mut parts = vec![];
+                for child in &self.children {
+                    if child.name == "MultiGeometry" {
+                        return Err(CoordinateError::Unsupported);
+                    }
+                    parts.extend(child.geometry()?);
+                }
+                match parts.first() {
+                    Some(Geometry::Point(_)) => Geometry::MultiPoint(
+                        parts
+                            .into_iter()
+                            .map(Position::try_from)
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    Some(Geometry::LineString(_)) => Geometry::MultiLineString(
+                        parts
+                            .into_iter()
+                            .map(|part| match part {
+                                Geometry::LineString(line) => Ok(line),
+                                _ => Err(CoordinateError::Unsupported),
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    Some(Geometry::Polygon(_)) => Geometry::MultiPolygon(
+                        parts
+                            .into_iter()
+                            .map(|part| match part {
+                                Geometry::Polygon(rings) => Ok(rings),
+                                _ => Err(CoordinateError::Unsupported),
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    _ => return Err(CoordinateError::Unsupported),
+                }
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Placemark of a placemark element (None without geometry)
+    fn placemark(&self) -> Result<Option<Placemark>, CoordinateError> {
+        let mut geometry = None;
+        for child in &self.children {
+            if let Some(found) = child.geometry()? {
+                geometry = Some(found);
+                break;
+            }
+        }
+        Ok(geometry.map(|geometry| Placemark {
+            name: self.child_text("name"),
+            description: self.child_text("description"),
+            geometry,
+        }))
+    }
+}
+
+impl FromStr for Kml {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut reader = Reader::from_str(str);
+        let mut kml = Kml::default();
+        // Open elements of the current placemark
+        let mut open: Vec<Element> = vec![];
+        let mut root = false;
+        loop {
+            match reader.read_event()? {
+                Event::Start(start) => {
+                    let name = start.local_name().as_ref().to_owned();
+                    root |= name == "kml";
+                    if !open.is_empty() || name == "Placemark" {
+                        if open.len() >= MAX_DEPTH {
+                            return Err(CoordinateError::Malformed);
+                        }
+                        open.push(Element {
+                            name,
+                            ..Default::default()
+                        });
+                    }
+                }
+                Event::Empty(start) => {
+                    root |= start.local_name().as_ref() == "kml";
+                    if let Some(parent) = open.last_mut() {
+                        parent.children.push(Element {
+                            name: start.local_name().as_ref().to_owned(),
+                            ..Default::default()
+                        });
+                    }
+                }
+                Event::End(_) => {
+                    if let Some(element) = open.pop() {
+                        match open.last_mut() {
+                            Some(parent) => parent.children.push(element),
+                            None => kml.placemarks.extend(element.placemark()?),
+                        }
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(element) = open.last_mut() {
+                        element.text.push_str(&text.xml10_content());
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(element) = open.last_mut() {
+                        element.text.push_str(&data);
+                    }
+                }
+                Event::GeneralRef(reference) => {
+                    if let Some(element) = open.last_mut() {
+                        match reference.resolve_char_ref()? {
+                            Some(char) => element.text.push(char),
+                            None => element.text.push_str(
+                                resolve_predefined_entity(&reference)
+                                    .ok_or(CoordinateError::Malformed)?,
+                            ),
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        if !root {
+            return Err(CoordinateError::Malformed);
+        }
+        Ok(kml)
+    }
+}
+
+/// Write the coordinates of positions as "lng,lat[,alt]" tuples
+fn write_coordinates(f: &mut fmt::Formatter, positions: &[Position], indent: &str) -> fmt::Result {
+    let absolute = positions.iter().any(|position| {
+        matches!(
+            position.altitude,
+            Some(Altitude {
+                reference: AltitudeReference::MeanSeaLevel,
+                ..
+            })
+        )
+    });
+    if absolute {
+        writeln!(f, "{indent}<altitudeMode>absolute</altitudeMode>")?;
+    }
+    write!(f, "{indent}<coordinates>")?;
+    for (index, position) in positions.iter().enumerate() {
+        let separator = if index > 0 { " " } else { "" };
+        let coordinate = &position.coordinate;
+        write!(f, "{separator}{},{}", coordinate.lng, coordinate.lat)?;
+        if absolute {
+            let meters = position.altitude.map_or(0., |altitude| altitude.meters);
+            write!(f, ",{meters}")?;
+        }
+    }
+    writeln!(f, "</coordinates>")
+}
+
+/// Write a single geometry element
+fn write_geometry(f: &mut fmt::Formatter, geometry: &Geometry, indent: &str) -> fmt::Result {
+    let inner = format!("{indent}  ");
+    let polygon = |f: &mut fmt::Formatter, rings: &[Vec<Position>], indent: &str| {
+        writeln!(f, "{indent}<Polygon>")?;
+        for (index, ring) in rings.iter().enumerate() {
+            let boundary = if index == 0 { "outer" } else { "inner" };
+            writeln!(f, "{indent}  <{boundary}BoundaryIs>")?;
+            writeln!(f, "{indent}    <LinearRing>")?;
+            write_coordinates(f, ring, &format!("{indent}      "))?;
+            writeln!(f, "{indent}    </LinearRing>")?;
+            writeln!(f, "{indent}  </{boundary}BoundaryIs>")?;
+        }
+        writeln!(f, "{indent}</Polygon>")
+    };
+    let element = |f: &mut fmt::Formatter, name: &str, positions: &[Position], indent: &str| {
+        writeln!(f, "{indent}<{name}>")?;
+        write_coordinates(f, positions, &format!("{indent}  "))?;
+        writeln!(f, "{indent}</{name}>")
+    };
+    match geometry {
+        Geometry::Point(point) => element(f, "Point", std::slice::from_ref(point), indent),
+        Geometry::LineString(line) => element(f, "LineString", line, indent),
+        Geometry::Polygon(rings) => polygon(f, rings, indent),
+        Geometry::MultiPoint(points) => {
+            writeln!(f, "{indent}<MultiGeometry>")?;
+            for point in points {
+                element(f, "Point", std::slice::from_ref(point), &inner)?;
+            }
+            writeln!(f, "{indent}</MultiGeometry>")
+        }
+        Geometry::MultiLineString(lines) => {
+            writeln!(f, "{indent}<MultiGeometry>")?;
+            for line in lines {
+                element(f, "LineString", line, &inner)?;
+            }
+            writeln!(f, "{indent}</MultiGeometry>")
+        }
+        Geometry::MultiPolygon(polygons) => {
+            writeln!(f, "{indent}<MultiGeometry>")?;
+            for rings in polygons {
+                polygon(f, rings, &inner)?;
+            }
+            writeln!(f, "{indent}</MultiGeometry>")
+        }
+    }
+}
+
+impl Display for Kml {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(f, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+        writeln!(f, "  <Document>")?;
+        for placemark in &self.placemarks {
+            writeln!(f, "    <Placemark>")?;
+            if let Some(name) = &placemark.name {
+                writeln!(f, "      <name>{}</name>", escape(name))?;
+            }
+            if let Some(description) = &placemark.description {
+                writeln!(
+                    f,
+                    "      <description>{}</description>",
+                    escape(description)
+                )?;
+            }
+            write_geometry(f, &placemark.geometry, "      ")?;
+            writeln!(f, "    </Placemark>")?;
+        }
+        writeln!(f, "  </Document>")?;
+        write!(f, "</kml>")
+    }
+}
+
+impl From<Placemark> for Kml {
+    fn from(placemark: Placemark) -> Self {
+        Kml {
+            placemarks: vec![placemark],
+        }
+    }
+}
+
+#[cfg(feature = "format_kmz")]
+impl Kml {
+    /// Read the KML document of a KMZ archive
+    ///
+    /// The document is "doc.kml" or else the first file ending in ".kml".
+    pub fn from_kmz(reader: impl std::io::Read + std::io::Seek) -> Result<Self, CoordinateError> {
+        use std::io::Read;
+
+        let mut archive = zip::ZipArchive::new(reader)?;
+        let name = match archive.index_for_name("doc.kml") {
+            Some(_) => "doc.kml".to_owned(),
+            None => archive
+                .file_names()
+                .find(|name| name.to_ascii_lowercase().ends_with(".kml"))
+                .ok_or(CoordinateError::Malformed)?
+                .to_owned(),
+        };
+        let mut document = String::new();
+        archive
+            .by_name(&name)?
+            .read_to_string(&mut document)
+            .map_err(zip::result::ZipError::from)?;
+        document.parse()
+    }
+
+    /// Write a KMZ archive with the document as "doc.kml"
+    pub fn to_kmz<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: W,
+    ) -> Result<W, CoordinateError> {
+        use std::io::Write;
+
+        let mut archive = zip::ZipWriter::new(writer);
+        archive.start_file("doc.kml", zip::write::SimpleFileOptions::default())?;
+        archive
+            .write_all(self.to_string().as_bytes())
+            .map_err(zip::result::ZipError::from)?;
+        Ok(archive.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Export of Google Earth, shortened
+    const GOOGLE_EARTH: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">
+<Document>
+	<name>Sales visits.kml</name>
+	<Style id="s_ylw-pushpin"><IconStyle><scale>1.1</scale></IconStyle></Style>
+	<Folder>
+		<name>Customers</name>
+		<Placemark>
+			<name>M&#252;ller &amp; Co</name>
+			<description><![CDATA[<b>Visit</b> on Monday]]></description>
+			<styleUrl>#s_ylw-pushpin</styleUrl>
+			<Point>
+				<gx:drawOrder>1</gx:drawOrder>
+				<coordinates>6.958281,50.941278,0</coordinates>
+			</Point>
+		</Placemark>
+		<Placemark>
+			<name>Without geometry</name>
+		</Placemark>
+	</Folder>
+	<Placemark>
+		<name>Route</name>
+		<LineString>
+			<tessellate>1</tessellate>
+			<altitudeMode>absolute</altitudeMode>
+			<coordinates>
+				6.958281,50.941278,54 6.9598,50.9430,60
+			</coordinates>
+		</LineString>
+	</Placemark>
+	<Placemark>
+		<name>Area</name>
+		<Polygon>
+			<outerBoundaryIs><LinearRing><coordinates>0,0 4,0 4,4 0,0</coordinates></LinearRing></outerBoundaryIs>
+			<innerBoundaryIs><LinearRing><coordinates>1,1 2,1 2,2 1,1</coordinates></LinearRing></innerBoundaryIs>
+		</Polygon>
+	</Placemark>
+	<Placemark>
+		<MultiGeometry>
+			<Point><coordinates>1,2</coordinates></Point>
+			<Point><coordinates>3,4</coordinates></Point>
+		</MultiGeometry>
+	</Placemark>
+</Document>
+</kml>"#;
+
+    #[test]
+    fn test_google_earth() {
+        let kml: Kml = GOOGLE_EARTH.parse().unwrap();
+        assert_eq!(kml.placemarks.len(), 4);
+        let customer = &kml.placemarks[0];
+        assert_eq!(customer.name.as_deref(), Some("Müller & Co"));
+        assert_eq!(
+            customer.description.as_deref(),
+            Some("<b>Visit</b> on Monday")
+        );
+        // The altitude is relative to the ground
+        assert_eq!(
+            customer.geometry,
+            Geometry::from(Coordinate::new(50.941278, 6.958281))
+        );
+        let route = kml.placemarks[1].geometry.positions();
+        assert_eq!(route[1].coordinate, Coordinate::new(50.943, 6.9598));
+        assert_eq!(route[1].altitude, Some(Altitude::above_sea_level(60.)));
+        let Geometry::Polygon(rings) = &kml.placemarks[2].geometry else {
+            panic!("Polygon expected");
+        };
+        assert_eq!(rings.len(), 2);
+        assert_eq!(kml.placemarks[3].geometry.kind(), "MultiPoint");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let kml: Kml = GOOGLE_EARTH.parse().unwrap();
+        assert_eq!(kml.to_string().parse::<Kml>().unwrap(), kml);
+        let point = Kml::from(Placemark::new(Coordinate::new(50.9413, 6.9583)).with_name("Dom"));
+        assert!(point
+            .to_string()
+            .contains("<coordinates>6.9583,50.9413</coordinates>"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            "<gpx></gpx>".parse::<Kml>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            "<kml><Placemark><Point><coordinates>6.9</coordinates></Point></Placemark></kml>"
+                .parse::<Kml>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            "<kml><Placemark><Point><coordinates>50.9,100</coordinates></Point></Placemark></kml>"
+                .parse::<Kml>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        let mixed = "<kml><Placemark><MultiGeometry>
+            <Point><coordinates>1,2</coordinates></Point>
+            <LineString><coordinates>1,2 3,4</coordinates></LineString>
+        </MultiGeometry></Placemark></kml>";
+        assert!(matches!(
+            mixed.parse::<Kml>(),
+            Err(CoordinateError::Unsupported)
+        ));
+        let nested = "<kml><Placemark><MultiGeometry><MultiGeometry>
+            <Point><coordinates>1,2</coordinates></Point>
+        </MultiGeometry></MultiGeometry></Placemark></kml>";
+        assert!(matches!(
+            nested.parse::<Kml>(),
+            Err(CoordinateError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        for element in ["a", "MultiGeometry"] {
+            let deep = format!("<kml><Placemark>{}", format!("<{element}>").repeat(200_000));
+            assert!(
+                matches!(deep.parse::<Kml>(), Err(CoordinateError::Malformed)),
+                "{element}"
+            );
+        }
+    }
+
+    #[cfg(feature = "format_kmz")]
+    #[test]
+    fn test_kmz() {
+        let kml: Kml = GOOGLE_EARTH.parse().unwrap();
+        let kmz = kml.to_kmz(std::io::Cursor::new(vec![])).unwrap();
+        assert_eq!(Kml::from_kmz(kmz).unwrap(), kml);
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use super::labeled_pair;
+use crate::{Coordinate, CoordinateError, Precision};
+
+/// Eastings in meters covering Switzerland and Liechtenstein
+const EASTINGS: std::ops::RangeInclusive<f64> = 2_400_000.0..=2_900_000.0;
+
+/// Northings in meters covering Switzerland and Liechtenstein
+const NORTHINGS: std::ops::RangeInclusive<f64> = 1_000_000.0..=1_350_000.0;
+
+/// Accuracy in meters of the approximate formulas of swisstopo
+const ACCURACY: f64 = 1.;
+
+/// A coordinate of the Swiss LV95 grid (e.g. "2600000/1200000")
+///
+/// Unlike the other national grids, the Swiss grid uses an oblique Mercator
+/// projection. It's converted from and to WGS84 with the approximate formulas
+/// published by swisstopo, which include the shift from the CH1903+ datum and
+/// are accurate to about a meter.
+///
+/// The labels "E" and "N" are optional and apostrophes may separate thousands
+/// (e.g. "E 2'600'000 N 1'200'000").
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::lv95::Lv95;
+/// let bern: Lv95 = "2600000/1200000".parse().unwrap();
+/// let coordinate = Coordinate::from(bern);
+/// assert!(coordinate.distance(&Coordinate::new(46.95108, 7.43863)) < 2.);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Lv95 {
+    /// Easting in meters
+    pub east: f64,
+    /// Northing in meters
+    pub north: f64,
+    /// Half a unit of the last written digit in meters (0 if not parsed)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    digits: f64,
+}
+
+impl PartialEq for Lv95 {
+    /// Only the values are compared, not how they were written
+    fn eq(&self, other: &Self) -> bool {
+        self.east == other.east && self.north == other.north
+    }
+}
+
+impl Lv95 {
+    /// Grid coordinate of a WGS84 coordinate
+    pub fn from_coordinate(coordinate: &Coordinate) -> Self {
+        // Offsets from Bern in 10000 arc seconds
+        let lat = (coordinate.lat * 3600. - 169_028.66) / 10_000.;
+        let lng = (coordinate.lng * 3600. - 26_782.5) / 10_000.;
+        let (east, north) = (
+            2_600_072.37 + 211_455.93 * lng
+                - 10_938.51 * lng * lat
+                - 0.36 * lng * lat.powi(2)
+                - 44.54 * lng.powi(3),
+            1_200_147.07 + 308_807.95 * lat + 3_745.25 * lng.powi(2) + 76.63 * lat.powi(2)
+                - 194.56 * lng.powi(2) * lat
+                + 119.79 * lat.powi(3),
+        );
+        // Nothing was written, so only the accuracy of the formulas counts
+        Lv95 {
+            east,
+            north,
+            digits: 0.,
+        }
+    }
+
+    /// Precision given by the written digits and the approximate formulas
+    pub fn precision(&self) -> Precision {
+        Precision::from_meters(self.digits + ACCURACY, &Coordinate::from(self.clone()))
+    }
+}
+
+impl FromStr for Lv95 {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let ((east, north), digits) = labeled_pair(str, [&["E"], &["N"]])?;
+        if !EASTINGS.contains(&east) || !NORTHINGS.contains(&north) {
+            return Err(CoordinateError::InvalidValue);
+        }
+        Ok(Lv95 {
+            east,
+            north,
+            digits,
+        })
+    }
+}
+
+impl Display for Lv95 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.east, self.north)
+    }
+}
+
+impl From<Lv95> for Coordinate {
+    /// Coordinate on the WGS84 datum
+    fn from(lv95: Lv95) -> Self {
+        // Offsets from Bern in 1000 km
+        let east = (lv95.east - 2_600_000.) / 1_000_000.;
+        let north = (lv95.north - 1_200_000.) / 1_000_000.;
+        let lng = 2.677_909_4
+            + 4.728_982 * east
+            + 0.791_484 * east * north
+            + 0.130_6 * east * north.powi(2)
+            - 0.043_6 * east.powi(3);
+        let lat = 16.902_389_2 + 3.238_272 * north
+            - 0.270_978 * east.powi(2)
+            - 0.002_528 * north.powi(2)
+            - 0.044_7 * east.powi(2) * north
+            - 0.014 * north.powi(3);
+        // The formulas give units of 10000 arc seconds
+        Coordinate {
+            lat: lat * 100. / 36.,
+            lng: lng * 100. / 36.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        for input in [
+            "2600000/1200000",
+            "2600000, 1200000",
+            "E 2'600'000 N 1'200'000",
+            "E: 2600000.0 N: 1200000.0",
+        ] {
+            let lv95: Lv95 = input.parse().unwrap();
+            assert_eq!((lv95.east, lv95.north), (2_600_000., 1_200_000.), "{input}");
+        }
+        assert!("600000/200000".parse::<Lv95>().is_err());
+        assert!("3570000 5650000".parse::<Lv95>().is_err());
+        assert!("N 1200000 E 2600000".parse::<Lv95>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // Zürich main station
+        let lv95: Lv95 = "2683151/1248041".parse().unwrap();
+        assert_eq!(lv95.to_string(), "2683151/1248041");
+        let coordinate = Coordinate::from(lv95.clone());
+        assert!(coordinate.distance(&Coordinate::new(47.3779, 8.5403)) < 100.);
+        let back = Lv95::from_coordinate(&coordinate);
+        assert!((back.east - lv95.east).abs() < 2. * ACCURACY);
+        assert!((back.north - lv95.north).abs() < 2. * ACCURACY);
+        assert_eq!(
+            back.precision(),
+            Precision::from_meters(ACCURACY, &Coordinate::from(back.clone()))
+        );
+        let written: Lv95 = "2683151.0/1248041.00".parse().unwrap();
+        assert_eq!(written, lv95);
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        let resolution = Coordinate::parse_detailed("2600000/1200000").unwrap();
+        assert_eq!(
+            resolution.source,
+            crate::ResolutionSource::Parsed(crate::formats::Format::Lv95)
+        );
+    }
+}
@@ -4,6 +4,214 @@ pub mod dd;
 /// Degree, Minutes, Seconds format (12°34'56"N 9°12'23"E)
 #[cfg(feature = "format_dms")]
 pub mod dms;
+/// German Gauss-Krüger grid (R 3570000 H 5650000)
+#[cfg(feature = "format_gauss_krueger")]
+pub mod gauss_krueger;
+/// Geo URI format (geo:50.9413,6.9583,54)
+#[cfg(feature = "format_geouri")]
+pub mod geo_uri;
 /// Geohash format (ezs42)
 #[cfg(feature = "format_geohash")]
 pub mod geohash;
+/// GeoJSON geometries and features ({"type": "Point", "coordinates": [6.9583, 50.9413]})
+#[cfg(feature = "format_geojson")]
+pub mod geojson;
+/// GPX waypoints, routes and tracks (<gpx><wpt lat="50.9413" lon="6.9583"/></gpx>)
+#[cfg(feature = "format_gpx")]
+pub mod gpx;
+/// Uber H3 hexagonal cells (89283082e73ffff)
+#[cfg(feature = "format_h3")]
+pub mod h3;
+/// ISO 6709 format (+50.9413+006.9583+54CRSWGS_84/)
+#[cfg(feature = "format_iso6709")]
+pub mod iso6709;
+/// KML placemarks of Google Earth (<Placemark><Point><coordinates>6.9583,50.9413</coordinates></Point></Placemark>)
+#[cfg(feature = "format_kml")]
+pub mod kml;
+/// Swiss LV95 grid (2600000/1200000)
+#[cfg(feature = "format_lv95")]
+pub mod lv95;
+/// NMEA 0183 sentences of GNSS receivers ($GPGGA,...)
+#[cfg(feature = "format_nmea")]
+pub mod nmea;
+/// British National Grid references (TQ 30080 80660)
+#[cfg(feature = "format_osgb")]
+pub mod osgb;
+/// Encoded polylines of coordinate sequences (_p~iF~ps|U)
+#[cfg(feature = "format_polyline")]
+pub mod polyline;
+/// Bing Maps quadkeys (120210233)
+#[cfg(feature = "format_quadkey")]
+pub mod quadkey;
+/// Google S2 cell ids and tokens (89c25)
+#[cfg(feature = "format_s2")]
+pub mod s2;
+/// Web Mercator projection and slippy map tiles (14/8508/5489)
+#[cfg(feature = "format_tile")]
+pub mod tile;
+/// Well-Known Binary geometries of PostGIS and SpatiaLite (0101000020E6100000...)
+#[cfg(feature = "format_wkb")]
+pub mod wkb;
+/// Well-Known Text geometries (POINT(16.37 48.21))
+#[cfg(feature = "format_wkt")]
+pub mod wkt;
+
+/// Format a coordinate string was written in
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Double floats format (see [`dd`])
+    #[cfg(feature = "format_dd")]
+    DD,
+    /// Degree, Minutes, Seconds format (see [`dms`])
+    #[cfg(feature = "format_dms")]
+    DMS,
+    /// German Gauss-Krüger grid (see [`gauss_krueger`])
+    #[cfg(feature = "format_gauss_krueger")]
+    GaussKrueger,
+    /// Geohash format (see [`geohash`])
+    #[cfg(feature = "format_geohash")]
+    Geohash,
+    /// GeoJSON geometries (see [`geojson`])
+    #[cfg(feature = "format_geojson")]
+    GeoJson,
+    /// Geo URI format (see [`geo_uri`])
+    #[cfg(feature = "format_geouri")]
+    GeoUri,
+    /// ISO 6709 format (see [`iso6709`])
+    #[cfg(feature = "format_iso6709")]
+    Iso6709,
+    /// Swiss LV95 grid (see [`lv95`])
+    #[cfg(feature = "format_lv95")]
+    Lv95,
+    /// NMEA 0183 sentences (see [`nmea`])
+    #[cfg(feature = "format_nmea")]
+    Nmea,
+    /// British National Grid references (see [`osgb`])
+    #[cfg(feature = "format_osgb")]
+    Osgb,
+    /// Google S2 cell tokens prefixed with "s2:" (see [`s2`])
+    #[cfg(feature = "format_s2")]
+    S2,
+    /// Slippy map tiles (see [`tile`])
+    #[cfg(feature = "format_tile")]
+    Tile,
+    /// Well-Known Text points (see [`wkt`])
+    #[cfg(feature = "format_wkt")]
+    Wkt,
+}
+
+/// Direction of a neighbouring geohash, tile or S2 cell
+#[cfg(any(feature = "format_geohash", feature = "format_s2", feature = "format_tile"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Towards the north pole
+    North,
+    /// North and east
+    NorthEast,
+    /// Towards increasing longitude
+    East,
+    /// South and east
+    SouthEast,
+    /// Towards the south pole
+    South,
+    /// South and west
+    SouthWest,
+    /// Towards decreasing longitude
+    West,
+    /// North and west
+    NorthWest,
+}
+
+#[cfg(any(feature = "format_geohash", feature = "format_s2", feature = "format_tile"))]
+impl Direction {
+    /// All directions clockwise, starting in the north
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Steps in latitude and longitude
+    pub(crate) fn offset(self) -> (f64, f64) {
+        match self {
+            Direction::North => (1., 0.),
+            Direction::NorthEast => (1., 1.),
+            Direction::East => (0., 1.),
+            Direction::SouthEast => (-1., 1.),
+            Direction::South => (-1., 0.),
+            Direction::SouthWest => (-1., -1.),
+            Direction::West => (0., -1.),
+            Direction::NorthWest => (1., -1.),
+        }
+    }
+}
+
+/// Half a unit of the last decimal place of a written number (e.g. 0.05 for "12.3")
+///
+/// Trailing zeros count, so "12.30" gives 0.005. Values which weren't read
+/// from text are passed as their shortest representation (`f64::to_string`).
+#[cfg(any(
+    feature = "format_dd",
+    feature = "format_dms",
+    feature = "format_gauss_krueger",
+    feature = "format_geojson",
+    feature = "format_geouri",
+    feature = "format_iso6709",
+    feature = "format_lv95",
+    feature = "format_wkt"
+))]
+pub(crate) fn last_digit_precision(number: &str) -> f64 {
+    let number = number.trim();
+    let (mantissa, exponent) = number.split_once(['e', 'E']).unwrap_or((number, "0"));
+    let decimals = mantissa
+        .split_once('.')
+        .map_or(0, |(_, fract)| fract.len() as i32);
+    0.5 * 10f64.powi(exponent.parse::<i32>().unwrap_or(0) - decimals)
+}
+
+/// Two numbers separated by whitespace, "," or "/", each optionally preceded by a label
+///
+/// Labels are compared case-insensitively and may end with a colon (e.g.
+/// "R: 3570000 H: 5650000"). Apostrophes are skipped as thousands separators.
+/// Returns the numbers and half a unit of the last decimal place written in
+/// either of them.
+#[cfg(any(feature = "format_gauss_krueger", feature = "format_lv95"))]
+pub(crate) fn labeled_pair(
+    str: &str,
+    labels: [&[&str]; 2],
+) -> Result<((f64, f64), f64), crate::CoordinateError> {
+    let mut tokens = str
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .filter(|token| !token.is_empty())
+        .peekable();
+    let mut digits = 0f64;
+    let mut number = |labels: &[&str]| -> Result<f64, crate::CoordinateError> {
+        let is_label = |token: &&str| {
+            let token = token.strip_suffix(':').unwrap_or(token);
+            labels.iter().any(|label| label.eq_ignore_ascii_case(token))
+        };
+        tokens.next_if(is_label);
+        let token: String = tokens
+            .next()
+            .ok_or(crate::CoordinateError::Malformed)?
+            .chars()
+            .filter(|c| !matches!(c, '\'' | '\u{2019}'))
+            .collect();
+        if token.is_empty() || !token.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return Err(crate::CoordinateError::Malformed);
+        }
+        digits = digits.max(last_digit_precision(&token));
+        Ok(token.parse()?)
+    };
+    let pair = (number(labels[0])?, number(labels[1])?);
+    if tokens.next().is_some() {
+        return Err(crate::CoordinateError::Malformed);
+    }
+    Ok((pair, digits))
+}
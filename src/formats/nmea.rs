@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use crate::{Altitude, Coordinate, CoordinateError, Position, Precision};
+
+/// A position report of a GNSS receiver as NMEA 0183 sentence
+///
+/// Supported are GGA (with altitude), RMC and GLL sentences of any talker
+/// (e.g. `$GPGGA` or `$GNRMC`). If a checksum is given, it has to match.
+/// Sentences without a valid fix are rejected with
+/// [`CoordinateError::InvalidValue`].
+///
+/// ```
+/// # use recoord::formats::nmea::NmeaSentence;
+/// # use recoord::AltitudeReference;
+/// let gga = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+/// let sentence: NmeaSentence = gga.parse().unwrap();
+/// assert_eq!(sentence.kind, "GGA");
+/// assert!((sentence.position.coordinate.lat - 48.1173).abs() < 1e-9);
+/// let altitude = sentence.position.altitude.unwrap();
+/// assert_eq!((altitude.meters, altitude.reference), (545.4, AltitudeReference::MeanSeaLevel));
+/// assert_eq!(sentence.geoid_separation, Some(46.9));
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NmeaSentence {
+    /// Talker ID of the sender (e.g. "GP" for GPS or "GN" for multiple systems)
+    pub talker: String,
+    /// Kind of the sentence ("GGA", "RMC" or "GLL")
+    pub kind: String,
+    /// UTC time of the fix as hhmmss.ss (if given)
+    pub time: Option<String>,
+    /// Reported position (GGA sentences include the altitude above mean sea level)
+    pub position: Position,
+    /// Height of the geoid above the WGS 84 ellipsoid in meters (GGA only)
+    pub geoid_separation: Option<f64>,
+    /// Precision given by the written digits
+    precision: Precision,
+}
+
+impl NmeaSentence {
+    /// Precision given by the written digits of the minutes
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    /// Height above the WGS 84 ellipsoid, if altitude and geoid separation are known
+    pub fn ellipsoidal_altitude(&self) -> Option<Altitude> {
+        Some(Altitude::ellipsoidal(
+            self.position.altitude?.meters + self.geoid_separation?,
+        ))
+    }
+}
+
+/// Verify the checksum (if any) and return the content between `$` and `*`
+fn content(sentence: &str) -> Result<&str, CoordinateError> {
+    let sentence = sentence
+        .trim()
+        .strip_prefix(['$', '!'])
+        .ok_or(CoordinateError::Malformed)?;
+    let Some((content, checksum)) = sentence.split_once('*') else {
+        return Ok(sentence);
+    };
+    let expected =
+        u8::from_str_radix(checksum.trim(), 16).map_err(|_| CoordinateError::Malformed)?;
+    if content.bytes().fold(0, |sum, byte| sum ^ byte) != expected {
+        return Err(CoordinateError::Malformed);
+    }
+    Ok(content)
+}
+
+/// Parse an angle given as (d)ddmm.mmmm and a hemisphere
+///
+/// Returns the angle and its precision in degrees.
+fn parse_angle(
+    value: &str,
+    hemisphere: &str,
+    degree_digits: usize,
+    negative: &str,
+) -> Result<(f64, f64), CoordinateError> {
+    if value.len() < degree_digits + 2 || !value.is_char_boundary(degree_digits) {
+        return Err(CoordinateError::Malformed);
+    }
+    let degrees: f64 = value[..degree_digits].parse()?;
+    let minutes: f64 = value[degree_digits..].parse()?;
+    if minutes >= 60. {
+        return Err(CoordinateError::InvalidValue);
+    }
+    let sign = match hemisphere {
+        hemisphere if hemisphere == negative => -1.,
+        "N" | "E" => 1.,
+        _ => return Err(CoordinateError::Malformed),
+    };
+    let decimals = value.split_once('.').map_or(0, |(_, fract)| fract.len());
+    let precision = 0.5 * 10f64.powi(-(decimals as i32)) / 60.;
+    Ok((sign * (degrees + minutes / 60.), precision))
+}
+
+impl FromStr for NmeaSentence {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = content(str)?.split(',').collect();
+        let address = fields[0];
+        if address.len() != 5 || !address.is_ascii() {
+            return Err(CoordinateError::Malformed);
+        }
+        let (talker, kind) = address.split_at(2);
+        let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+
+        // Indices of time, latitude and validity, and the value marking a valid fix
+        let (time_index, lat_index, valid_index, valid_value) = match kind {
+            "GGA" => (1, 2, 6, None),
+            "RMC" => (1, 3, 2, Some("A")),
+            "GLL" => (5, 1, 6, Some("A")),
+            _ => return Err(CoordinateError::Unsupported),
+        };
+        let has_fix = match valid_value {
+            Some(valid_value) => field(valid_index) == valid_value,
+            None => !matches!(field(valid_index), "" | "0"),
+        };
+        if !has_fix {
+            return Err(CoordinateError::InvalidValue);
+        }
+        let (lat, lat_precision) = parse_angle(field(lat_index), field(lat_index + 1), 2, "S")?;
+        let (lng, lng_precision) = parse_angle(field(lat_index + 2), field(lat_index + 3), 3, "W")?;
+
+        let optional_number = |index: usize| match field(index) {
+            "" => Ok(None),
+            value => value.parse().map(Some),
+        };
+        let (altitude, geoid_separation) = if kind == "GGA" {
+            (
+                optional_number(9)?.map(Altitude::above_sea_level),
+                optional_number(11)?,
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(NmeaSentence {
+            talker: talker.to_string(),
+            kind: kind.to_string(),
+            time: Some(field(time_index))
+                .filter(|time| !time.is_empty())
+                .map(str::to_string),
+            position: Position {
+                coordinate: Coordinate::try_new(lat, lng)?,
+                altitude,
+            },
+            geoid_separation,
+            precision: Precision {
+                lat: lat_precision,
+                lng: lng_precision,
+            },
+        })
+    }
+}
+
+impl From<NmeaSentence> for Position {
+    fn from(sentence: NmeaSentence) -> Self {
+        sentence.position
+    }
+}
+
+impl From<NmeaSentence> for Coordinate {
+    fn from(sentence: NmeaSentence) -> Self {
+        sentence.position.coordinate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rmc() {
+        let rmc = "$GPRMC,123519,A,4807.038,N,01131.000,W,022.4,084.4,230394,003.1,W*78";
+        let sentence: NmeaSentence = rmc.parse().unwrap();
+        assert_eq!(
+            (sentence.talker.as_str(), sentence.kind.as_str()),
+            ("GP", "RMC")
+        );
+        assert_eq!(sentence.time.as_deref(), Some("123519"));
+        assert!((sentence.position.coordinate.lng + 11.516667).abs() < 1e-6);
+        assert_eq!(sentence.position.altitude, None);
+        assert!((sentence.precision().lng - 0.0005 / 60.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_gll_without_checksum() {
+        let sentence: NmeaSentence = "$GNGLL,4916.45,S,12311.12,E,225444,A".parse().unwrap();
+        assert!((sentence.position.coordinate.lat + 49.274167).abs() < 1e-6);
+        assert_eq!(sentence.time.as_deref(), Some("225444"));
+    }
+
+    #[test]
+    fn test_ellipsoidal_altitude() {
+        let gga = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+        let sentence: NmeaSentence = gga.parse().unwrap();
+        let altitude = sentence.ellipsoidal_altitude().unwrap();
+        assert!((altitude.meters - 592.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalid() {
+        // Wrong checksum
+        assert!(matches!(
+            "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*48"
+                .parse::<NmeaSentence>(),
+            Err(CoordinateError::Malformed)
+        ));
+        // No fix
+        assert!(matches!(
+            "$GPRMC,123519,V,4807.038,N,01131.000,E,,,230394,,".parse::<NmeaSentence>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!(matches!(
+            "$GPGSV,3,1,11,03,03,111,00".parse::<NmeaSentence>(),
+            Err(CoordinateError::Unsupported)
+        ));
+        assert!("$GPGLL,4916.45,X,12311.12,E,225444,A"
+            .parse::<NmeaSentence>()
+            .is_err());
+        assert!("50.1,7.2".parse::<NmeaSentence>().is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use crate::{
+    datum::{Datum, DatumCoordinate, Method},
+    geodesy::{Ellipsoid, TransverseMercator},
+    Coordinate, CoordinateError, Precision,
+};
+
+/// Projection of the British National Grid on the OSGB36 datum
+pub const NATIONAL_GRID: TransverseMercator = TransverseMercator {
+    ellipsoid: Ellipsoid::AIRY1830,
+    origin_lat: 49.,
+    central_meridian: -2.,
+    scale: 0.999_601_271_7,
+    false_easting: 400_000.,
+    false_northing: -100_000.,
+};
+
+/// Size of the squares named by two letters in meters
+const SQUARE: f64 = 100_000.;
+
+/// A British National Grid reference (e.g. "TQ 30080 80660")
+///
+/// Two letters name a 100 km square, followed by one to five digits each for
+/// easting and northing within the square. A reference names the south-west
+/// corner of a square of the given resolution; it's converted into the center
+/// of that square. Grid references are on the OSGB36 datum and shifted to WGS84
+/// with the Helmert transformation (accurate to about 5 m).
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::osgb::GridReference;
+/// let reference: GridReference = "TQ 30080 80660".parse().unwrap();
+/// assert_eq!((reference.easting, reference.northing), (530_080., 180_660.));
+/// // Worked example of the Ordnance Survey guide to coordinate systems
+/// let reference: GridReference = "TG 51409 13177".parse().unwrap();
+/// let expected = Coordinate::new(
+///     52. + 39. / 60. + 27.2531 / 3600.,
+///     1. + 43. / 60. + 4.5177 / 3600.,
+/// );
+/// assert!(reference.osgb36().distance(&expected) < 1.);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridReference {
+    /// Easting in meters from the false origin of the grid
+    pub easting: f64,
+    /// Northing in meters from the false origin of the grid
+    pub northing: f64,
+    /// Number of digits for each of easting and northing (1 to 5)
+    digits: u8,
+}
+
+impl GridReference {
+    /// Whether a grid reference can't be mistaken for a geohash
+    ///
+    /// Geohashes are written in lowercase without spaces, so references with
+    /// capitals or spaces (e.g. "TQ3008080660" or "tq 300 806") are taken as
+    /// grid references first.
+    pub(crate) fn is_unambiguous(str: &str) -> bool {
+        str.trim()
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_ascii_uppercase())
+    }
+
+    /// Grid reference of a WGS84 coordinate with `digits` digits for easting and northing
+    pub fn from_coordinate(coordinate: &Coordinate, digits: u8) -> Result<Self, CoordinateError> {
+        if !(1..=5).contains(&digits) {
+            return Err(CoordinateError::InvalidValue);
+        }
+        let osgb36 = DatumCoordinate::from(coordinate.clone())
+            .transform(Datum::Osgb36, Method::Helmert)
+            .coordinate
+            .coordinate;
+        let (easting, northing) = NATIONAL_GRID.project(&osgb36);
+        let resolution = 10f64.powi(5 - digits as i32);
+        let reference = GridReference {
+            easting: (easting / resolution).floor() * resolution,
+            northing: (northing / resolution).floor() * resolution,
+            digits,
+        };
+        reference.letters()?;
+        Ok(reference)
+    }
+
+    /// Size in meters of the square named by the reference (e.g. 10 m for 4 digits)
+    pub fn resolution(&self) -> f64 {
+        10f64.powi(5 - self.digits as i32)
+    }
+
+    /// Center of the square on the OSGB36 datum
+    pub fn osgb36(&self) -> Coordinate {
+        let half = self.resolution() / 2.;
+        NATIONAL_GRID.unproject(self.easting + half, self.northing + half)
+    }
+
+    /// Precision of the center, combining the size of the square and the datum shift
+    pub fn precision(&self) -> Precision {
+        let meters = self.resolution() / 2. + Datum::Osgb36.accuracy(Method::Helmert);
+        Precision::from_meters(meters, &self.osgb36())
+    }
+
+    /// Letters of the 100 km square
+    fn letters(&self) -> Result<[char; 2], CoordinateError> {
+        let (east, north) = (
+            (self.easting / SQUARE).floor(),
+            (self.northing / SQUARE).floor(),
+        );
+        if !(0. ..7.).contains(&east) || !(0. ..13.).contains(&north) {
+            return Err(CoordinateError::InvalidValue);
+        }
+        let (east, north) = (east as u8, north as u8);
+        // Letters are laid out in 5x5 grids from the north-west, skipping "I"
+        let first = (19 - north) - (19 - north) % 5 + (east + 10) / 5;
+        let second = (19 - north) * 5 % 25 + east % 5;
+        let letter = |index: u8| (b'A' + index + u8::from(index > 7)) as char;
+        Ok([letter(first), letter(second)])
+    }
+}
+
+impl FromStr for GridReference {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str: String = str.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut chars = str.chars();
+        let (Some(first), Some(second)) = (chars.next(), chars.next()) else {
+            return Err(CoordinateError::Malformed);
+        };
+        let index = |letter: char| match letter.to_ascii_uppercase() {
+            letter @ ('A'..='H' | 'J'..='Z') => {
+                let index = letter as u8 - b'A';
+                Ok(index - u8::from(index > 7))
+            }
+            _ => Err(CoordinateError::Malformed),
+        };
+        let (first, second) = (index(first)?, index(second)?);
+        let digits = chars.as_str();
+        if !digits.len().is_multiple_of(2)
+            || !(2..=10).contains(&digits.len())
+            || !digits.bytes().all(|byte| byte.is_ascii_digit())
+        {
+            return Err(CoordinateError::Malformed);
+        }
+        let east = (first as i32 - 2).rem_euclid(5) * 5 + (second % 5) as i32;
+        let north = (19 - (first / 5) * 5) as i32 - (second / 5) as i32;
+        let half = digits.len() / 2;
+        let scale = 10f64.powi(5 - half as i32);
+        let offset = |digits: &str| {
+            digits
+                .parse::<u32>()
+                .map(|digits| digits as f64 * scale)
+                .map_err(|_| CoordinateError::Malformed)
+        };
+        let reference = GridReference {
+            easting: east as f64 * SQUARE + offset(&digits[..half])?,
+            northing: north as f64 * SQUARE + offset(&digits[half..])?,
+            digits: half as u8,
+        };
+        reference.letters()?;
+        Ok(reference)
+    }
+}
+
+impl Display for GridReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [first, second] = self.letters().map_err(|_| fmt::Error)?;
+        let resolution = self.resolution();
+        let digits = self.digits as usize;
+        let east = (self.easting % SQUARE / resolution).round();
+        let north = (self.northing % SQUARE / resolution).round();
+        write!(f, "{first}{second} {east:0digits$} {north:0digits$}")
+    }
+}
+
+impl From<GridReference> for Coordinate {
+    /// Center of the square on the WGS84 datum
+    fn from(reference: GridReference) -> Self {
+        DatumCoordinate::new(reference.osgb36(), Datum::Osgb36)
+            .to_wgs84()
+            .coordinate
+            .coordinate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letters() {
+        for (input, easting, northing) in [
+            ("SV 0 0", 0., 0.),
+            ("HP 6 1", 460_000., 1_210_000.),
+            ("NN 166 712", 216_600., 771_200.),
+            ("tg5140913177", 651_409., 313_177.),
+        ] {
+            let reference: GridReference = input.parse().unwrap();
+            assert_eq!((reference.easting, reference.northing), (easting, northing));
+        }
+    }
+
+    #[test]
+    fn test_parse_compact() {
+        for input in ["TQ3008080660", "NN166712", "TQ 30080 80660"] {
+            let parsed = Coordinate::parse_detailed(input).unwrap();
+            assert_eq!(
+                parsed.source,
+                crate::ResolutionSource::Parsed(crate::formats::Format::Osgb)
+            );
+            assert!((49. ..61.).contains(&parsed.coordinate.lat), "{input}");
+        }
+    }
+
+    #[cfg(feature = "format_geohash")]
+    #[test]
+    fn test_lowercase_geohashes() {
+        for input in ["su12", "tq30", "sv00", "nn44", "hp12", "st55"] {
+            let parsed = Coordinate::parse_detailed(input).unwrap();
+            assert_eq!(
+                parsed.source,
+                crate::ResolutionSource::Parsed(crate::formats::Format::Geohash),
+                "{input}"
+            );
+        }
+        let hash: crate::formats::geohash::Geohash = "tq30".parse().unwrap();
+        assert_eq!("tq30".parse::<Coordinate>().unwrap(), hash.center());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in ["TQ 30080 80660", "NN 166 712", "SV 0 0", "HP 6 1"] {
+            let reference: GridReference = input.parse().unwrap();
+            assert_eq!(reference.to_string(), input);
+        }
+        let reference: GridReference = "NN 16650 71250".parse().unwrap();
+        let coordinate = Coordinate::from(reference.clone());
+        assert_eq!(
+            GridReference::from_coordinate(&coordinate, 5).unwrap(),
+            reference
+        );
+        let coarse = GridReference::from_coordinate(&coordinate, 3).unwrap();
+        assert_eq!(coarse.to_string(), "NN 166 712");
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("TQ 3008 806".parse::<GridReference>().is_err());
+        // Squares without digits would take words like "NY" or "SO"
+        assert!("NY".parse::<GridReference>().is_err());
+        assert!("TI 30 80".parse::<GridReference>().is_err());
+        assert!("TQ 300800 806600".parse::<GridReference>().is_err());
+        assert!(matches!(
+            "AA 1 1".parse::<GridReference>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!(GridReference::from_coordinate(&Coordinate::new(40., 10.), 5).is_err());
+    }
+}
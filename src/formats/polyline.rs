@@ -0,0 +1,267 @@
+use std::borrow::Borrow;
+
+use crate::{Coordinate, CoordinateError};
+
+/// Decimal places used by Google
+pub const GOOGLE_PRECISION: u8 = 5;
+
+/// Decimal places used by OSRM and Valhalla
+pub const OSRM_PRECISION: u8 = 6;
+
+/// Highest supported number of decimal places
+const MAX_PRECISION: u8 = 10;
+
+/// Offset added to each 5 bit chunk to get printable characters
+const CHARACTER_OFFSET: u8 = 63;
+
+/// Bit marking that another chunk of the same value follows
+const CONTINUATION: u8 = 0x20;
+
+/// Factor between degrees and the encoded integers
+fn factor(precision: u8) -> f64 {
+    10f64.powi(precision.min(MAX_PRECISION) as i32)
+}
+
+/// Encode a sequence of coordinates with `precision` decimal places
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::polyline::{encode, GOOGLE_PRECISION};
+/// let route = [
+///     Coordinate::new(38.5, -120.2),
+///     Coordinate::new(40.7, -120.95),
+///     Coordinate::new(43.252, -126.453),
+/// ];
+/// assert_eq!(encode(&route, GOOGLE_PRECISION), "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+/// ```
+pub fn encode<C: Borrow<Coordinate>>(
+    coordinates: impl IntoIterator<Item = C>,
+    precision: u8,
+) -> String {
+    let factor = factor(precision);
+    let mut encoded = String::new();
+    let mut previous = (0, 0);
+    for coordinate in coordinates {
+        let coordinate = coordinate.borrow();
+        let current = (
+            (coordinate.lat * factor).round() as i64,
+            (coordinate.lng * factor).round() as i64,
+        );
+        encode_value(current.0 - previous.0, &mut encoded);
+        encode_value(current.1 - previous.1, &mut encoded);
+        previous = current;
+    }
+    encoded
+}
+
+/// Append a single signed value
+fn encode_value(value: i64, encoded: &mut String) {
+    // Move the sign into the lowest bit, so small negative values stay short
+    let mut value = if value < 0 { !(value << 1) } else { value << 1 } as u64;
+    while value >= CONTINUATION as u64 {
+        encoded.push((((value & 0x1f) as u8 | CONTINUATION) + CHARACTER_OFFSET) as char);
+        value >>= 5;
+    }
+    encoded.push((value as u8 + CHARACTER_OFFSET) as char);
+}
+
+/// Decode a polyline with `precision` decimal places into coordinates
+///
+/// Errors report the byte offset of the malformed part with
+/// [`CoordinateError::MalformedAt`].
+///
+/// ```
+/// # use recoord::{Coordinate, CoordinateError};
+/// # use recoord::formats::polyline::{decode, GOOGLE_PRECISION};
+/// let route = decode("_p~iF~ps|U_ulLnnqC", GOOGLE_PRECISION).unwrap();
+/// assert_eq!(route, [Coordinate::new(38.5, -120.2), Coordinate::new(40.7, -120.95)]);
+/// // The longitude of the second coordinate is missing
+/// let error = decode("_p~iF~ps|U_ulL", GOOGLE_PRECISION);
+/// assert!(matches!(error, Err(CoordinateError::MalformedAt(14))));
+/// ```
+pub fn decode(polyline: &str, precision: u8) -> Result<Vec<Coordinate>, CoordinateError> {
+    Decoder::new(polyline, precision).collect()
+}
+
+/// Iterator decoding a polyline coordinate by coordinate
+///
+/// The decoder works on the bytes of the input and doesn't allocate. After
+/// an error, no further coordinates are returned.
+///
+/// ```
+/// # use recoord::formats::polyline::{Decoder, OSRM_PRECISION};
+/// let mut decoder = Decoder::new("_izlhA~rlgdF_{geC~ywl@", OSRM_PRECISION);
+/// let first = decoder.next().unwrap().unwrap();
+/// assert_eq!((first.lat, first.lng), (38.5, -120.2));
+/// assert_eq!(decoder.count(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    /// Encoded polyline
+    bytes: &'a [u8],
+    /// Offset of the next byte to read
+    position: usize,
+    /// Factor between degrees and the encoded integers
+    factor: f64,
+    /// Latitude and longitude of the previous coordinate as encoded integers
+    previous: (i64, i64),
+    /// Whether an error ended the decoding
+    failed: bool,
+}
+
+impl<'a> Decoder<'a> {
+    /// Decode a polyline with `precision` decimal places
+    pub fn new(polyline: &'a str, precision: u8) -> Self {
+        Decoder {
+            bytes: polyline.as_bytes(),
+            position: 0,
+            factor: factor(precision),
+            previous: (0, 0),
+            failed: false,
+        }
+    }
+
+    /// Byte offset of the next coordinate in the input
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Read a single signed value
+    fn value(&mut self) -> Result<i64, CoordinateError> {
+        let start = self.position;
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = *self
+                .bytes
+                .get(self.position)
+                .ok_or(CoordinateError::MalformedAt(self.position))?;
+            let chunk = byte
+                .checked_sub(CHARACTER_OFFSET)
+                .filter(|chunk| *chunk < 2 * CONTINUATION)
+                .ok_or(CoordinateError::MalformedAt(self.position))?;
+            // 13 chunks of 5 bits fill 64 bits
+            if shift > 60 {
+                return Err(CoordinateError::MalformedAt(start));
+            }
+            value |= ((chunk & 0x1f) as u64) << shift;
+            shift += 5;
+            self.position += 1;
+            if chunk & CONTINUATION == 0 {
+                break;
+            }
+        }
+        // The lowest bit holds the sign
+        Ok(if value & 1 == 1 {
+            !(value >> 1) as i64
+        } else {
+            (value >> 1) as i64
+        })
+    }
+
+    /// Read the next coordinate
+    fn coordinate(&mut self) -> Result<Coordinate, CoordinateError> {
+        let start = self.position;
+        let overflow = || CoordinateError::MalformedAt(start);
+        let lat = self
+            .previous
+            .0
+            .checked_add(self.value()?)
+            .ok_or_else(overflow)?;
+        let lng = self
+            .previous
+            .1
+            .checked_add(self.value()?)
+            .ok_or_else(overflow)?;
+        self.previous = (lat, lng);
+        Coordinate::try_new(lat as f64 / self.factor, lng as f64 / self.factor)
+            .map_err(|_| CoordinateError::MalformedAt(start))
+    }
+}
+
+impl Iterator for Decoder<'_> {
+    type Item = Result<Coordinate, CoordinateError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.position >= self.bytes.len() {
+            return None;
+        }
+        let coordinate = self.coordinate();
+        self.failed = coordinate.is_err();
+        Some(coordinate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let route = vec![
+            Coordinate::new(50.941278, 6.958281),
+            Coordinate::new(-33.868820, 151.209296),
+            Coordinate::new(0., -180.),
+        ];
+        for precision in [GOOGLE_PRECISION, OSRM_PRECISION] {
+            let encoded = encode(&route, precision);
+            let decoded = decode(&encoded, precision).unwrap();
+            for (decoded, original) in decoded.iter().zip(&route) {
+                assert!((decoded.lat - original.lat).abs() < 1. / factor(precision));
+                assert!((decoded.lng - original.lng).abs() < 1. / factor(precision));
+            }
+        }
+        assert_eq!(encode(Vec::<Coordinate>::new(), GOOGLE_PRECISION), "");
+        assert_eq!(decode("", GOOGLE_PRECISION).unwrap(), []);
+    }
+
+    #[test]
+    fn test_osrm_precision() {
+        let route = [
+            Coordinate::new(38.5, -120.2),
+            Coordinate::new(40.7, -120.95),
+        ];
+        assert_eq!(encode(route, OSRM_PRECISION), "_izlhA~rlgdF_{geC~ywl@");
+    }
+
+    #[test]
+    fn test_errors() {
+        // Character below the offset
+        assert!(matches!(
+            decode("_p~iF ps|U", GOOGLE_PRECISION),
+            Err(CoordinateError::MalformedAt(5))
+        ));
+        // Value ending in a continuation chunk
+        assert!(matches!(
+            decode("_p~iF~ps|", GOOGLE_PRECISION),
+            Err(CoordinateError::MalformedAt(9))
+        ));
+        // Too many chunks for a single value
+        assert!(matches!(
+            decode(&format!("_p~iF{}", "~".repeat(20)), GOOGLE_PRECISION),
+            Err(CoordinateError::MalformedAt(5))
+        ));
+        // Latitude out of range
+        let encoded = encode(
+            [Coordinate::new(10., 0.), Coordinate::new(100., 0.)],
+            GOOGLE_PRECISION,
+        );
+        let mut decoder = Decoder::new(&encoded, GOOGLE_PRECISION);
+        assert!(decoder.next().unwrap().is_ok());
+        let start = decoder.position();
+        assert!(matches!(
+            decoder.next(),
+            Some(Err(CoordinateError::MalformedAt(position))) if position == start
+        ));
+        assert!(decoder.next().is_none());
+        // Latitude overflowing the sum of the previous ones
+        let mut encoded = encode([Coordinate::new(10., 0.)], GOOGLE_PRECISION);
+        let start = encoded.len();
+        encode_value(i64::MAX, &mut encoded);
+        encode_value(0, &mut encoded);
+        assert!(matches!(
+            decode(&encoded, GOOGLE_PRECISION),
+            Err(CoordinateError::MalformedAt(position)) if position == start
+        ));
+    }
+}
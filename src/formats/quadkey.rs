@@ -0,0 +1,204 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use super::{
+    tile::{Tile, MAX_ZOOM},
+    Direction,
+};
+use crate::{BoundingBox, Coordinate, CoordinateError, Precision};
+
+/// A Bing Maps quadkey naming a tile by its path from the whole world (e.g. "120210233")
+///
+/// Each digit picks one quarter of the previous tile: 0 north-west, 1
+/// north-east, 2 south-west and 3 south-east. The number of digits is the
+/// level of detail, which equals the zoom level of the [`Tile`].
+///
+/// Quadkeys aren't tried when parsing a [`Coordinate`], since they can't be
+/// told apart from geohashes made up of digits.
+///
+/// ```
+/// # use recoord::formats::{quadkey::Quadkey, tile::Tile};
+/// let quadkey: Quadkey = "213".parse().unwrap();
+/// assert_eq!(quadkey.tile(), Tile { z: 3, x: 3, y: 5 });
+/// assert_eq!(Quadkey::try_from(quadkey.tile()).unwrap().to_string(), "213");
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quadkey {
+    /// Tile named by the quadkey
+    tile: Tile,
+}
+
+impl Quadkey {
+    /// Quadkey of the tile at a level of detail (1 to [`MAX_ZOOM`]) containing a coordinate
+    ///
+    /// Latitudes beyond ±[`MAX_LATITUDE`](super::tile::MAX_LATITUDE) are rejected.
+    pub fn at(coordinate: &Coordinate, level: u8) -> Result<Self, CoordinateError> {
+        Quadkey::try_from(Tile::at(coordinate, level)?)
+    }
+
+    /// Tile named by the quadkey
+    pub fn tile(&self) -> Tile {
+        self.tile
+    }
+
+    /// Level of detail (the number of digits)
+    pub fn level(&self) -> u8 {
+        self.tile.z
+    }
+
+    /// Area covered by the tile
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.tile.bounding_box()
+    }
+
+    /// Center of the tile
+    pub fn center(&self) -> Coordinate {
+        self.tile.center()
+    }
+
+    /// Precision of the center, which is half the size of the tile
+    pub fn precision(&self) -> Precision {
+        self.tile.precision()
+    }
+
+    /// The quadkey without its last digit (None for single digits)
+    pub fn parent(&self) -> Option<Quadkey> {
+        self.tile
+            .parent()
+            .and_then(|parent| Quadkey::try_from(parent).ok())
+    }
+
+    /// The four quadkeys with one more digit, in the order of the digits 0 to 3
+    pub fn children(&self) -> Option<[Quadkey; 4]> {
+        self.tile
+            .children()
+            .map(|tiles| tiles.map(|tile| Quadkey { tile }))
+    }
+
+    /// The adjacent quadkey of the same level in a direction
+    ///
+    /// Neighbours wrap around the antimeridian, but there are none beyond the
+    /// northern and southern edge of the map.
+    ///
+    /// ```
+    /// # use recoord::formats::{quadkey::Quadkey, Direction};
+    /// let quadkey: Quadkey = "0".parse().unwrap();
+    /// assert_eq!(quadkey.neighbor(Direction::West).unwrap().to_string(), "1");
+    /// assert_eq!(quadkey.neighbor(Direction::North), None);
+    /// ```
+    pub fn neighbor(&self, direction: Direction) -> Option<Quadkey> {
+        self.tile.neighbor(direction).map(|tile| Quadkey { tile })
+    }
+
+    /// All eight neighbours in the order of [`Direction::ALL`]
+    pub fn neighbors(&self) -> [Option<Quadkey>; 8] {
+        self.tile
+            .neighbors()
+            .map(|tile| tile.map(|tile| Quadkey { tile }))
+    }
+}
+
+impl Display for Quadkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for bit in (0..self.tile.z).rev() {
+            let digit = ((self.tile.x >> bit) & 1) | (((self.tile.y >> bit) & 1) << 1);
+            write!(f, "{digit}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Quadkey {
+    type Err = CoordinateError;
+
+    /// Parse a quadkey of 1 to [`MAX_ZOOM`] digits
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str = str.trim();
+        if str.is_empty() || str.len() > MAX_ZOOM as usize {
+            return Err(CoordinateError::Malformed);
+        }
+        let (mut x, mut y) = (0, 0);
+        for char in str.chars() {
+            let digit = match char {
+                '0'..='3' => char as u32 - '0' as u32,
+                _ => return Err(CoordinateError::Malformed),
+            };
+            x = (x << 1) | (digit & 1);
+            y = (y << 1) | (digit >> 1);
+        }
+        Ok(Quadkey {
+            tile: Tile {
+                z: str.len() as u8,
+                x,
+                y,
+            },
+        })
+    }
+}
+
+impl TryFrom<Tile> for Quadkey {
+    type Error = CoordinateError;
+
+    /// The tile of zoom level 0 (the whole world) has no quadkey
+    fn try_from(tile: Tile) -> Result<Self, Self::Error> {
+        if tile.z == 0 {
+            return Err(CoordinateError::InvalidValue);
+        }
+        Ok(Quadkey { tile })
+    }
+}
+
+impl From<Quadkey> for Tile {
+    fn from(quadkey: Quadkey) -> Self {
+        quadkey.tile
+    }
+}
+
+impl From<Quadkey> for Coordinate {
+    fn from(quadkey: Quadkey) -> Self {
+        quadkey.center()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let quadkey: Quadkey = "120210233".parse().unwrap();
+        assert_eq!(quadkey.level(), 9);
+        assert_eq!(quadkey.to_string(), "120210233");
+        let center = quadkey.center();
+        assert_eq!(Quadkey::at(&center, 9).unwrap(), quadkey);
+        assert!(quadkey.bounding_box().contains(&center));
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let quadkey: Quadkey = "1202".parse().unwrap();
+        assert_eq!(quadkey.parent().unwrap().to_string(), "120");
+        let children = quadkey.children().unwrap().map(|child| child.to_string());
+        assert_eq!(children, ["12020", "12021", "12022", "12023"]);
+        assert_eq!("1".parse::<Quadkey>().unwrap().parent(), None);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let quadkey: Quadkey = "12".parse().unwrap();
+        let neighbors = quadkey
+            .neighbors()
+            .map(|neighbor| neighbor.map(|neighbor| neighbor.to_string()));
+        let expected = ["10", "11", "13", "31", "30", "21", "03", "01"];
+        assert_eq!(neighbors, expected.map(|key| Some(key.to_string())));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("".parse::<Quadkey>().is_err());
+        assert!("1204".parse::<Quadkey>().is_err());
+        assert!("0".repeat(31).parse::<Quadkey>().is_err());
+        assert!(Quadkey::at(&Coordinate::new(0., 0.), 0).is_err());
+        assert!(Quadkey::try_from(Tile { z: 0, x: 0, y: 0 }).is_err());
+    }
+}
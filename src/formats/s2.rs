@@ -0,0 +1,530 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use super::Direction;
+use crate::{geometry::Geometry, BoundingBox, Coordinate, CoordinateError, Position, Precision};
+
+/// Finest level of S2 cells
+pub const MAX_LEVEL: u8 = 30;
+
+/// Number of leaf cells along an edge of a cube face
+const MAX_SIZE: u32 = 1 << MAX_LEVEL;
+
+/// Bits of the position along the Hilbert curve, including the trailing marker bit
+const POS_BITS: u32 = 2 * MAX_LEVEL as u32 + 1;
+
+/// Orientation bit swapping the i and j axes
+const SWAP_MASK: usize = 1;
+
+/// Orientation bit inverting both axes
+const INVERT_MASK: usize = 2;
+
+/// Position of the child (by i and j bit) along the curve, per orientation
+const IJ_TO_POS: [[u64; 4]; 4] = [[0, 1, 3, 2], [0, 3, 1, 2], [2, 3, 1, 0], [2, 1, 3, 0]];
+
+/// i and j bit of the child at a position along the curve, per orientation
+const POS_TO_IJ: [[u32; 4]; 4] = [[0, 1, 3, 2], [0, 2, 3, 1], [3, 2, 0, 1], [3, 1, 0, 2]];
+
+/// Change of orientation when descending into the child at a position
+const POS_TO_ORIENTATION: [usize; 4] = [SWAP_MASK, 0, 0, INVERT_MASK | SWAP_MASK];
+
+/// A cell of Google's S2 grid (e.g. token "89c25")
+///
+/// The sphere is projected onto the six faces of a cube, each of which is
+/// divided into quadrants along a Hilbert curve down to level 30, where
+/// cells are about a centimeter wide. The 64 bit id is usually written as
+/// a token, its hexadecimal digits without trailing zeros.
+///
+/// Since bare tokens can't be told apart from geohashes, they're only tried
+/// when parsing a [`Coordinate`] with an "s2:" prefix (e.g. "s2:89c25").
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::{s2::S2CellId, Direction};
+/// let cell = S2CellId::at(&Coordinate::new(0., 0.), 30).unwrap();
+/// assert_eq!(cell.id(), 0x1000000000000001);
+/// let cell: S2CellId = "89c25".parse().unwrap();
+/// assert_eq!((cell.face(), cell.level()), (4, 8));
+/// assert_eq!(cell.parent().unwrap().to_string(), "89c24");
+/// assert_eq!(cell.neighbor(Direction::North).unwrap().level(), 8);
+/// let center: Coordinate = "s2:89c25".parse().unwrap();
+/// assert_eq!(center, cell.center());
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct S2CellId {
+    /// Face, position along the Hilbert curve and marker bit
+    id: u64,
+}
+
+impl S2CellId {
+    /// Cell at a level (0 to 30) containing a coordinate
+    pub fn at(coordinate: &Coordinate, level: u8) -> Result<Self, CoordinateError> {
+        if level > MAX_LEVEL || !coordinate.lat.is_finite() || !coordinate.lng.is_finite() {
+            return Err(CoordinateError::InvalidValue);
+        }
+        let (lat, lng) = (coordinate.lat.to_radians(), coordinate.lng.to_radians());
+        let xyz = [lat.cos() * lng.cos(), lat.cos() * lng.sin(), lat.sin()];
+        let (face, u, v) = xyz_to_face_uv(xyz);
+        let leaf = S2CellId::from_face_ij(face, st_to_ij(uv_to_st(u)), st_to_ij(uv_to_st(v)));
+        Ok(leaf.parent_at(level))
+    }
+
+    /// Whole face (0 to 5) of the cube
+    pub fn from_face(face: u8) -> Result<Self, CoordinateError> {
+        if face > 5 {
+            return Err(CoordinateError::InvalidValue);
+        }
+        Ok(S2CellId {
+            id: (u64::from(face) << POS_BITS) | (1 << (POS_BITS - 1)),
+        })
+    }
+
+    /// 64 bit id of the cell
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Face of the cube (0 to 5) the cell lies on
+    pub fn face(&self) -> u8 {
+        (self.id >> POS_BITS) as u8
+    }
+
+    /// Level of the cell, from 0 (a whole face) to 30
+    pub fn level(&self) -> u8 {
+        MAX_LEVEL - (self.id.trailing_zeros() / 2) as u8
+    }
+
+    /// Hexadecimal id without trailing zeros
+    pub fn token(&self) -> String {
+        let hex = format!("{:016x}", self.id);
+        hex.trim_end_matches('0').to_string()
+    }
+
+    /// Center of the cell
+    pub fn center(&self) -> Coordinate {
+        let (face, i, j, size) = self.face_ij_range();
+        let half = f64::from(size) / 2.;
+        face_st_to_coordinate(
+            face,
+            (f64::from(i) + half) / f64::from(MAX_SIZE),
+            (f64::from(j) + half) / f64::from(MAX_SIZE),
+        )
+    }
+
+    /// Corners of the cell, counterclockwise
+    pub fn vertices(&self) -> [Coordinate; 4] {
+        let (face, i, j, size) = self.face_ij_range();
+        let st = |i: u32| f64::from(i) / f64::from(MAX_SIZE);
+        let (s0, t0, s1, t1) = (st(i), st(j), st(i + size), st(j + size));
+        [(s0, t0), (s1, t0), (s1, t1), (s0, t1)].map(|(s, t)| face_st_to_coordinate(face, s, t))
+    }
+
+    /// Smallest box containing the corners of the cell
+    ///
+    /// The edges of a cell are great circle arcs, which bulge slightly beyond
+    /// the corners. Cells containing a pole reach up to it over all
+    /// longitudes.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let vertices = self.vertices();
+        let bbox = BoundingBox::from_points(vertices).expect("cells have corners");
+        let pole = |lat: f64| S2CellId::at(&Coordinate::new(lat, 0.), MAX_LEVEL).expect("valid");
+        if self.contains(&pole(90.)) {
+            BoundingBox::new(bbox.south(), -180., 90., 180.).expect("valid box")
+        } else if self.contains(&pole(-90.)) {
+            BoundingBox::new(-90., -180., bbox.north(), 180.).expect("valid box")
+        } else {
+            bbox
+        }
+    }
+
+    /// Precision of the center, half the size of the bounding box
+    pub fn precision(&self) -> Precision {
+        self.bounding_box().precision()
+    }
+
+    /// Whether another cell lies within this one (or is the same)
+    pub fn contains(&self, other: &S2CellId) -> bool {
+        let lsb = self.lsb();
+        other.id >= self.id - (lsb - 1) && other.id <= self.id + (lsb - 1)
+    }
+
+    /// Cell at the next coarser level containing this one (None at level 0)
+    pub fn parent(&self) -> Option<S2CellId> {
+        let level = self.level().checked_sub(1)?;
+        Some(self.parent_at(level))
+    }
+
+    /// Four cells at the next finer level, in Hilbert curve order (None at level 30)
+    pub fn children(&self) -> Option<[S2CellId; 4]> {
+        if self.level() == MAX_LEVEL {
+            return None;
+        }
+        let lsb = self.lsb();
+        let first = self.id - lsb + (lsb >> 2);
+        Some([0, 1, 2, 3].map(|k| S2CellId {
+            id: first + k * (lsb >> 1),
+        }))
+    }
+
+    /// Cell of the same level in a direction (None beyond a pole)
+    ///
+    /// S2 cells aren't aligned to meridians, so this is the first other cell
+    /// reached walking from the center in the direction. It shares an edge or
+    /// a corner with this one.
+    pub fn neighbor(&self, direction: Direction) -> Option<S2CellId> {
+        let level = self.level();
+        let center = self.center();
+        let (d_lat, d_lng) = direction.offset();
+        // Degrees of longitude are shorter away from the equator
+        let scale = 1. / center.lat.to_radians().cos().max(f64::EPSILON);
+        let cell_at = |distance: f64| {
+            let lat = center.lat + d_lat * distance;
+            if !(-90.0..=90.0).contains(&lat) {
+                return None;
+            }
+            let lng = center.lng + d_lng * distance * scale;
+            S2CellId::at(&Coordinate { lat, lng }, level).ok()
+        };
+        // Find a distance beyond the edge, then narrow it down
+        let (mut inside, mut outside) = (0., self.precision().lat);
+        while cell_at(outside)? == *self {
+            if outside > 180. {
+                return None;
+            }
+            (inside, outside) = (outside, 2. * outside);
+        }
+        for _ in 0..64 {
+            let middle = 0.5 * (inside + outside);
+            if cell_at(middle)? == *self {
+                inside = middle;
+            } else {
+                outside = middle;
+            }
+        }
+        cell_at(outside)
+    }
+
+    /// All eight neighbours in the order of [`Direction::ALL`]
+    pub fn neighbors(&self) -> [Option<S2CellId>; 8] {
+        Direction::ALL.map(|direction| self.neighbor(direction))
+    }
+
+    /// Cells of the same level sharing an edge, also across faces of the cube
+    ///
+    /// They're ordered down, right, up and left in the coordinates of the face.
+    pub fn edge_neighbors(&self) -> [S2CellId; 4] {
+        let level = self.level();
+        let (face, i, j, size) = self.face_ij_range();
+        let (i, j, size) = (i64::from(i), i64::from(j), i64::from(size));
+        [(i, j - size), (i + size, j), (i, j + size), (i - size, j)]
+            .map(|(i, j)| S2CellId::from_face_ij_wrap(face, i, j).parent_at(level))
+    }
+
+    /// Value of the lowest set bit, marking the level
+    fn lsb(&self) -> u64 {
+        self.id & self.id.wrapping_neg()
+    }
+
+    /// Cell containing this one at a coarser or equal level
+    fn parent_at(&self, level: u8) -> S2CellId {
+        let lsb = 1u64 << (2 * u32::from(MAX_LEVEL - level));
+        S2CellId {
+            id: (self.id & !(lsb - 1)) | lsb,
+        }
+    }
+
+    /// Leaf cell at leaf coordinates on a face
+    fn from_face_ij(face: u8, i: u32, j: u32) -> S2CellId {
+        let mut orientation = usize::from(face) & SWAP_MASK;
+        let mut pos = 0u64;
+        for k in (0..u32::from(MAX_LEVEL)).rev() {
+            let ij = (((i >> k) & 1) << 1 | ((j >> k) & 1)) as usize;
+            let sub = IJ_TO_POS[orientation][ij];
+            pos = (pos << 2) | sub;
+            orientation ^= POS_TO_ORIENTATION[sub as usize];
+        }
+        S2CellId {
+            id: (u64::from(face) << POS_BITS) | (pos << 1) | 1,
+        }
+    }
+
+    /// Leaf cell at leaf coordinates, which may lie just beyond the face
+    fn from_face_ij_wrap(face: u8, i: i64, j: i64) -> S2CellId {
+        let max = i64::from(MAX_SIZE);
+        // Project onto the adjacent face through the point just outside the edge
+        let uv = |i: i64| {
+            let i = i.clamp(-1, max);
+            let limit = 1. + f64::EPSILON;
+            ((2 * (i - max / 2) + 1) as f64 / max as f64).clamp(-limit, limit)
+        };
+        let (face, u, v) = xyz_to_face_uv(face_uv_to_xyz(face, uv(i), uv(j)));
+        S2CellId::from_face_ij(face, st_to_ij(0.5 * (u + 1.)), st_to_ij(0.5 * (v + 1.)))
+    }
+
+    /// Face, leaf coordinates of the lower corner and size in leaf cells
+    fn face_ij_range(&self) -> (u8, u32, u32, u32) {
+        let face = self.face();
+        let mut orientation = usize::from(face) & SWAP_MASK;
+        let (mut i, mut j) = (0, 0);
+        for k in (0..u32::from(MAX_LEVEL)).rev() {
+            let sub = ((self.id >> (2 * k + 1)) & 3) as usize;
+            let ij = POS_TO_IJ[orientation][sub];
+            i |= (ij >> 1) << k;
+            j |= (ij & 1) << k;
+            orientation ^= POS_TO_ORIENTATION[sub];
+        }
+        let size = 1u32 << (MAX_LEVEL - self.level());
+        (face, i & !(size - 1), j & !(size - 1), size)
+    }
+}
+
+/// Face of the cube a direction points to, and the position on it
+fn xyz_to_face_uv([x, y, z]: [f64; 3]) -> (u8, f64, f64) {
+    let axis = if x.abs() >= y.abs() && x.abs() >= z.abs() {
+        0
+    } else if y.abs() >= z.abs() {
+        1
+    } else {
+        2
+    };
+    let face = if [x, y, z][axis] < 0. { axis + 3 } else { axis };
+    let (u, v) = match face {
+        0 => (y / x, z / x),
+        1 => (-x / y, z / y),
+        2 => (-x / z, -y / z),
+        3 => (z / x, y / x),
+        4 => (z / y, -x / y),
+        _ => (-y / z, -x / z),
+    };
+    (face as u8, u, v)
+}
+
+/// Direction of a position on a face of the cube
+fn face_uv_to_xyz(face: u8, u: f64, v: f64) -> [f64; 3] {
+    match face {
+        0 => [1., u, v],
+        1 => [-u, 1., v],
+        2 => [-u, -v, 1.],
+        3 => [-1., -v, -u],
+        4 => [v, -1., -u],
+        _ => [v, u, -1.],
+    }
+}
+
+/// Quadratic projection making cells of a level about equally large
+fn uv_to_st(u: f64) -> f64 {
+    if u >= 0. {
+        0.5 * (1. + 3. * u).sqrt()
+    } else {
+        1. - 0.5 * (1. - 3. * u).sqrt()
+    }
+}
+
+/// Inverse of [`uv_to_st`]
+fn st_to_uv(s: f64) -> f64 {
+    if s >= 0.5 {
+        (4. * s * s - 1.) / 3.
+    } else {
+        (1. - 4. * (1. - s) * (1. - s)) / 3.
+    }
+}
+
+/// Leaf coordinate containing a position from 0 to 1 along a face
+fn st_to_ij(s: f64) -> u32 {
+    (f64::from(MAX_SIZE) * s)
+        .floor()
+        .clamp(0., f64::from(MAX_SIZE - 1)) as u32
+}
+
+/// Coordinate of a position from 0 to 1 along both axes of a face
+fn face_st_to_coordinate(face: u8, s: f64, t: f64) -> Coordinate {
+    let [x, y, z] = face_uv_to_xyz(face, st_to_uv(s), st_to_uv(t));
+    Coordinate::new(z.atan2(x.hypot(y)).to_degrees(), y.atan2(x).to_degrees())
+}
+
+impl Display for S2CellId {
+    /// Write the token
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.token())
+    }
+}
+
+impl FromStr for S2CellId {
+    type Err = CoordinateError;
+
+    /// Read a token of up to 16 hexadecimal digits, optionally prefixed with "s2:"
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str = str.trim();
+        let str = str.strip_prefix("s2:").unwrap_or(str);
+        if str.is_empty() || str.len() > 16 || !str.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(CoordinateError::Malformed);
+        }
+        let id = u64::from_str_radix(&format!("{str:0<16}"), 16)
+            .map_err(|_| CoordinateError::Malformed)?;
+        S2CellId::try_from(id)
+    }
+}
+
+impl TryFrom<u64> for S2CellId {
+    type Error = CoordinateError;
+
+    /// Only valid cell ids are accepted
+    fn try_from(id: u64) -> Result<Self, Self::Error> {
+        if id == 0 || id >> POS_BITS > 5 || !id.trailing_zeros().is_multiple_of(2) {
+            return Err(CoordinateError::InvalidValue);
+        }
+        Ok(S2CellId { id })
+    }
+}
+
+impl From<S2CellId> for u64 {
+    fn from(cell: S2CellId) -> Self {
+        cell.id
+    }
+}
+
+impl From<S2CellId> for Coordinate {
+    /// Center of the cell
+    fn from(cell: S2CellId) -> Self {
+        cell.center()
+    }
+}
+
+impl From<S2CellId> for Geometry {
+    /// Polygon of the cell
+    fn from(cell: S2CellId) -> Self {
+        let mut ring: Vec<Position> = cell.vertices().into_iter().map(Position::new).collect();
+        ring.push(ring[0].clone());
+        Geometry::Polygon(vec![ring])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_vectors() {
+        let cell = S2CellId::at(&Coordinate::new(0., 0.), MAX_LEVEL).unwrap();
+        assert_eq!(cell.id(), 1152921504606846977);
+        assert_eq!(cell.token(), "1000000000000001");
+        let manhattan = S2CellId::at(&Coordinate::new(40.7128, -74.006), 8).unwrap();
+        assert_eq!(manhattan.to_string(), "89c25");
+        let faces: Vec<String> = (0..6)
+            .map(|face| S2CellId::from_face(face).unwrap().token())
+            .collect();
+        assert_eq!(faces, ["1", "3", "5", "7", "9", "b"]);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for (lat, lng) in [
+            (50.9413, 6.9583),
+            (-33.8568, 151.2153),
+            (89.9, 45.),
+            (-12., -179.9),
+        ] {
+            let coordinate = Coordinate::new(lat, lng);
+            let cell = S2CellId::at(&coordinate, MAX_LEVEL).unwrap();
+            let center = cell.center();
+            assert!((center.lat - lat).abs() < 1e-6 && (center.lng - lng).abs() < 1e-6);
+            let cell = S2CellId::at(&coordinate, 12).unwrap();
+            assert!(cell.bounding_box().contains(&coordinate));
+            assert_eq!(cell.token().parse::<S2CellId>().unwrap(), cell);
+        }
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let cell = S2CellId::at(&Coordinate::new(50.9413, 6.9583), 14).unwrap();
+        let parent = cell.parent().unwrap();
+        assert_eq!(parent.level(), 13);
+        assert!(parent.contains(&cell) && !cell.contains(&parent));
+        let children = parent.children().unwrap();
+        assert!(children.contains(&cell));
+        assert!(children.iter().all(|child| child.parent() == Some(parent)));
+        assert_eq!(S2CellId::from_face(3).unwrap().parent(), None);
+        let leaf = S2CellId::at(&Coordinate::new(50.9413, 6.9583), MAX_LEVEL).unwrap();
+        assert_eq!(leaf.children(), None);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let neighbors = S2CellId::from_face(0)
+            .unwrap()
+            .edge_neighbors()
+            .map(|cell| cell.token());
+        assert_eq!(neighbors, ["b", "3", "5", "9"]);
+        for level in [1, 10, MAX_LEVEL] {
+            // The corner cell of face 0 borders faces 4 and 5
+            let cell = S2CellId::at(&Coordinate::new(-35., -44.9), level).unwrap();
+            for neighbor in cell.edge_neighbors() {
+                assert_eq!(neighbor.level(), level);
+                assert!(neighbor.edge_neighbors().contains(&cell));
+            }
+        }
+    }
+
+    #[test]
+    fn test_directions() {
+        let face = S2CellId::from_face(0).unwrap();
+        let tokens = [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ]
+        .map(|direction| face.neighbor(direction).unwrap().token());
+        assert_eq!(tokens, ["5", "3", "b", "9"]);
+        let cell = S2CellId::at(&Coordinate::new(10., 10.), 12).unwrap();
+        let neighbors = cell.neighbors().map(Option::unwrap);
+        for (direction, neighbor) in Direction::ALL.into_iter().zip(neighbors) {
+            assert_eq!(neighbor.level(), 12);
+            assert_ne!(neighbor, cell);
+            let (d_lat, d_lng) = direction.offset();
+            let (center, other) = (cell.center(), neighbor.center());
+            assert!((other.lat - center.lat) * d_lat >= 0., "{direction:?}");
+            assert!((other.lng - center.lng) * d_lng >= 0., "{direction:?}");
+        }
+        // Edge neighbours lie straight north, east, south and west on face 0
+        for neighbor in cell.edge_neighbors() {
+            assert!(neighbors.iter().step_by(2).any(|other| *other == neighbor));
+        }
+        let pole = S2CellId::at(&Coordinate::new(90., 0.), 5).unwrap();
+        assert_eq!(pole.neighbor(Direction::North), None);
+        assert_eq!(pole.neighbor(Direction::South).unwrap().level(), 5);
+        // The north face is centered on the pole, which has no east or west
+        let face = S2CellId::from_face(2).unwrap();
+        assert_eq!(face.neighbor(Direction::East), None);
+        assert_eq!(face.neighbor(Direction::South).unwrap().level(), 0);
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        let parsed = Coordinate::parse_detailed(" s2:89c25 ").unwrap();
+        assert_eq!(
+            parsed.source,
+            crate::ResolutionSource::Parsed(crate::formats::Format::S2)
+        );
+        let cell: S2CellId = "89c25".parse().unwrap();
+        assert_eq!(parsed.coordinate, cell.center());
+        assert_eq!(parsed.precision, Some(cell.precision()));
+        assert!(Coordinate::parse_detailed("s2:89c25g").is_err());
+    }
+
+    #[test]
+    fn test_pole() {
+        let cell = S2CellId::at(&Coordinate::new(90., 0.), 5).unwrap();
+        let bbox = cell.bounding_box();
+        assert_eq!((bbox.north(), bbox.west(), bbox.east()), (90., -180., 180.));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for token in ["", "X", "89c25g", "10000000000000000", "c", "2", "s2:"] {
+            assert!(token.parse::<S2CellId>().is_err(), "{token}");
+        }
+        assert!(S2CellId::at(&Coordinate::new(0., 0.), 31).is_err());
+        assert!(S2CellId::from_face(6).is_err());
+    }
+}
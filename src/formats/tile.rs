@@ -0,0 +1,446 @@
+use std::{f64::consts::PI, fmt, fmt::Display, str::FromStr};
+
+use super::Direction;
+use crate::{BoundingBox, Coordinate, CoordinateError, Precision};
+
+/// Northernmost latitude of the Web Mercator projection (atan(sinh(π)))
+///
+/// The map is cut off here to make it square. The southernmost latitude is its negative.
+pub const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Highest supported zoom level
+pub const MAX_ZOOM: u8 = 30;
+
+/// Width and height of a tile in pixels
+pub const TILE_SIZE: f64 = 256.;
+
+/// Radius of the sphere used by Web Mercator (the WGS 84 semi-major axis)
+const RADIUS: f64 = 6_378_137.;
+
+/// Reject latitudes outside of the Web Mercator map
+fn check_latitude(coordinate: &Coordinate) -> Result<(), CoordinateError> {
+    if (-MAX_LATITUDE..=MAX_LATITUDE).contains(&coordinate.lat) {
+        Ok(())
+    } else {
+        Err(CoordinateError::InvalidValue)
+    }
+}
+
+/// Limit the latitude of a coordinate to the Web Mercator map
+fn clamp_latitude(coordinate: &Coordinate) -> Coordinate {
+    Coordinate {
+        lat: coordinate.lat.clamp(-MAX_LATITUDE, MAX_LATITUDE),
+        lng: coordinate.lng,
+    }
+}
+
+/// Position on the Web Mercator projection (EPSG:3857) in meters
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::tile::WebMercator;
+/// let point = WebMercator::try_from(Coordinate::new(0., 180.)).unwrap();
+/// assert!((point.x - 20_037_508.34).abs() < 0.01);
+/// assert!(WebMercator::try_from(Coordinate::new(89., 0.)).is_err());
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WebMercator {
+    /// Easting in meters from the prime meridian
+    pub x: f64,
+    /// Northing in meters from the equator
+    pub y: f64,
+}
+
+impl WebMercator {
+    /// Project a coordinate, clamping the latitude to ±[`MAX_LATITUDE`]
+    pub fn clamped(coordinate: &Coordinate) -> Self {
+        let coordinate = clamp_latitude(coordinate);
+        WebMercator {
+            x: RADIUS * coordinate.lng.to_radians(),
+            y: RADIUS * (PI / 4. + coordinate.lat.to_radians() / 2.).tan().ln(),
+        }
+    }
+}
+
+impl TryFrom<Coordinate> for WebMercator {
+    type Error = CoordinateError;
+
+    /// Project a coordinate, rejecting latitudes beyond ±[`MAX_LATITUDE`]
+    fn try_from(coordinate: Coordinate) -> Result<Self, Self::Error> {
+        check_latitude(&coordinate)?;
+        Ok(Self::clamped(&coordinate))
+    }
+}
+
+impl From<WebMercator> for Coordinate {
+    fn from(point: WebMercator) -> Self {
+        Coordinate {
+            lat: (point.y / RADIUS).sinh().atan().to_degrees(),
+            lng: (point.x / RADIUS).to_degrees(),
+        }
+    }
+}
+
+/// Pixel position on the world map at a zoom level
+///
+/// The origin is the north-west corner and the map is [`TILE_SIZE`] · 2^zoom pixels wide.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pixel {
+    /// Zoom level of the map
+    pub zoom: u8,
+    /// Pixels from the western edge (the antimeridian)
+    pub x: f64,
+    /// Pixels from the northern edge
+    pub y: f64,
+}
+
+impl Pixel {
+    /// Pixel of a coordinate, rejecting latitudes beyond ±[`MAX_LATITUDE`]
+    pub fn new(coordinate: &Coordinate, zoom: u8) -> Result<Self, CoordinateError> {
+        check_latitude(coordinate)?;
+        Self::clamped(coordinate, zoom)
+    }
+
+    /// Pixel of a coordinate, clamping the latitude to ±[`MAX_LATITUDE`]
+    pub fn clamped(coordinate: &Coordinate, zoom: u8) -> Result<Self, CoordinateError> {
+        if zoom > MAX_ZOOM {
+            return Err(CoordinateError::InvalidValue);
+        }
+        let size = map_size(zoom);
+        let point = WebMercator::clamped(coordinate);
+        Ok(Pixel {
+            zoom,
+            x: (0.5 + point.x / (2. * PI * RADIUS)) * size,
+            y: (0.5 - point.y / (2. * PI * RADIUS)) * size,
+        })
+    }
+
+    /// Tile containing the pixel
+    pub fn tile(&self) -> Tile {
+        let max = (1u32 << self.zoom) - 1;
+        let index = |value: f64| ((value / TILE_SIZE).floor().max(0.) as u32).min(max);
+        Tile {
+            z: self.zoom,
+            x: index(self.x),
+            y: index(self.y),
+        }
+    }
+}
+
+impl From<Pixel> for Coordinate {
+    fn from(pixel: Pixel) -> Self {
+        let size = map_size(pixel.zoom);
+        Coordinate::from(WebMercator {
+            x: (pixel.x / size - 0.5) * 2. * PI * RADIUS,
+            y: (0.5 - pixel.y / size) * 2. * PI * RADIUS,
+        })
+    }
+}
+
+/// Width of the world map in pixels at a zoom level
+fn map_size(zoom: u8) -> f64 {
+    TILE_SIZE * (1u64 << zoom) as f64
+}
+
+/// A slippy map tile as used by OpenStreetMap ("z/x/y")
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::tile::Tile;
+/// let tile = Tile::at(&Coordinate::new(50.9413, 6.9583), 14).unwrap();
+/// assert_eq!(tile.to_string(), "14/8508/5489");
+/// assert!(tile.bounding_box().contains(&Coordinate::new(50.9413, 6.9583)));
+/// assert_eq!("14/8508/5489".parse::<Tile>().unwrap(), tile);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile {
+    /// Zoom level
+    pub z: u8,
+    /// Column from west to east
+    pub x: u32,
+    /// Row from north to south
+    pub y: u32,
+}
+
+impl Tile {
+    /// Create a tile, checking that it exists at the zoom level
+    pub fn new(z: u8, x: u32, y: u32) -> Result<Self, CoordinateError> {
+        if z > MAX_ZOOM || x >> z != 0 || y >> z != 0 {
+            return Err(CoordinateError::InvalidValue);
+        }
+        Ok(Tile { z, x, y })
+    }
+
+    /// Tile containing a coordinate, rejecting latitudes beyond ±[`MAX_LATITUDE`]
+    pub fn at(coordinate: &Coordinate, z: u8) -> Result<Self, CoordinateError> {
+        Pixel::new(coordinate, z).map(|pixel| pixel.tile())
+    }
+
+    /// Tile containing a coordinate, clamping the latitude to ±[`MAX_LATITUDE`]
+    pub fn at_clamped(coordinate: &Coordinate, z: u8) -> Result<Self, CoordinateError> {
+        Pixel::clamped(coordinate, z).map(|pixel| pixel.tile())
+    }
+
+    /// Area covered by the tile
+    pub fn bounding_box(&self) -> BoundingBox {
+        let latitude = |y: u32| {
+            Coordinate::from(Pixel {
+                zoom: self.z,
+                x: 0.,
+                y: y as f64 * TILE_SIZE,
+            })
+            .lat
+        };
+        // Longitudes are linear in x, which keeps the edges exact
+        let longitude = |x: u32| x as f64 / (1u64 << self.z) as f64 * 360. - 180.;
+        BoundingBox {
+            south: latitude(self.y + 1),
+            west: longitude(self.x),
+            north: latitude(self.y),
+            east: longitude(self.x + 1),
+        }
+    }
+
+    /// Center of the bounding box of the tile
+    pub fn center(&self) -> Coordinate {
+        self.bounding_box().center()
+    }
+
+    /// Precision of the center, which is half the size of the tile
+    pub fn precision(&self) -> Precision {
+        self.bounding_box().precision()
+    }
+
+    /// The tile one zoom level up containing this one
+    pub fn parent(&self) -> Option<Tile> {
+        let z = self.z.checked_sub(1)?;
+        Some(Tile {
+            z,
+            x: self.x / 2,
+            y: self.y / 2,
+        })
+    }
+
+    /// The four tiles one zoom level down (north-west, north-east, south-west, south-east)
+    pub fn children(&self) -> Option<[Tile; 4]> {
+        if self.z >= MAX_ZOOM {
+            return None;
+        }
+        let (z, x, y) = (self.z + 1, self.x * 2, self.y * 2);
+        Some([
+            Tile { z, x, y },
+            Tile { z, x: x + 1, y },
+            Tile { z, x, y: y + 1 },
+            Tile {
+                z,
+                x: x + 1,
+                y: y + 1,
+            },
+        ])
+    }
+
+    /// The adjacent tile in a direction
+    ///
+    /// Neighbours wrap around the antimeridian, but there are none beyond the
+    /// northern and southern edge of the map.
+    ///
+    /// ```
+    /// # use recoord::formats::{tile::Tile, Direction};
+    /// let tile = Tile::new(2, 0, 1).unwrap();
+    /// assert_eq!(tile.neighbor(Direction::SouthWest), Some(Tile { z: 2, x: 3, y: 2 }));
+    /// assert_eq!(Tile::new(2, 0, 0).unwrap().neighbor(Direction::North), None);
+    /// ```
+    pub fn neighbor(&self, direction: Direction) -> Option<Tile> {
+        let (d_lat, d_lng) = direction.offset();
+        let size = 1i64 << self.z;
+        // Rows count from north to south
+        let y = self.y as i64 - d_lat as i64;
+        if !(0..size).contains(&y) {
+            return None;
+        }
+        Some(Tile {
+            z: self.z,
+            x: (self.x as i64 + d_lng as i64).rem_euclid(size) as u32,
+            y: y as u32,
+        })
+    }
+
+    /// All eight neighbours in the order of [`Direction::ALL`]
+    pub fn neighbors(&self) -> [Option<Tile>; 8] {
+        Direction::ALL.map(|direction| self.neighbor(direction))
+    }
+
+    /// All tiles at a zoom level intersecting a bounding box
+    ///
+    /// Latitudes beyond ±[`MAX_LATITUDE`] are clamped. Boxes crossing the
+    /// antimeridian are covered on both sides. Tiles are ordered row by row
+    /// from north to south and west to east within a row.
+    ///
+    /// Tiles are produced lazily, since large boxes at high zoom levels
+    /// contain more tiles than fit into memory.
+    ///
+    /// ```
+    /// # use recoord::BoundingBox;
+    /// # use recoord::formats::tile::Tile;
+    /// let pacific = BoundingBox::new(-10., 170., 10., -170.).unwrap();
+    /// let tiles = Tile::covering(&pacific, 2).unwrap();
+    /// let columns: Vec<u32> = tiles.map(|tile| tile.x).collect();
+    /// assert_eq!(columns, [3, 0, 3, 0]);
+    /// ```
+    pub fn covering(
+        bounds: &BoundingBox,
+        z: u8,
+    ) -> Result<impl Iterator<Item = Tile>, CoordinateError> {
+        let north_west = Pixel::clamped(&Coordinate::new(bounds.north, bounds.west), z)?;
+        let south_east = Pixel::clamped(&Coordinate::new(bounds.south, bounds.east), z)?;
+        let max = (1u32 << z) - 1;
+        let first = |value: f64| ((value / TILE_SIZE).floor().max(0.) as u32).min(max);
+        // Tiles only touched at their edge are left out
+        let last = |value: f64| {
+            ((value / TILE_SIZE).ceil() as u32)
+                .saturating_sub(1)
+                .min(max)
+        };
+
+        let (x_first, x_last) = (first(north_west.x), last(south_east.x));
+        let columns = if bounds.crosses_antimeridian() {
+            u64::from(max - x_first) + u64::from(x_last) + 2
+        } else {
+            u64::from(x_last.max(x_first) - x_first) + 1
+        };
+        let size = u64::from(max) + 1;
+        let (y_first, y_last) = (first(north_west.y), last(south_east.y));
+        Ok((y_first..=y_last.max(y_first)).flat_map(move |y| {
+            (0..columns).map(move |column| Tile {
+                z,
+                x: ((u64::from(x_first) + column) % size) as u32,
+                y,
+            })
+        }))
+    }
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.z, self.x, self.y)
+    }
+}
+
+impl FromStr for Tile {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut parts = str.trim().split('/');
+        let mut next = || -> Result<u32, CoordinateError> {
+            let part = parts.next().ok_or(CoordinateError::Malformed)?;
+            if part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(CoordinateError::Malformed);
+            }
+            part.parse().map_err(|_| CoordinateError::InvalidValue)
+        };
+        let (z, x, y) = (next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return Err(CoordinateError::Malformed);
+        }
+        let z = u8::try_from(z).map_err(|_| CoordinateError::InvalidValue)?;
+        Tile::new(z, x, y)
+    }
+}
+
+impl From<Tile> for Coordinate {
+    fn from(tile: Tile) -> Self {
+        tile.center()
+    }
+}
+
+impl From<Tile> for BoundingBox {
+    fn from(tile: Tile) -> Self {
+        tile.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_mercator_round_trip() {
+        let coordinate = Coordinate::new(-33.8688, 151.2093);
+        let point = WebMercator::try_from(coordinate.clone()).unwrap();
+        assert!((point.x - 16_832_542.28).abs() < 1.);
+        let back = Coordinate::from(point);
+        assert!(back.distance(&coordinate) < 1e-6);
+
+        let clamped = Coordinate::from(WebMercator::clamped(&Coordinate::new(90., 0.)));
+        assert!((clamped.lat - MAX_LATITUDE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pixel() {
+        let pixel = Pixel::new(&Coordinate::new(0., 0.), 1).unwrap();
+        assert_eq!((pixel.x, pixel.y), (256., 256.));
+        let corner = Pixel::new(&Coordinate::new(MAX_LATITUDE, -180.), 3).unwrap();
+        assert!(corner.x.abs() < 1e-6 && corner.y.abs() < 1e-6);
+        assert!(Pixel::new(&Coordinate::new(-86., 0.), 3).is_err());
+        assert!(Pixel::new(&Coordinate::new(0., 0.), MAX_ZOOM + 1).is_err());
+    }
+
+    #[test]
+    fn test_tile_bounds() {
+        let tile = Tile::new(1, 1, 0).unwrap();
+        let bounds = tile.bounding_box();
+        assert!((bounds.north() - MAX_LATITUDE).abs() < 1e-9);
+        assert_eq!(
+            (bounds.south(), bounds.west(), bounds.east()),
+            (0., 0., 180.)
+        );
+        assert_eq!(
+            Tile::at(&Coordinate::new(0., 180.), 1).unwrap(),
+            Tile::new(1, 1, 1).unwrap()
+        );
+        assert_eq!(tile.parent(), Some(Tile { z: 0, x: 0, y: 0 }));
+        assert_eq!(tile.children().unwrap()[3], Tile { z: 2, x: 3, y: 1 });
+    }
+
+    #[test]
+    fn test_covering() {
+        let tile = Tile::new(10, 532, 342).unwrap();
+        let covering = |bounds: &BoundingBox, z| Tile::covering(bounds, z).unwrap();
+        assert_eq!(
+            covering(&tile.bounding_box(), 10).collect::<Vec<_>>(),
+            [tile]
+        );
+        assert_eq!(covering(&tile.bounding_box(), 11).count(), 4);
+        assert_eq!(covering(&BoundingBox::WORLD, 2).count(), 16);
+        let mut world = covering(&BoundingBox::WORLD, 20);
+        assert_eq!(world.next(), Some(Tile { z: 20, x: 0, y: 0 }));
+        assert_eq!(world.nth((1 << 20) - 1), Some(Tile { z: 20, x: 0, y: 1 }));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("0/0/0".parse::<Tile>().unwrap(), Tile { z: 0, x: 0, y: 0 });
+        assert!(matches!(
+            "2/4/0".parse::<Tile>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!(matches!(
+            "31/0/0".parse::<Tile>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!(matches!(
+            "1/0".parse::<Tile>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            "1/0/0/0".parse::<Tile>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(matches!(
+            "1/-1/0".parse::<Tile>(),
+            Err(CoordinateError::Malformed)
+        ));
+    }
+}
@@ -0,0 +1,497 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use crate::{geometry::Geometry, Altitude, Coordinate, CoordinateError, Position};
+
+/// Flag of the geometry type for a height in extended WKB
+const Z_FLAG: u32 = 0x8000_0000;
+
+/// Flag of the geometry type for a measure in extended WKB
+const M_FLAG: u32 = 0x4000_0000;
+
+/// Flag of the geometry type for an SRID in extended WKB
+const SRID_FLAG: u32 = 0x2000_0000;
+
+/// A geometry in (extended) Well-Known Binary (e.g. "0101000020E6100000...")
+///
+/// Both byte orders, ISO WKB and PostGIS' extended WKB with SRID are read.
+/// Heights are read as altitudes above the ellipsoid, and measures are
+/// dropped. Like for [`wkt`](super::wkt::Wkt), coordinates have to be
+/// longitudes and latitudes in degrees.
+///
+/// Geometries are written in little endian, like PostGIS' `ST_AsBinary`
+/// (`ST_AsEWKB` if there is an SRID). Errors of binary input report the byte
+/// offset with [`CoordinateError::MalformedAt`].
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::wkb::Wkb;
+/// let wkb: Wkb = "0101000020E61000001F85EB51B85E30407B14AE47E11A4840".parse().unwrap();
+/// assert_eq!(wkb.srid, Some(4326));
+/// assert_eq!(Coordinate::try_from(wkb).unwrap(), Coordinate::new(48.21, 16.37));
+///
+/// let wkb = Wkb::from(Coordinate::new(2., 1.));
+/// assert_eq!(wkb.to_string(), "0101000000000000000000F03F0000000000000040");
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wkb {
+    /// The geometry
+    pub geometry: Geometry,
+    /// Spatial reference identifier, usually an EPSG code like 4326
+    pub srid: Option<u32>,
+}
+
+impl Wkb {
+    /// A geometry without SRID
+    pub fn new(geometry: Geometry) -> Self {
+        Self {
+            geometry,
+            srid: None,
+        }
+    }
+
+    /// Set the spatial reference identifier
+    pub fn with_srid(mut self, srid: u32) -> Self {
+        self.srid = Some(srid);
+        self
+    }
+
+    /// Read a geometry from its binary representation
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CoordinateError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let (geometry, srid) = reader.geometry()?;
+        if reader.position < bytes.len() {
+            return Err(CoordinateError::MalformedAt(reader.position));
+        }
+        Ok(Wkb { geometry, srid })
+    }
+
+    /// Binary representation in little endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_geometry(&self.geometry, self.srid, self.geometry.is_3d(), &mut bytes);
+        bytes
+    }
+}
+
+/// Cursor over binary input
+struct Reader<'a> {
+    /// Input
+    bytes: &'a [u8],
+    /// Offset of the next byte to read
+    position: usize,
+}
+
+impl Reader<'_> {
+    /// Read the next `N` bytes
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], CoordinateError> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + N)
+            .ok_or(CoordinateError::MalformedAt(self.bytes.len()))?;
+        self.position += N;
+        Ok(bytes.try_into().expect("slice has N bytes"))
+    }
+
+    /// Read an unsigned integer
+    fn u32(&mut self, little_endian: bool) -> Result<u32, CoordinateError> {
+        let bytes = self.take()?;
+        Ok(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    /// Read a float
+    fn f64(&mut self, little_endian: bool) -> Result<f64, CoordinateError> {
+        let bytes = self.take()?;
+        Ok(if little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    /// Read a position with an optional height and measure
+    fn position(
+        &mut self,
+        little_endian: bool,
+        (z, m): (bool, bool),
+    ) -> Result<Position, CoordinateError> {
+        let start = self.position;
+        let (lng, lat) = (self.f64(little_endian)?, self.f64(little_endian)?);
+        let altitude = if z {
+            Some(Altitude::ellipsoidal(self.f64(little_endian)?))
+        } else {
+            None
+        };
+        if m {
+            self.f64(little_endian)?;
+        }
+        // PostGIS writes empty points as NaN
+        if lng.is_nan() && lat.is_nan() {
+            return Err(CoordinateError::Unsupported);
+        }
+        Ok(Position {
+            coordinate: Coordinate::try_new(lat, lng)
+                .map_err(|_| CoordinateError::MalformedAt(start))?,
+            altitude,
+        })
+    }
+
+    /// Read a number of items
+    fn list<T>(
+        &mut self,
+        little_endian: bool,
+        mut read: impl FnMut(&mut Self) -> Result<T, CoordinateError>,
+    ) -> Result<Vec<T>, CoordinateError> {
+        let count = self.u32(little_endian)?;
+        (0..count).map(|_| read(self)).collect()
+    }
+
+    /// Read a geometry and its SRID
+    fn geometry(&mut self) -> Result<(Geometry, Option<u32>), CoordinateError> {
+        let (little_endian, code, dimensions, srid) = self.header()?;
+        Ok((self.body(little_endian, code, dimensions)?, srid))
+    }
+
+    /// Read the byte order, type code, dimensions and SRID of a geometry
+    #[allow(clippy::type_complexity)]
+    fn header(&mut self) -> Result<(bool, u32, (bool, bool), Option<u32>), CoordinateError> {
+        let start = self.position;
+        let little_endian = match self.take()? {
+            [0] => false,
+            [1] => true,
+            _ => return Err(CoordinateError::MalformedAt(start)),
+        };
+        let code = self.u32(little_endian)?;
+        let srid = if code & SRID_FLAG != 0 {
+            Some(self.u32(little_endian)?)
+        } else {
+            None
+        };
+        let mut dimensions = (code & Z_FLAG != 0, code & M_FLAG != 0);
+        // ISO WKB adds 1000 for a height, 2000 for a measure and 3000 for both
+        let code = code & !(Z_FLAG | M_FLAG | SRID_FLAG);
+        match code / 1000 {
+            0 => {}
+            1 => dimensions.0 = true,
+            2 => dimensions.1 = true,
+            3 => dimensions = (true, true),
+            _ => return Err(CoordinateError::Unsupported),
+        }
+        Ok((little_endian, code % 1000, dimensions, srid))
+    }
+
+    /// Read the geometry following a header
+    fn body(
+        &mut self,
+        little_endian: bool,
+        code: u32,
+        dimensions: (bool, bool),
+    ) -> Result<Geometry, CoordinateError> {
+        let positions = |reader: &mut Self| {
+            reader.list(little_endian, |reader| {
+                reader.position(little_endian, dimensions)
+            })
+        };
+        let lines = |reader: &mut Self| reader.list(little_endian, positions);
+        // Parts of multi geometries are geometries of their own. Their type is
+        // checked before reading them so multi geometries can't nest.
+        let part = |reader: &mut Self| {
+            let start = reader.position;
+            let (little_endian, code, dimensions, _) = reader.header()?;
+            if code > 3 {
+                return Err(CoordinateError::MalformedAt(start));
+            }
+            Ok((start, reader.body(little_endian, code, dimensions)?))
+        };
+        Ok(match code {
+            1 => Geometry::Point(self.position(little_endian, dimensions)?),
+            2 => Geometry::LineString(positions(self)?),
+            3 => Geometry::Polygon(lines(self)?),
+            4 => Geometry::MultiPoint(self.list(little_endian, |reader| match part(reader)? {
+                (_, Geometry::Point(position)) => Ok(position),
+                (start, _) => Err(CoordinateError::MalformedAt(start)),
+            })?),
+            5 => Geometry::MultiLineString(self.list(little_endian, |reader| {
+                match part(reader)? {
+                    (_, Geometry::LineString(line)) => Ok(line),
+                    (start, _) => Err(CoordinateError::MalformedAt(start)),
+                }
+            })?),
+            6 => {
+                Geometry::MultiPolygon(self.list(little_endian, |reader| match part(reader)? {
+                    (_, Geometry::Polygon(rings)) => Ok(rings),
+                    (start, _) => Err(CoordinateError::MalformedAt(start)),
+                })?)
+            }
+            _ => return Err(CoordinateError::Unsupported),
+        })
+    }
+}
+
+/// Append a geometry in little endian
+///
+/// Extended WKB flags are used if there is an SRID, ISO type codes otherwise.
+fn write_geometry(geometry: &Geometry, srid: Option<u32>, z: bool, bytes: &mut Vec<u8>) {
+    let extended = srid.is_some();
+    let header = |code: u32, srid: Option<u32>, bytes: &mut Vec<u8>| {
+        bytes.push(1);
+        if extended {
+            let flags = if z { Z_FLAG } else { 0 } | if srid.is_some() { SRID_FLAG } else { 0 };
+            bytes.extend((code | flags).to_le_bytes());
+            if let Some(srid) = srid {
+                bytes.extend(srid.to_le_bytes());
+            }
+        } else {
+            bytes.extend((code + if z { 1000 } else { 0 }).to_le_bytes());
+        }
+    };
+    let count = |count: usize, bytes: &mut Vec<u8>| bytes.extend((count as u32).to_le_bytes());
+    let position = |position: &Position, bytes: &mut Vec<u8>| {
+        bytes.extend(position.coordinate.lng.to_le_bytes());
+        bytes.extend(position.coordinate.lat.to_le_bytes());
+        if z {
+            let meters = position.altitude.map_or(0., |altitude| altitude.meters);
+            bytes.extend(meters.to_le_bytes());
+        }
+    };
+    let positions = |positions: &[Position], bytes: &mut Vec<u8>| {
+        count(positions.len(), bytes);
+        positions.iter().for_each(|item| position(item, bytes));
+    };
+    let lines = |lines: &[Vec<Position>], bytes: &mut Vec<u8>| {
+        count(lines.len(), bytes);
+        lines.iter().for_each(|line| positions(line, bytes));
+    };
+    // Parts of multi geometries don't repeat the SRID
+    match geometry {
+        Geometry::Point(point) => {
+            header(1, srid, bytes);
+            position(point, bytes);
+        }
+        Geometry::LineString(line) => {
+            header(2, srid, bytes);
+            positions(line, bytes);
+        }
+        Geometry::Polygon(rings) => {
+            header(3, srid, bytes);
+            lines(rings, bytes);
+        }
+        Geometry::MultiPoint(points) => {
+            header(4, srid, bytes);
+            count(points.len(), bytes);
+            for point in points {
+                header(1, None, bytes);
+                position(point, bytes);
+            }
+        }
+        Geometry::MultiLineString(parts) => {
+            header(5, srid, bytes);
+            count(parts.len(), bytes);
+            for line in parts {
+                header(2, None, bytes);
+                positions(line, bytes);
+            }
+        }
+        Geometry::MultiPolygon(polygons) => {
+            header(6, srid, bytes);
+            count(polygons.len(), bytes);
+            for rings in polygons {
+                header(3, None, bytes);
+                lines(rings, bytes);
+            }
+        }
+    }
+}
+
+impl FromStr for Wkb {
+    type Err = CoordinateError;
+
+    /// Read hexadecimal (E)WKB as written by PostGIS
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let str = str.trim();
+        if !str.len().is_multiple_of(2) || !str.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(CoordinateError::Malformed);
+        }
+        let bytes = (0..str.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&str[index..index + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| CoordinateError::Malformed)?;
+        Wkb::from_bytes(&bytes)
+    }
+}
+
+impl Display for Wkb {
+    /// Upper case hexadecimal like PostGIS
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_bytes()
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02X}"))
+    }
+}
+
+impl From<Geometry> for Wkb {
+    fn from(geometry: Geometry) -> Self {
+        Self::new(geometry)
+    }
+}
+
+impl From<Coordinate> for Wkb {
+    fn from(coordinate: Coordinate) -> Self {
+        Self::new(coordinate.into())
+    }
+}
+
+impl From<Position> for Wkb {
+    fn from(position: Position) -> Self {
+        Self::new(position.into())
+    }
+}
+
+#[cfg(feature = "format_wkt")]
+impl From<super::wkt::Wkt> for Wkb {
+    fn from(wkt: super::wkt::Wkt) -> Self {
+        Wkb {
+            geometry: wkt.geometry,
+            srid: wkt.srid,
+        }
+    }
+}
+
+#[cfg(feature = "format_wkt")]
+impl From<Wkb> for super::wkt::Wkt {
+    fn from(wkb: Wkb) -> Self {
+        let mut wkt = super::wkt::Wkt::new(wkb.geometry);
+        wkt.srid = wkb.srid;
+        wkt
+    }
+}
+
+impl TryFrom<Wkb> for Coordinate {
+    type Error = CoordinateError;
+
+    /// Only points can be converted
+    fn try_from(wkb: Wkb) -> Result<Self, Self::Error> {
+        wkb.geometry.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output of `ST_AsBinary` and `ST_AsEWKB` in PostGIS for the WKT
+    const POSTGIS: [(&str, &str); 6] = [
+        (
+            "SRID=4326;POINT(16.37 48.21)",
+            "0101000020E61000001F85EB51B85E30407B14AE47E11A4840",
+        ),
+        (
+            "POINT Z (16.37 48.21 171)",
+            "01E90300001F85EB51B85E30407B14AE47E11A48400000000000606540",
+        ),
+        (
+            "SRID=4326;POINT(16.37 48.21 171)",
+            "01010000A0E61000001F85EB51B85E30407B14AE47E11A48400000000000606540",
+        ),
+        (
+            "LINESTRING(16.37 48.21,13.4 52.52)",
+            "0102000000020000001F85EB51B85E30407B14AE47E11A4840CDCCCCCCCCCC2A40C3F5285C8F424A40",
+        ),
+        (
+            "POLYGON((0 0,1 0,1 1,0 0))",
+            "0103000000010000000400000000000000000000000000000000000000000000000000F0\
+             3F0000000000000000000000000000F03F000000000000F03F0000000000000000000000\
+             0000000000",
+        ),
+        (
+            "SRID=4326;MULTIPOINT(1 2,3 4)",
+            "0104000020E6100000020000000101000000000000000000F03F00000000000000400101\
+             00000000000000000008400000000000001040",
+        ),
+    ];
+
+    #[test]
+    fn test_postgis() {
+        for (wkt, hex) in POSTGIS {
+            let wkb: Wkb = hex.parse().unwrap();
+            assert_eq!(wkb.srid.is_some(), wkt.starts_with("SRID=4326"));
+            assert_eq!(wkb.to_string(), hex, "{wkt}");
+        }
+        let line: Wkb = POSTGIS[3].1.parse().unwrap();
+        assert_eq!(
+            line.geometry.coordinates(),
+            [Coordinate::new(48.21, 16.37), Coordinate::new(52.52, 13.4)]
+        );
+    }
+
+    #[test]
+    fn test_big_endian() {
+        let wkb: Wkb = "00000000013FF00000000000004000000000000000"
+            .parse()
+            .unwrap();
+        assert_eq!(wkb.geometry, Geometry::from(Coordinate::new(2., 1.)));
+        assert_eq!(
+            wkb.to_string(),
+            "0101000000000000000000F03F0000000000000040"
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let (_, hex) = POSTGIS[3];
+        // Missing last byte
+        assert!(matches!(
+            hex[..hex.len() - 2].parse::<Wkb>(),
+            Err(CoordinateError::MalformedAt(40))
+        ));
+        // Trailing byte
+        assert!(matches!(
+            format!("{hex}00").parse::<Wkb>(),
+            Err(CoordinateError::MalformedAt(41))
+        ));
+        assert!(matches!(
+            "0101".parse::<Wkb>(),
+            Err(CoordinateError::MalformedAt(2))
+        ));
+        assert!(matches!(
+            "02".parse::<Wkb>(),
+            Err(CoordinateError::MalformedAt(0))
+        ));
+        assert!(matches!(
+            "010".parse::<Wkb>(),
+            Err(CoordinateError::Malformed)
+        ));
+        // Geometry collection
+        assert!(matches!(
+            "010700000000000000".parse::<Wkb>(),
+            Err(CoordinateError::Unsupported)
+        ));
+        // Empty point
+        assert!(matches!(
+            "0101000000000000000000F87F000000000000F87F".parse::<Wkb>(),
+            Err(CoordinateError::Unsupported)
+        ));
+        // Multi points nested in multi points
+        assert!(matches!(
+            "010400000001000000".repeat(100_000).parse::<Wkb>(),
+            Err(CoordinateError::MalformedAt(9))
+        ));
+    }
+
+    #[cfg(feature = "format_wkt")]
+    #[test]
+    fn test_wkt() {
+        use crate::formats::wkt::Wkt;
+        for (wkt, hex) in POSTGIS {
+            let parsed: Wkt = wkt.parse().unwrap();
+            assert_eq!(Wkb::from(parsed).to_string(), hex);
+            let wkb: Wkb = hex.parse().unwrap();
+            assert_eq!(Wkt::from(wkb).to_string(), wkt);
+        }
+    }
+}
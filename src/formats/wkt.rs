@@ -0,0 +1,475 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+use crate::{
+    formats::last_digit_precision, geometry::Geometry, Altitude, BoundingBox, Coordinate,
+    CoordinateError, Position, Precision,
+};
+
+/// A geometry in Well-Known Text, optionally with an SRID (e.g. "SRID=4326;POINT(16.37 48.21)")
+///
+/// Positions are written as `longitude latitude`, followed by the height
+/// above the ellipsoid for 3D geometries. The "SRID=" prefix of PostGIS'
+/// extended WKT is kept, but coordinates have to be longitudes and latitudes
+/// in degrees, so projected reference systems can't be read. Measures ("M")
+/// are accepted and dropped.
+///
+/// Geometries are written like PostGIS' `ST_AsText` (`ST_AsEWKT` if there is
+/// an SRID).
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::formats::wkt::Wkt;
+/// let wkt: Wkt = "SRID=4326;POINT(16.37 48.21)".parse().unwrap();
+/// assert_eq!(wkt.srid, Some(4326));
+/// assert_eq!(Coordinate::try_from(wkt).unwrap(), Coordinate::new(48.21, 16.37));
+///
+/// let line: Wkt = "linestring (16.37 48.21, 13.4 52.52)".parse().unwrap();
+/// assert_eq!(line.to_string(), "LINESTRING(16.37 48.21,13.4 52.52)");
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Wkt {
+    /// The geometry
+    pub geometry: Geometry,
+    /// Spatial reference identifier, usually an EPSG code like 4326
+    pub srid: Option<u32>,
+    /// Precision given by the written decimals of a parsed point
+    #[cfg_attr(feature = "serde", serde(skip))]
+    precision: Option<Precision>,
+}
+
+impl PartialEq for Wkt {
+    /// Only the geometry and SRID are compared, not how they were written
+    fn eq(&self, other: &Self) -> bool {
+        self.geometry == other.geometry && self.srid == other.srid
+    }
+}
+
+impl Wkt {
+    /// A geometry without SRID
+    pub fn new(geometry: Geometry) -> Self {
+        Self {
+            geometry,
+            srid: None,
+            precision: None,
+        }
+    }
+
+    /// Set the spatial reference identifier
+    pub fn with_srid(mut self, srid: u32) -> Self {
+        self.srid = Some(srid);
+        self
+    }
+
+    /// Precision given by the number of decimals of a point, or half the size of other geometries
+    pub fn precision(&self) -> Precision {
+        match &self.geometry {
+            Geometry::Point(position) => self.precision.unwrap_or_else(|| Precision {
+                lat: last_digit_precision(&position.coordinate.lat.to_string()),
+                lng: last_digit_precision(&position.coordinate.lng.to_string()),
+            }),
+            geometry => geometry
+                .bounding_box()
+                .map_or(Precision { lat: 0., lng: 0. }, |bbox| bbox.precision()),
+        }
+    }
+}
+
+/// Nested lists following the geometry type
+#[derive(Debug)]
+enum Node {
+    /// Numbers of a single position, and the precision given by the decimals
+    /// of its longitude and latitude
+    Numbers(Vec<f64>, Precision),
+    /// Parenthesized list
+    List(Vec<Node>),
+}
+
+impl Node {
+    /// Deepest nesting of lists, as in a MULTIPOLYGON
+    const MAX_DEPTH: usize = 3;
+
+    /// Read a list or the numbers of a position from the start of `rest`
+    ///
+    /// At most `depth` further lists may be nested.
+    fn read(rest: &mut &str, depth: usize) -> Result<Node, CoordinateError> {
+        *rest = rest.trim_start();
+        if let Some(inner) = rest.strip_prefix('(') {
+            let depth = depth.checked_sub(1).ok_or(CoordinateError::Malformed)?;
+            *rest = inner;
+            let mut nodes = vec![Node::read(rest, depth)?];
+            loop {
+                *rest = rest.trim_start();
+                if let Some(next) = rest.strip_prefix(',') {
+                    *rest = next;
+                    nodes.push(Node::read(rest, depth)?);
+                } else if let Some(next) = rest.strip_prefix(')') {
+                    *rest = next;
+                    return Ok(Node::List(nodes));
+                } else {
+                    return Err(CoordinateError::Malformed);
+                }
+            }
+        }
+        let end = rest.find([',', '(', ')']).unwrap_or(rest.len());
+        let words: Vec<&str> = rest[..end].split_whitespace().collect();
+        let numbers = words
+            .iter()
+            .map(|number| number.parse().map_err(|_| CoordinateError::Malformed))
+            .collect::<Result<Vec<f64>, _>>()?;
+        *rest = &rest[end..];
+        if numbers.is_empty() {
+            return Err(CoordinateError::Malformed);
+        }
+        let precision = Precision {
+            lat: words.get(1).map_or(0., |lat| last_digit_precision(lat)),
+            lng: last_digit_precision(words[0]),
+        };
+        Ok(Node::Numbers(numbers, precision))
+    }
+
+    /// Nodes of a list
+    fn list(&self) -> Result<&[Node], CoordinateError> {
+        match self {
+            Node::List(nodes) => Ok(nodes),
+            Node::Numbers(..) => Err(CoordinateError::Malformed),
+        }
+    }
+
+    /// Numbers of the first position
+    fn first(&self) -> Option<&[f64]> {
+        match self {
+            Node::Numbers(numbers, _) => Some(numbers),
+            Node::List(nodes) => nodes.first().and_then(Node::first),
+        }
+    }
+
+    /// Position of a node with numbers
+    fn position(&self, dimensions: Dimensions) -> Result<Position, CoordinateError> {
+        match self {
+            Node::Numbers(numbers, _) if numbers.len() == dimensions.count() => Ok(Position {
+                coordinate: Coordinate::try_new(numbers[1], numbers[0])?,
+                altitude: dimensions.z.then(|| Altitude::ellipsoidal(numbers[2])),
+            }),
+            _ => Err(CoordinateError::Malformed),
+        }
+    }
+
+    /// Positions of a list
+    fn positions(&self, dimensions: Dimensions) -> Result<Vec<Position>, CoordinateError> {
+        self.list()?
+            .iter()
+            .map(|node| node.position(dimensions))
+            .collect()
+    }
+
+    /// Lines or rings of a list
+    fn lines(&self, dimensions: Dimensions) -> Result<Vec<Vec<Position>>, CoordinateError> {
+        self.list()?
+            .iter()
+            .map(|node| node.positions(dimensions))
+            .collect()
+    }
+}
+
+/// Dimensions of the positions besides longitude and latitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Dimensions {
+    /// Whether there's a height
+    z: bool,
+    /// Whether there's a measure
+    m: bool,
+}
+
+impl Dimensions {
+    /// Longitude and latitude only
+    const XY: Dimensions = Dimensions { z: false, m: false };
+    /// With measure
+    const XYM: Dimensions = Dimensions { z: false, m: true };
+    /// With height
+    const XYZ: Dimensions = Dimensions { z: true, m: false };
+    /// With height and measure
+    const XYZM: Dimensions = Dimensions { z: true, m: true };
+
+    /// Number of values of each position
+    fn count(self) -> usize {
+        2 + usize::from(self.z) + usize::from(self.m)
+    }
+}
+
+impl FromStr for Wkt {
+    type Err = CoordinateError;
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut str = str.trim();
+        let mut srid = None;
+        if str
+            .get(..5)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("SRID="))
+        {
+            let (value, rest) = str[5..].split_once(';').ok_or(CoordinateError::Malformed)?;
+            srid = Some(
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| CoordinateError::Malformed)?,
+            );
+            str = rest.trim_start();
+        }
+        let (head, mut body) = str.split_at(str.find('(').unwrap_or(str.len()));
+        let mut words = head.split_whitespace();
+        let mut kind = words
+            .next()
+            .ok_or(CoordinateError::Malformed)?
+            .to_ascii_uppercase();
+        let mut dimensions = None;
+        let mut empty = false;
+        // Extended WKT appends an "M" to the type for measures
+        if kind.len() > 1 && kind.ends_with('M') {
+            kind.pop();
+            dimensions = Some(Dimensions::XYM);
+        }
+        for word in words {
+            match (word.to_ascii_uppercase().as_str(), dimensions, empty) {
+                ("Z", None, false) => dimensions = Some(Dimensions::XYZ),
+                ("M", None, false) => dimensions = Some(Dimensions::XYM),
+                ("ZM", None, false) => dimensions = Some(Dimensions::XYZM),
+                ("EMPTY", _, false) => empty = true,
+                _ => return Err(CoordinateError::Malformed),
+            }
+        }
+        if empty != body.is_empty() {
+            return Err(CoordinateError::Malformed);
+        }
+        let node = if empty {
+            Node::List(vec![])
+        } else {
+            let node = Node::read(&mut body, Node::MAX_DEPTH)?;
+            if !body.trim().is_empty() {
+                return Err(CoordinateError::Malformed);
+            }
+            node
+        };
+        // Extended WKT doesn't name the dimensions
+        let dimensions = match (dimensions, node.first().map(<[f64]>::len)) {
+            (Some(dimensions), _) => dimensions,
+            (None, None | Some(2)) => Dimensions::XY,
+            (None, Some(3)) => Dimensions::XYZ,
+            (None, Some(4)) => Dimensions::XYZM,
+            (None, Some(_)) => return Err(CoordinateError::Malformed),
+        };
+        let mut precision = None;
+        let geometry = match kind.as_str() {
+            "POINT" => match node.list()? {
+                [position] => {
+                    if let Node::Numbers(_, decimals) = position {
+                        precision = Some(*decimals);
+                    }
+                    Geometry::Point(position.position(dimensions)?)
+                }
+                [] => return Err(CoordinateError::Unsupported),
+                _ => return Err(CoordinateError::Malformed),
+            },
+            // Points may or may not be parenthesized
+            "MULTIPOINT" => Geometry::MultiPoint(
+                node.list()?
+                    .iter()
+                    .map(|node| match node {
+                        Node::List(nodes) if nodes.len() == 1 => nodes[0].position(dimensions),
+                        node => node.position(dimensions),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            "LINESTRING" => Geometry::LineString(node.positions(dimensions)?),
+            "MULTILINESTRING" => Geometry::MultiLineString(node.lines(dimensions)?),
+            "POLYGON" => Geometry::Polygon(node.lines(dimensions)?),
+            "MULTIPOLYGON" => Geometry::MultiPolygon(
+                node.list()?
+                    .iter()
+                    .map(|node| node.lines(dimensions))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "GEOMETRYCOLLECTION" | "CIRCULARSTRING" | "COMPOUNDCURVE" | "CURVEPOLYGON"
+            | "MULTICURVE" | "MULTISURFACE" | "POLYHEDRALSURFACE" | "TIN" | "TRIANGLE" => {
+                return Err(CoordinateError::Unsupported)
+            }
+            _ => return Err(CoordinateError::Malformed),
+        };
+        Ok(Wkt {
+            geometry,
+            srid,
+            precision,
+        })
+    }
+}
+
+/// Write positions separated by commas in parentheses
+fn write_list<T>(
+    f: &mut fmt::Formatter,
+    items: &[T],
+    write: impl Fn(&mut fmt::Formatter, &T) -> fmt::Result,
+) -> fmt::Result {
+    write!(f, "(")?;
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            write!(f, ",")?;
+        }
+        write(f, item)?;
+    }
+    write!(f, ")")
+}
+
+impl Display for Wkt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(srid) = self.srid {
+            write!(f, "SRID={srid};")?;
+        }
+        let z = self.geometry.is_3d();
+        write!(f, "{}", self.geometry.kind().to_ascii_uppercase())?;
+        // Extended WKT leaves out the dimensions
+        if z && self.srid.is_none() {
+            write!(f, " Z ")?;
+        }
+        let position = |f: &mut fmt::Formatter, position: &Position| {
+            write!(f, "{} {}", position.coordinate.lng, position.coordinate.lat)?;
+            match position.altitude {
+                Some(altitude) if z => write!(f, " {}", altitude.meters),
+                _ => Ok(()),
+            }
+        };
+        let positions =
+            |f: &mut fmt::Formatter, positions: &Vec<Position>| write_list(f, positions, position);
+        let lines =
+            |f: &mut fmt::Formatter, lines: &Vec<Vec<Position>>| write_list(f, lines, positions);
+        match &self.geometry {
+            Geometry::Point(point) => write_list(f, std::slice::from_ref(point), position),
+            Geometry::MultiPoint(points) | Geometry::LineString(points) if points.is_empty() => {
+                write!(f, " EMPTY")
+            }
+            Geometry::MultiPoint(points) | Geometry::LineString(points) => positions(f, points),
+            Geometry::MultiLineString(rings) | Geometry::Polygon(rings) if rings.is_empty() => {
+                write!(f, " EMPTY")
+            }
+            Geometry::MultiLineString(rings) | Geometry::Polygon(rings) => lines(f, rings),
+            Geometry::MultiPolygon(polygons) if polygons.is_empty() => write!(f, " EMPTY"),
+            Geometry::MultiPolygon(polygons) => write_list(f, polygons, lines),
+        }
+    }
+}
+
+impl From<Geometry> for Wkt {
+    fn from(geometry: Geometry) -> Self {
+        Self::new(geometry)
+    }
+}
+
+impl From<Coordinate> for Wkt {
+    fn from(coordinate: Coordinate) -> Self {
+        Self::new(coordinate.into())
+    }
+}
+
+impl From<Position> for Wkt {
+    fn from(position: Position) -> Self {
+        Self::new(position.into())
+    }
+}
+
+impl From<BoundingBox> for Wkt {
+    fn from(bounds: BoundingBox) -> Self {
+        Self::new(bounds.into())
+    }
+}
+
+impl TryFrom<Wkt> for Coordinate {
+    type Error = CoordinateError;
+
+    /// Only points can be converted
+    fn try_from(wkt: Wkt) -> Result<Self, Self::Error> {
+        wkt.geometry.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output of `ST_AsText` and `ST_AsEWKT` in PostGIS
+    const POSTGIS: [&str; 8] = [
+        "POINT(16.37 48.21)",
+        "SRID=4326;POINT(16.37 48.21)",
+        "POINT Z (16.37 48.21 171)",
+        "SRID=4326;POINT(16.37 48.21 171)",
+        "MULTIPOINT(1 2,3 4)",
+        "LINESTRING(16.37 48.21,13.4 52.52)",
+        "POLYGON((0 0,4 0,4 4,0 0),(1 1,2 1,2 2,1 1))",
+        "MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((5 5,6 5,6 6,5 5)))",
+    ];
+
+    #[test]
+    fn test_round_trip() {
+        for input in POSTGIS {
+            let wkt: Wkt = input.parse().unwrap();
+            assert_eq!(wkt.to_string(), input);
+        }
+        let empty: Wkt = "MULTIPOINT EMPTY".parse().unwrap();
+        assert_eq!(empty.geometry, Geometry::MultiPoint(vec![]));
+        assert_eq!(empty.to_string(), "MULTIPOINT EMPTY");
+    }
+
+    #[test]
+    fn test_variants() {
+        let multipoint: Wkt = "MultiPoint ((1 2), (3 4))".parse().unwrap();
+        assert_eq!(multipoint.to_string(), "MULTIPOINT(1 2,3 4)");
+        let measured: Wkt = "POINTM(1 2 3)".parse().unwrap();
+        assert_eq!(measured.to_string(), "POINT(1 2)");
+        let zm: Wkt = "srid=4258; point zm (1 2 3 4)".parse().unwrap();
+        assert_eq!(zm.srid, Some(4258));
+        assert_eq!(zm.to_string(), "SRID=4258;POINT(1 2 3)");
+    }
+
+    #[test]
+    fn test_invalid() {
+        for input in [
+            "POINT",
+            "POINT(1)",
+            "POINT Z (1 2)",
+            "POINT(1 2, 3 4)",
+            "POINT(1 2",
+            "POINT(1 2))",
+            "POINT(a b)",
+            "SPHERE(1 2)",
+            "SRID=x;POINT(1 2)",
+            "LINESTRING(1 2 3, 4 5)",
+            "MULTIPOLYGON((((1 2))))",
+        ] {
+            assert!(
+                matches!(input.parse::<Wkt>(), Err(CoordinateError::Malformed)),
+                "{input}"
+            );
+        }
+        assert!(matches!(
+            "POINT EMPTY".parse::<Wkt>(),
+            Err(CoordinateError::Unsupported)
+        ));
+        assert!(matches!(
+            "POINT(1 100)".parse::<Wkt>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        let nested = format!("MULTIPOLYGON{}", "(".repeat(1_000_000));
+        assert!(matches!(
+            nested.parse::<Wkt>(),
+            Err(CoordinateError::Malformed)
+        ));
+        assert!(nested.parse::<Coordinate>().is_err());
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        let resolution = Coordinate::parse_detailed("POINT(16.37 48.21)").unwrap();
+        assert_eq!(
+            resolution.source,
+            crate::ResolutionSource::Parsed(crate::formats::Format::Wkt)
+        );
+        assert_eq!(resolution.coordinate, Coordinate::new(48.21, 16.37));
+    }
+}
@@ -0,0 +1,516 @@
+use crate::{Altitude, AltitudeReference, Coordinate, CoordinateError, Position};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Reference ellipsoid approximating the shape of the earth
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ellipsoid {
+    /// Semi-major axis (equatorial radius) in meters
+    pub a: f64,
+    /// Flattening
+    pub f: f64,
+}
+
+impl Ellipsoid {
+    /// World Geodetic System 1984 (used by GPS)
+    pub const WGS84: Ellipsoid = Ellipsoid {
+        a: 6_378_137.,
+        f: 1. / 298.257_223_563,
+    };
+    /// Geodetic Reference System 1980 (used by ETRS89 and NAD83)
+    pub const GRS80: Ellipsoid = Ellipsoid {
+        a: 6_378_137.,
+        f: 1. / 298.257_222_101,
+    };
+    /// Bessel 1841 (used by DHDN and the Swiss CH1903)
+    pub const BESSEL1841: Ellipsoid = Ellipsoid {
+        a: 6_377_397.155,
+        f: 1. / 299.152_812_8,
+    };
+    /// Airy 1830 (used by the British OSGB36)
+    pub const AIRY1830: Ellipsoid = Ellipsoid {
+        a: 6_377_563.396,
+        f: 1. / 299.324_964_6,
+    };
+    /// International 1924 / Hayford (used by ED50)
+    pub const INTERNATIONAL1924: Ellipsoid = Ellipsoid {
+        a: 6_378_388.,
+        f: 1. / 297.,
+    };
+    /// Clarke 1866 (used by NAD27)
+    pub const CLARKE1866: Ellipsoid = Ellipsoid {
+        a: 6_378_206.4,
+        f: 1. / 294.978_698_2,
+    };
+
+    /// Semi-minor (polar) axis in meters
+    pub fn b(&self) -> f64 {
+        self.a * (1. - self.f)
+    }
+
+    /// Square of the first eccentricity
+    pub fn e2(&self) -> f64 {
+        self.f * (2. - self.f)
+    }
+
+    /// Radius of curvature in the prime vertical at a latitude (in radians)
+    fn prime_vertical_radius(&self, lat: f64) -> f64 {
+        self.a / (1. - self.e2() * lat.sin().powi(2)).sqrt()
+    }
+}
+
+impl Default for Ellipsoid {
+    fn default() -> Self {
+        Ellipsoid::WGS84
+    }
+}
+
+/// Earth-Centered, Earth-Fixed cartesian coordinates in meters
+///
+/// The x axis points to latitude 0 / longitude 0, the y axis to
+/// longitude 90° east and the z axis to the north pole.
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::geodesy::{Ecef, Ellipsoid};
+/// let ecef = Ecef::from_geodetic(&Coordinate::new(0., 90.), 0., &Ellipsoid::WGS84);
+/// assert!(ecef.x.abs() < 1e-9 && (ecef.y - 6_378_137.).abs() < 1e-9);
+/// let (coordinate, height) = ecef.to_geodetic(&Ellipsoid::WGS84);
+/// assert!((coordinate.lng - 90.).abs() < 1e-12 && height.abs() < 1e-6);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ecef {
+    /// Towards latitude 0 / longitude 0
+    pub x: f64,
+    /// Towards latitude 0 / longitude 90° east
+    pub y: f64,
+    /// Towards the north pole
+    pub z: f64,
+}
+
+impl Ecef {
+    /// Convert a coordinate with a height above the ellipsoid into ECEF
+    pub fn from_geodetic(coordinate: &Coordinate, height: f64, ellipsoid: &Ellipsoid) -> Self {
+        let (lat, lng) = (coordinate.lat.to_radians(), coordinate.lng.to_radians());
+        let n = ellipsoid.prime_vertical_radius(lat);
+        Ecef {
+            x: (n + height) * lat.cos() * lng.cos(),
+            y: (n + height) * lat.cos() * lng.sin(),
+            z: (n * (1. - ellipsoid.e2()) + height) * lat.sin(),
+        }
+    }
+
+    /// Convert a position into ECEF
+    ///
+    /// A missing altitude counts as height 0. Heights above mean sea level are
+    /// rejected with [`CoordinateError::Unsupported`], since converting them
+    /// needs a geoid model.
+    pub fn from_position(
+        position: &Position,
+        ellipsoid: &Ellipsoid,
+    ) -> Result<Self, CoordinateError> {
+        let height = match position.altitude {
+            None => 0.,
+            Some(Altitude {
+                meters,
+                reference: AltitudeReference::Ellipsoid,
+            }) => meters,
+            Some(_) => return Err(CoordinateError::Unsupported),
+        };
+        Ok(Self::from_geodetic(&position.coordinate, height, ellipsoid))
+    }
+
+    /// Convert back into a coordinate and the height above the ellipsoid
+    pub fn to_geodetic(&self, ellipsoid: &Ellipsoid) -> (Coordinate, f64) {
+        let e2 = ellipsoid.e2();
+        let p = self.x.hypot(self.y);
+        let lng = self.y.atan2(self.x);
+        let mut lat = self.z.atan2(p * (1. - e2));
+        for _ in 0..10 {
+            let n = ellipsoid.prime_vertical_radius(lat);
+            let next = (self.z + e2 * n * lat.sin()).atan2(p);
+            let converged = (next - lat).abs() < 1e-15;
+            lat = next;
+            if converged {
+                break;
+            }
+        }
+        // This form of the height is stable at the poles, where cos(lat) is 0
+        let height =
+            p * lat.cos() + self.z * lat.sin() - ellipsoid.a * (1. - e2 * lat.sin().powi(2)).sqrt();
+        (
+            Coordinate {
+                lat: lat.to_degrees(),
+                lng: lng.to_degrees(),
+            },
+            height,
+        )
+    }
+
+    /// Convert into a position with an ellipsoidal altitude
+    pub fn to_position(&self, ellipsoid: &Ellipsoid) -> Position {
+        let (coordinate, height) = self.to_geodetic(ellipsoid);
+        Position::new(coordinate).with_altitude(Altitude::ellipsoidal(height))
+    }
+
+    /// Straight line distance in meters
+    pub fn distance(&self, other: &Ecef) -> f64 {
+        (self.x - other.x)
+            .hypot(self.y - other.y)
+            .hypot(self.z - other.z)
+    }
+}
+
+/// Offset in a local East-North-Up frame in meters
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Enu {
+    /// Towards the east
+    pub east: f64,
+    /// Towards the north
+    pub north: f64,
+    /// Away from the ellipsoid
+    pub up: f64,
+}
+
+/// Offset in a local North-East-Down frame in meters (common in aviation)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ned {
+    /// Towards the north
+    pub north: f64,
+    /// Towards the east
+    pub east: f64,
+    /// Towards the ellipsoid
+    pub down: f64,
+}
+
+impl From<Enu> for Ned {
+    fn from(enu: Enu) -> Self {
+        Ned {
+            north: enu.north,
+            east: enu.east,
+            down: -enu.up,
+        }
+    }
+}
+
+impl From<Ned> for Enu {
+    fn from(ned: Ned) -> Self {
+        Enu {
+            east: ned.east,
+            north: ned.north,
+            up: -ned.down,
+        }
+    }
+}
+
+/// A local tangent plane at an origin for ENU and NED offsets
+///
+/// ```
+/// # use recoord::Coordinate;
+/// # use recoord::geodesy::{Ellipsoid, LocalFrame};
+/// let frame = LocalFrame::new(&Coordinate::new(50.94, 6.96), 60., Ellipsoid::WGS84);
+/// let (coordinate, height) = frame.from_enu_geodetic(0., 1_000., 0.);
+/// assert!((coordinate.lat - 50.949).abs() < 0.001);
+/// let enu = frame.enu_of(&coordinate, height);
+/// assert!((enu.north - 1_000.).abs() < 1e-6);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalFrame {
+    /// Origin of the frame
+    origin: Ecef,
+    /// Ellipsoid of the geodetic coordinates
+    ellipsoid: Ellipsoid,
+    /// Sine and cosine of the origin latitude
+    lat: (f64, f64),
+    /// Sine and cosine of the origin longitude
+    lng: (f64, f64),
+}
+
+impl LocalFrame {
+    /// Frame at a coordinate and height above the ellipsoid
+    pub fn new(origin: &Coordinate, height: f64, ellipsoid: Ellipsoid) -> Self {
+        Self {
+            origin: Ecef::from_geodetic(origin, height, &ellipsoid),
+            ellipsoid,
+            lat: origin.lat.to_radians().sin_cos(),
+            lng: origin.lng.to_radians().sin_cos(),
+        }
+    }
+
+    /// Origin of the frame in ECEF
+    pub fn origin(&self) -> Ecef {
+        self.origin
+    }
+
+    /// ENU offset of an ECEF point from the origin
+    pub fn to_enu(&self, ecef: &Ecef) -> Enu {
+        let ((sin_lat, cos_lat), (sin_lng, cos_lng)) = (self.lat, self.lng);
+        let (dx, dy, dz) = (
+            ecef.x - self.origin.x,
+            ecef.y - self.origin.y,
+            ecef.z - self.origin.z,
+        );
+        Enu {
+            east: -sin_lng * dx + cos_lng * dy,
+            north: -sin_lat * cos_lng * dx - sin_lat * sin_lng * dy + cos_lat * dz,
+            up: cos_lat * cos_lng * dx + cos_lat * sin_lng * dy + sin_lat * dz,
+        }
+    }
+
+    /// ECEF point at an ENU offset from the origin
+    pub fn from_enu(&self, enu: &Enu) -> Ecef {
+        let ((sin_lat, cos_lat), (sin_lng, cos_lng)) = (self.lat, self.lng);
+        Ecef {
+            x: self.origin.x - sin_lng * enu.east - sin_lat * cos_lng * enu.north
+                + cos_lat * cos_lng * enu.up,
+            y: self.origin.y + cos_lng * enu.east - sin_lat * sin_lng * enu.north
+                + cos_lat * sin_lng * enu.up,
+            z: self.origin.z + cos_lat * enu.north + sin_lat * enu.up,
+        }
+    }
+
+    /// NED offset of an ECEF point from the origin
+    pub fn to_ned(&self, ecef: &Ecef) -> Ned {
+        self.to_enu(ecef).into()
+    }
+
+    /// ECEF point at a NED offset from the origin
+    pub fn from_ned(&self, ned: &Ned) -> Ecef {
+        self.from_enu(&Enu::from(*ned))
+    }
+
+    /// ENU offset of a coordinate with a height above the ellipsoid
+    pub fn enu_of(&self, coordinate: &Coordinate, height: f64) -> Enu {
+        self.to_enu(&Ecef::from_geodetic(coordinate, height, &self.ellipsoid))
+    }
+
+    /// Coordinate and height above the ellipsoid at an ENU offset
+    pub fn from_enu_geodetic(&self, east: f64, north: f64, up: f64) -> (Coordinate, f64) {
+        self.from_enu(&Enu { east, north, up })
+            .to_geodetic(&self.ellipsoid)
+    }
+}
+
+/// Transverse Mercator projection (as used by national grids and UTM)
+///
+/// Uses the series of Krüger to the third order in the third flattening,
+/// which is accurate to better than a millimetre within a few degrees of the
+/// central meridian.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransverseMercator {
+    /// Ellipsoid of the datum
+    pub ellipsoid: Ellipsoid,
+    /// Latitude of the true origin in degrees
+    pub origin_lat: f64,
+    /// Longitude of the central meridian in degrees
+    pub central_meridian: f64,
+    /// Scale factor on the central meridian
+    pub scale: f64,
+    /// Easting of the true origin in meters
+    pub false_easting: f64,
+    /// Northing of the true origin in meters
+    pub false_northing: f64,
+}
+
+impl TransverseMercator {
+    /// Third flattening and rectifying radius of the ellipsoid
+    fn radius(&self) -> (f64, f64) {
+        let n = self.ellipsoid.f / (2. - self.ellipsoid.f);
+        let radius = self.ellipsoid.a / (1. + n) * (1. + n.powi(2) / 4. + n.powi(4) / 64.);
+        (n, radius)
+    }
+
+    /// Conformal latitude of a geodetic latitude (both in radians) as tangent
+    fn conformal_tan(&self, lat: f64) -> f64 {
+        let e = self.ellipsoid.e2().sqrt();
+        (lat.sin().atanh() - e * (e * lat.sin()).atanh()).sinh()
+    }
+
+    /// Krüger series from the conformal ξ' and η' to northing and easting divided by the radius
+    fn series(n: f64, xi: f64, eta: f64) -> (f64, f64) {
+        let alpha = [
+            n / 2. - 2. * n.powi(2) / 3. + 5. * n.powi(3) / 16.,
+            13. * n.powi(2) / 48. - 3. * n.powi(3) / 5.,
+            61. * n.powi(3) / 240.,
+        ];
+        alpha
+            .iter()
+            .zip(1..)
+            .fold((xi, eta), |(north, east), (alpha, j)| {
+                let j = 2. * j as f64;
+                (
+                    north + alpha * (j * xi).sin() * (j * eta).cosh(),
+                    east + alpha * (j * xi).cos() * (j * eta).sinh(),
+                )
+            })
+    }
+
+    /// Easting and northing in meters of a coordinate
+    pub fn project(&self, coordinate: &Coordinate) -> (f64, f64) {
+        let (n, radius) = self.radius();
+        let d_lng = crate::wrap_degrees(coordinate.lng - self.central_meridian).to_radians();
+        let tan = self.conformal_tan(coordinate.lat.to_radians());
+        let xi = tan.atan2(d_lng.cos());
+        let eta = (d_lng.sin() / (1. + tan.powi(2)).sqrt()).atanh();
+        let (north, east) = Self::series(n, xi, eta);
+        let (origin, _) = Self::series(
+            n,
+            self.conformal_tan(self.origin_lat.to_radians()).atan(),
+            0.,
+        );
+        (
+            self.false_easting + self.scale * radius * east,
+            self.false_northing + self.scale * radius * (north - origin),
+        )
+    }
+
+    /// Coordinate of an easting and northing in meters
+    pub fn unproject(&self, easting: f64, northing: f64) -> Coordinate {
+        let (n, radius) = self.radius();
+        let beta = [
+            n / 2. - 2. * n.powi(2) / 3. + 37. * n.powi(3) / 96.,
+            n.powi(2) / 48. + n.powi(3) / 15.,
+            17. * n.powi(3) / 480.,
+        ];
+        let delta = [
+            2. * n - 2. * n.powi(2) / 3. - 2. * n.powi(3),
+            7. * n.powi(2) / 3. - 8. * n.powi(3) / 5.,
+            56. * n.powi(3) / 15.,
+        ];
+        let (origin, _) = Self::series(
+            n,
+            self.conformal_tan(self.origin_lat.to_radians()).atan(),
+            0.,
+        );
+        let xi = (northing - self.false_northing) / (self.scale * radius) + origin;
+        let eta = (easting - self.false_easting) / (self.scale * radius);
+        let (xi_prime, eta_prime) =
+            beta.iter()
+                .zip(1..)
+                .fold((xi, eta), |(xi_prime, eta_prime), (beta, j)| {
+                    let j = 2. * j as f64;
+                    (
+                        xi_prime - beta * (j * xi).sin() * (j * eta).cosh(),
+                        eta_prime - beta * (j * xi).cos() * (j * eta).sinh(),
+                    )
+                });
+        let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+        let lat = delta.iter().zip(1..).fold(chi, |lat, (delta, j)| {
+            lat + delta * (2. * j as f64 * chi).sin()
+        });
+        Coordinate {
+            lat: lat.to_degrees(),
+            lng: crate::wrap_degrees(
+                self.central_meridian + eta_prime.sinh().atan2(xi_prime.cos()).to_degrees(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axes() {
+        let wgs84 = Ellipsoid::WGS84;
+        let origin = Ecef::from_geodetic(&Coordinate::new(0., 0.), 0., &wgs84);
+        assert_eq!(
+            origin,
+            Ecef {
+                x: wgs84.a,
+                y: 0.,
+                z: 0.
+            }
+        );
+        let pole = Ecef::from_geodetic(&Coordinate::new(90., 0.), 100., &wgs84);
+        assert!(pole.x.abs() < 1e-6 && (pole.z - wgs84.b() - 100.).abs() < 1e-6);
+        let (coordinate, height) = pole.to_geodetic(&wgs84);
+        assert!((coordinate.lat - 90.).abs() < 1e-12);
+        assert!((height - 100.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_round_trip_millimetre() {
+        for ellipsoid in [Ellipsoid::WGS84, Ellipsoid::BESSEL1841, Ellipsoid::AIRY1830] {
+            for lat in (-90..=90).step_by(15) {
+                for lng in (-180..180).step_by(45) {
+                    for height in [-400., 0., 8_848., 400_000.] {
+                        let coordinate = Coordinate::new(lat as f64 + 0.123, lng as f64 + 0.456);
+                        let coordinate = Coordinate::new(coordinate.lat.min(90.), coordinate.lng);
+                        let ecef = Ecef::from_geodetic(&coordinate, height, &ellipsoid);
+                        let (back, back_height) = ecef.to_geodetic(&ellipsoid);
+                        let back_ecef = Ecef::from_geodetic(&back, back_height, &ellipsoid);
+                        assert!(ecef.distance(&back_ecef) < 1e-3);
+                        assert!((back_height - height).abs() < 1e-3);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_position() {
+        let position =
+            Position::new(Coordinate::new(10., 20.)).with_altitude(Altitude::ellipsoidal(30.));
+        let ecef = Ecef::from_position(&position, &Ellipsoid::WGS84).unwrap();
+        let back = ecef.to_position(&Ellipsoid::WGS84);
+        assert!((back.altitude.unwrap().meters - 30.).abs() < 1e-6);
+        let above_sea =
+            Position::new(Coordinate::new(10., 20.)).with_altitude(Altitude::above_sea_level(30.));
+        assert!(matches!(
+            Ecef::from_position(&above_sea, &Ellipsoid::WGS84),
+            Err(CoordinateError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_transverse_mercator() {
+        // Worked example of the Ordnance Survey guide to coordinate systems
+        let grid = TransverseMercator {
+            ellipsoid: Ellipsoid::AIRY1830,
+            origin_lat: 49.,
+            central_meridian: -2.,
+            scale: 0.999_601_271_7,
+            false_easting: 400_000.,
+            false_northing: -100_000.,
+        };
+        let coordinate = Coordinate::new(
+            52. + 39. / 60. + 27.2531 / 3600.,
+            1. + 43. / 60. + 4.5177 / 3600.,
+        );
+        let (easting, northing) = grid.project(&coordinate);
+        assert!((easting - 651_409.903).abs() < 0.01, "{easting}");
+        assert!((northing - 313_177.270).abs() < 0.01, "{northing}");
+        let back = grid.unproject(easting, northing);
+        assert!(back.distance(&coordinate) < 0.001);
+    }
+
+    #[test]
+    fn test_local_frame() {
+        let origin = Coordinate::new(-33.86, 151.21);
+        let frame = LocalFrame::new(&origin, 10., Ellipsoid::WGS84);
+        let up = frame.enu_of(&origin, 11.);
+        assert!(up.east.abs() < 1e-9 && up.north.abs() < 1e-9 && (up.up - 1.).abs() < 1e-9);
+
+        let enu = Enu {
+            east: 1234.5,
+            north: -678.9,
+            up: 42.,
+        };
+        let back = frame.to_enu(&frame.from_enu(&enu));
+        assert!((back.east - enu.east).abs() < 1e-6);
+        assert!((back.north - enu.north).abs() < 1e-6);
+        assert!((back.up - enu.up).abs() < 1e-6);
+
+        let ned = frame.to_ned(&frame.from_enu(&enu));
+        assert!((ned.down + 42.).abs() < 1e-6);
+        assert!(frame.from_ned(&ned).distance(&frame.from_enu(&enu)) < 1e-6);
+    }
+}
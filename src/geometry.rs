@@ -0,0 +1,197 @@
+use crate::{Altitude, AltitudeReference, BoundingBox, Coordinate, CoordinateError, Position};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Points, lines and areas made up of positions
+///
+/// This follows the geometry types of the Simple Features model, which are
+/// shared by GeoJSON, WKT and others. Polygons are lists of closed rings, the
+/// first being the exterior and the others holes.
+///
+/// ```
+/// # use recoord::{Coordinate, geometry::Geometry};
+/// let line = Geometry::LineString(vec![
+///     Coordinate::new(50., 7.).into(),
+///     Coordinate::new(51., 8.).into(),
+/// ]);
+/// assert_eq!(line.kind(), "LineString");
+/// let bbox = line.bounding_box().unwrap();
+/// assert_eq!((bbox.south(), bbox.west(), bbox.north(), bbox.east()), (50., 7., 51., 8.));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    /// A single position
+    Point(Position),
+    /// Unconnected positions
+    MultiPoint(Vec<Position>),
+    /// Positions connected by straight lines
+    LineString(Vec<Position>),
+    /// Multiple lines
+    MultiLineString(Vec<Vec<Position>>),
+    /// An area given by its exterior ring and optional holes
+    Polygon(Vec<Vec<Position>>),
+    /// Multiple areas
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+}
+
+impl Geometry {
+    /// Name of the geometry type (e.g. "LineString")
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Geometry::Point(_) => "Point",
+            Geometry::MultiPoint(_) => "MultiPoint",
+            Geometry::LineString(_) => "LineString",
+            Geometry::MultiLineString(_) => "MultiLineString",
+            Geometry::Polygon(_) => "Polygon",
+            Geometry::MultiPolygon(_) => "MultiPolygon",
+        }
+    }
+
+    /// All positions of the geometry in order
+    pub fn positions(&self) -> Vec<&Position> {
+        match self {
+            Geometry::Point(position) => vec![position],
+            Geometry::MultiPoint(positions) | Geometry::LineString(positions) => {
+                positions.iter().collect()
+            }
+            Geometry::MultiLineString(lines) | Geometry::Polygon(lines) => {
+                lines.iter().flatten().collect()
+            }
+            Geometry::MultiPolygon(polygons) => polygons.iter().flatten().flatten().collect(),
+        }
+    }
+
+    /// All coordinates of the geometry in order
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        self.positions()
+            .into_iter()
+            .map(|position| position.coordinate.clone())
+            .collect()
+    }
+
+    /// Whether there are positions and all of them have an altitude above the ellipsoid
+    pub fn is_3d(&self) -> bool {
+        let positions = self.positions();
+        !positions.is_empty()
+            && positions.iter().all(|position| {
+                matches!(
+                    position.altitude,
+                    Some(Altitude {
+                        reference: AltitudeReference::Ellipsoid,
+                        ..
+                    })
+                )
+            })
+    }
+
+    /// Smallest box containing all positions (None for empty geometries)
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_points(
+            self.positions()
+                .into_iter()
+                .map(|position| &position.coordinate),
+        )
+    }
+}
+
+impl From<Coordinate> for Geometry {
+    fn from(coordinate: Coordinate) -> Self {
+        Geometry::Point(coordinate.into())
+    }
+}
+
+impl From<Position> for Geometry {
+    fn from(position: Position) -> Self {
+        Geometry::Point(position)
+    }
+}
+
+impl From<BoundingBox> for Geometry {
+    /// Counterclockwise polygon of the box
+    ///
+    /// Boxes crossing the antimeridian are split into two polygons.
+    fn from(bounds: BoundingBox) -> Self {
+        let rectangle = |west: f64, east: f64| {
+            vec![[
+                (bounds.south, west),
+                (bounds.south, east),
+                (bounds.north, east),
+                (bounds.north, west),
+                (bounds.south, west),
+            ]
+            .into_iter()
+            .map(|(lat, lng)| Position::new(Coordinate::new(lat, lng)))
+            .collect()]
+        };
+        if bounds.crosses_antimeridian() {
+            Geometry::MultiPolygon(vec![
+                rectangle(bounds.west, 180.),
+                rectangle(-180., bounds.east),
+            ])
+        } else {
+            Geometry::Polygon(rectangle(bounds.west, bounds.east))
+        }
+    }
+}
+
+impl TryFrom<Geometry> for Position {
+    type Error = CoordinateError;
+
+    /// Only points can be converted
+    fn try_from(geometry: Geometry) -> Result<Self, Self::Error> {
+        match geometry {
+            Geometry::Point(position) => Ok(position),
+            _ => Err(CoordinateError::Unsupported),
+        }
+    }
+}
+
+impl TryFrom<Geometry> for Coordinate {
+    type Error = CoordinateError;
+
+    /// Only points can be converted
+    fn try_from(geometry: Geometry) -> Result<Self, Self::Error> {
+        Position::try_from(geometry).map(Coordinate::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounding_box_polygon() {
+        let bounds = BoundingBox::new(-10., 170., 10., -170.).unwrap();
+        let Geometry::MultiPolygon(polygons) = Geometry::from(bounds) else {
+            panic!("Boxes crossing the antimeridian are split");
+        };
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[0][0].len(), 5);
+        let bounds = BoundingBox::new(50., 6., 51., 7.).unwrap();
+        let polygon = Geometry::from(bounds);
+        assert_eq!(polygon.bounding_box(), Some(bounds));
+        assert_eq!(polygon.positions()[1].coordinate, Coordinate::new(50., 7.));
+    }
+
+    #[test]
+    fn test_point() {
+        let point = Geometry::from(Coordinate::new(1., 2.));
+        assert_eq!(
+            Coordinate::try_from(point).unwrap(),
+            Coordinate::new(1., 2.)
+        );
+        assert!(Coordinate::try_from(Geometry::MultiPoint(vec![])).is_err());
+        assert_eq!(Geometry::MultiPoint(vec![]).bounding_box(), None);
+        assert!(!Geometry::MultiPoint(vec![]).is_3d());
+        let position = Position::new(Coordinate::new(1., 2.));
+        assert!(!Geometry::from(
+            position
+                .clone()
+                .with_altitude(Altitude::above_sea_level(3.))
+        )
+        .is_3d());
+        assert!(Geometry::from(position.with_altitude(Altitude::ellipsoidal(3.))).is_3d());
+    }
+}
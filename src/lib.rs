@@ -11,20 +11,44 @@
 use std::{
     fmt,
     fmt::{Display, Formatter},
+    ops::Deref,
 };
+/// Rectangular areas on the globe
+mod bounding_box;
+/// Geodetic datums and transformations between them
+pub mod datum;
 /// A wrapper around different coordinate formats
 pub mod formats;
+/// Ellipsoids and cartesian (ECEF, ENU, NED) coordinates
+pub mod geodesy;
+/// Points, lines and areas shared by vector formats
+pub mod geometry;
+/// Coordinates with altitude
+mod position;
 
 /// A wrapper around differend resolvers for Coordinates
 pub mod resolvers;
 
+pub use bounding_box::BoundingBox;
+pub use position::{Altitude, AltitudeReference, Position};
+pub use resolvers::{BlockingResolver, Resolution, ResolutionSource, Resolver};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "format_any")]
 use std::str::FromStr;
 
-#[cfg(any(feature = "format_dd", feature = "format_dms", feature = "resolve_osm"))]
+#[cfg(any(
+    feature = "format_dd",
+    feature = "format_dms",
+    feature = "format_gauss_krueger",
+    feature = "format_geouri",
+    feature = "format_iso6709",
+    feature = "format_lv95",
+    feature = "format_nmea",
+    feature = "resolve_osm"
+))]
 use std::num::ParseFloatError;
 
 use thiserror::Error;
@@ -44,6 +68,8 @@ pub struct Coordinate {
 impl Coordinate {
     /// Create a new coordinate with longitude and latitude
     ///
+    /// The values aren't checked, use [`Coordinate::try_new`] for user input.
+    ///
     /// ```
     /// /// Normal Coordinate creation
     /// # use recoord::Coordinate;
@@ -54,6 +80,294 @@ impl Coordinate {
     pub fn new(lat: f64, lng: f64) -> Self {
         Self { lat, lng }
     }
+
+    /// Create a new coordinate, rejecting values outside of -90 - 90 / -180 - 180
+    ///
+    /// ```
+    /// # use recoord::Coordinate;
+    /// assert!(Coordinate::try_new(50.9, 6.9).is_ok());
+    /// assert!(Coordinate::try_new(200., 500.).is_err());
+    /// assert!(Coordinate::try_new(f64::NAN, 0.).is_err());
+    /// ```
+    pub fn try_new(lat: f64, lng: f64) -> Result<Self, CoordinateError> {
+        Validation::STRICT.apply(lat, lng)
+    }
+
+    /// Create a new coordinate, normalizing or rejecting invalid values
+    ///
+    /// ```
+    /// # use recoord::{Coordinate, Validation};
+    /// let coordinate = Coordinate::try_new_with(95., 190., Validation::LENIENT).unwrap();
+    /// assert_eq!(coordinate, Coordinate::new(90., -170.));
+    /// ```
+    pub fn try_new_with(
+        lat: f64,
+        lng: f64,
+        validation: Validation,
+    ) -> Result<Self, CoordinateError> {
+        validation.apply(lat, lng)
+    }
+
+    /// Whether latitude and longitude are finite and in their ranges
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lng)
+    }
+
+    /// Wrap the longitude into -180 - 180 (exclusive) and fold the latitude over the poles
+    ///
+    /// ```
+    /// # use recoord::Coordinate;
+    /// assert_eq!(Coordinate::new(10., 190.).normalize(), Coordinate::new(10., -170.));
+    /// assert_eq!(Coordinate::new(10., 180.).normalize(), Coordinate::new(10., -180.));
+    /// // Going 10° over the north pole ends up on the other side of the globe
+    /// assert_eq!(Coordinate::new(100., 20.).normalize(), Coordinate::new(80., -160.));
+    /// ```
+    pub fn normalize(&self) -> Coordinate {
+        let mut lat = wrap_degrees(self.lat);
+        let mut lng = self.lng;
+        if lat > 90. {
+            lat = 180. - lat;
+            lng += 180.;
+        } else if lat < -90. {
+            lat = -180. - lat;
+            lng += 180.;
+        }
+        Coordinate {
+            lat,
+            lng: wrap_degrees(lng),
+        }
+    }
+
+    /// Longitude difference to another coordinate, going the short way around
+    ///
+    /// The result is positive when `other` is east of this coordinate.
+    ///
+    /// ```
+    /// # use recoord::Coordinate;
+    /// let fiji = Coordinate::new(-18., 178.);
+    /// let samoa = Coordinate::new(-14., -172.);
+    /// assert_eq!(fiji.lng_delta(&samoa), 10.);
+    /// assert_eq!(samoa.lng_delta(&fiji), -10.);
+    /// ```
+    pub fn lng_delta(&self, other: &Coordinate) -> f64 {
+        wrap_degrees(other.lng - self.lng)
+    }
+
+    /// Point halfway along the great circle to another coordinate
+    ///
+    /// ```
+    /// # use recoord::Coordinate;
+    /// let midpoint = Coordinate::new(0., 170.).midpoint(&Coordinate::new(0., -170.));
+    /// assert!(midpoint.lat.abs() < 1e-9);
+    /// assert!((midpoint.lng.abs() - 180.).abs() < 1e-9);
+    /// ```
+    pub fn midpoint(&self, other: &Coordinate) -> Coordinate {
+        self.interpolate(other, 0.5)
+    }
+
+    /// Point at a fraction (0 - 1) of the way along the great circle to another coordinate
+    ///
+    /// The result is normalized, so the path may cross the antimeridian.
+    /// For antipodal coordinates (where there is no single great circle)
+    /// the latitude and the short longitude difference are interpolated linearly.
+    pub fn interpolate(&self, other: &Coordinate, fraction: f64) -> Coordinate {
+        let angle = self.distance(other) / EARTH_RADIUS;
+        if angle.sin().abs() < 1e-12 {
+            return Coordinate {
+                lat: self.lat + (other.lat - self.lat) * fraction,
+                lng: self.lng + self.lng_delta(other) * fraction,
+            }
+            .normalize();
+        }
+        let a = ((1. - fraction) * angle).sin() / angle.sin();
+        let b = (fraction * angle).sin() / angle.sin();
+        let (lat_1, lng_1) = (self.lat.to_radians(), self.lng.to_radians());
+        let (lat_2, lng_2) = (other.lat.to_radians(), other.lng.to_radians());
+        let x = a * lat_1.cos() * lng_1.cos() + b * lat_2.cos() * lng_2.cos();
+        let y = a * lat_1.cos() * lng_1.sin() + b * lat_2.cos() * lng_2.sin();
+        let z = a * lat_1.sin() + b * lat_2.sin();
+        Coordinate {
+            lat: z.atan2(x.hypot(y)).to_degrees(),
+            lng: y.atan2(x).to_degrees(),
+        }
+        .normalize()
+    }
+
+    /// Great circle distance to another coordinate in meters
+    ///
+    /// This uses the haversine formula on a sphere with the mean earth radius,
+    /// which is accurate to about 0.5%.
+    ///
+    /// ```
+    /// # use recoord::Coordinate;
+    /// let cologne = Coordinate::new(50.9375, 6.9603);
+    /// let berlin = Coordinate::new(52.5200, 13.4050);
+    /// let distance = cologne.distance(&berlin);
+    /// assert!((distance - 477_000.).abs() < 1_000.);
+    /// ```
+    pub fn distance(&self, other: &Coordinate) -> f64 {
+        let d_lat = (other.lat - self.lat).to_radians();
+        let d_lng = (other.lng - self.lng).to_radians();
+        let a = (d_lat / 2.).sin().powi(2)
+            + self.lat.to_radians().cos()
+                * other.lat.to_radians().cos()
+                * (d_lng / 2.).sin().powi(2);
+        2. * EARTH_RADIUS * a.sqrt().min(1.).asin()
+    }
+}
+
+/// How out of range values are treated when creating a coordinate
+///
+/// Non finite values (NaN and infinity) are always rejected.
+/// The default rejects everything out of range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Validation {
+    /// Wrap longitudes outside of -180 - 180 around the globe (190 becomes -170)
+    pub wrap_longitude: bool,
+    /// Clamp latitudes outside of -90 - 90 to the poles instead of rejecting them
+    pub clamp_latitude: bool,
+}
+
+impl Validation {
+    /// Reject all values out of range
+    pub const STRICT: Validation = Validation {
+        wrap_longitude: false,
+        clamp_latitude: false,
+    };
+    /// Wrap the longitude and clamp the latitude
+    pub const LENIENT: Validation = Validation {
+        wrap_longitude: true,
+        clamp_latitude: true,
+    };
+
+    /// Create a coordinate following these rules
+    pub fn apply(&self, lat: f64, lng: f64) -> Result<Coordinate, CoordinateError> {
+        if !lat.is_finite() || !lng.is_finite() {
+            return Err(CoordinateError::InvalidValue);
+        }
+        let lat = match lat {
+            lat if (-90.0..=90.0).contains(&lat) => lat,
+            lat if self.clamp_latitude => lat.clamp(-90., 90.),
+            _ => return Err(CoordinateError::InvalidValue),
+        };
+        let lng = match lng {
+            lng if (-180.0..=180.0).contains(&lng) => lng,
+            lng if self.wrap_longitude => wrap_degrees(lng),
+            _ => return Err(CoordinateError::InvalidValue),
+        };
+        Ok(Coordinate { lat, lng })
+    }
+}
+
+/// A coordinate which is guaranteed to be valid
+///
+/// It can only be created through validation, so latitude and longitude are
+/// always finite and in range. It dereferences to [`Coordinate`] for reading.
+///
+/// ```
+/// # use recoord::{Coordinate, ValidCoordinate};
+/// let valid = ValidCoordinate::new(50.9, 6.9).unwrap();
+/// assert_eq!(valid.lat, 50.9);
+/// assert!(ValidCoordinate::try_from(Coordinate::new(91., 0.)).is_err());
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "Coordinate", into = "Coordinate")
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidCoordinate(Coordinate);
+
+impl ValidCoordinate {
+    /// Create a valid coordinate, rejecting values out of range
+    pub fn new(lat: f64, lng: f64) -> Result<Self, CoordinateError> {
+        Coordinate::try_new(lat, lng).map(Self)
+    }
+
+    /// Create a valid coordinate, normalizing or rejecting invalid values
+    pub fn new_with(lat: f64, lng: f64, validation: Validation) -> Result<Self, CoordinateError> {
+        validation.apply(lat, lng).map(Self)
+    }
+
+    /// The plain coordinate
+    pub fn into_inner(self) -> Coordinate {
+        self.0
+    }
+}
+
+impl Deref for ValidCoordinate {
+    type Target = Coordinate;
+    fn deref(&self) -> &Coordinate {
+        &self.0
+    }
+}
+
+impl TryFrom<Coordinate> for ValidCoordinate {
+    type Error = CoordinateError;
+    fn try_from(coordinate: Coordinate) -> Result<Self, Self::Error> {
+        Self::new(coordinate.lat, coordinate.lng)
+    }
+}
+
+impl From<ValidCoordinate> for Coordinate {
+    fn from(valid: ValidCoordinate) -> Self {
+        valid.0
+    }
+}
+
+impl Display for ValidCoordinate {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Wrap an angle in degrees into -180 - 180 (exclusive)
+pub(crate) fn wrap_degrees(degrees: f64) -> f64 {
+    (degrees + 180.).rem_euclid(360.) - 180.
+}
+
+/// Mean earth radius in meters (IUGG)
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// How exact a coordinate is, as the maximum deviation in degrees
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precision {
+    /// Maximum deviation of the latitude (±)
+    pub lat: f64,
+    /// Maximum deviation of the longitude (±)
+    pub lng: f64,
+}
+
+impl Precision {
+    /// Maximum deviation in meters for a coordinate at the given location
+    ///
+    /// ```
+    /// # use recoord::{Coordinate, Precision};
+    /// let precision = Precision { lat: 0.001, lng: 0. };
+    /// let meters = precision.meters(&Coordinate::new(50., 7.));
+    /// assert!((meters - 111.2).abs() < 0.1);
+    /// ```
+    pub fn meters(&self, at: &Coordinate) -> f64 {
+        let lat = self.lat.to_radians() * EARTH_RADIUS;
+        let lng = self.lng.to_radians() * EARTH_RADIUS * at.lat.to_radians().cos().abs();
+        lat.hypot(lng)
+    }
+
+    /// Precision of a deviation in meters along both axes at the given location
+    ///
+    /// ```
+    /// # use recoord::{Coordinate, Precision};
+    /// let precision = Precision::from_meters(50., &Coordinate::new(60., 7.));
+    /// assert!((precision.lng - 2. * precision.lat).abs() < 1e-12);
+    /// ```
+    pub fn from_meters(meters: f64, at: &Coordinate) -> Self {
+        let lat = (meters / EARTH_RADIUS).to_degrees();
+        Precision {
+            lat,
+            lng: lat / at.lat.to_radians().cos().abs(),
+        }
+    }
 }
 
 impl Display for Coordinate {
@@ -73,24 +387,66 @@ pub enum CoordinateError {
     InvalidValue,
     /// String passed into from_str was malformed
     #[cfg(any(
-        feature = "format_dd",
-        feature = "format_dms",
-        feature = "format_geohash"
+        feature = "format_any",
+        feature = "format_gpx",
+        feature = "format_h3",
+        feature = "format_kml",
+        feature = "format_s2",
+        feature = "format_wkb"
     ))]
     #[error("String passed into from_str was malformed")]
     Malformed,
     /// String passed into from_str contained invalid floats
-    #[cfg(any(feature = "format_dd", feature = "format_dms", feature = "resolve_osm"))]
+    #[cfg(any(
+        feature = "format_dd",
+        feature = "format_dms",
+        feature = "format_gauss_krueger",
+        feature = "format_geouri",
+        feature = "format_iso6709",
+        feature = "format_lv95",
+        feature = "format_nmea",
+        feature = "resolve_osm"
+    ))]
     #[error("String passed into from_str contained invalid floats")]
     ParseFloatError(#[from] ParseFloatError),
     /// Location not resolvable
-    #[cfg(feature = "resolve_osm")]
     #[error("Location not resolvable")]
     Unresolveable,
+    /// The resolver doesn't support this operation
+    #[error("The resolver doesn't support this operation")]
+    Unsupported,
     /// There was a problem connecting to the API
-    #[cfg(feature = "resolve_osm")]
+    #[cfg(feature = "resolve_http")]
     #[error("There was a problem connecting to the API")]
     ReqwestError(#[from] reqwest::Error),
+    /// The API answered with an unsuccessful HTTP status (after all retries)
+    #[cfg(feature = "resolve_http")]
+    #[error("The API answered with HTTP status {0}")]
+    HttpStatus(u16),
+    /// A line of a gazetteer file couldn't be parsed
+    #[cfg(feature = "resolve_gazetteer")]
+    #[error("Line {0} of the gazetteer file is malformed")]
+    MalformedRecord(usize),
+    /// Input is malformed starting at a byte offset
+    #[cfg(any(feature = "format_polyline", feature = "format_wkb"))]
+    #[error("Input is malformed at byte {0}")]
+    MalformedAt(usize),
+    /// A file couldn't be read or written
+    #[cfg(any(feature = "resolve_cache", feature = "resolve_gazetteer"))]
+    #[error("A file couldn't be read or written")]
+    IoError(#[from] std::io::Error),
+    /// JSON couldn't be read or written
+    #[cfg(any(feature = "resolve_cache", feature = "format_geojson"))]
+    #[error("JSON couldn't be read or written")]
+    JsonError(#[from] serde_json::Error),
+    /// XML couldn't be read
+    #[cfg(any(feature = "format_gpx", feature = "format_kml"))]
+    #[error("XML couldn't be read")]
+    XmlError(#[from] quick_xml::Error),
+    /// A zip archive couldn't be read or written
+    #[cfg(feature = "format_kmz")]
+    #[error("A zip archive couldn't be read or written")]
+    ZipError(#[from] zip::result::ZipError),
 }
 
 impl TryFrom<(f64, f64)> for Coordinate {
@@ -110,49 +466,362 @@ impl TryFrom<(f64, f64)> for Coordinate {
     /// let from = Coordinate::try_from((100., 20.));
     /// assert!(from.is_err());
     /// ```
-    fn try_from(tupl_coord: (f64, f64)) -> Result<Self, Self::Error> {
-        match tupl_coord {
-            (lat, lng) if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lng) => {
-                Ok(Self { lat, lng })
-            }
-            _ => Err(CoordinateError::InvalidValue),
-        }
+    fn try_from((lat, lng): (f64, f64)) -> Result<Self, Self::Error> {
+        Self::try_new(lat, lng)
     }
 }
 
 #[cfg(feature = "format_any")]
-impl FromStr for Coordinate {
-    type Err = CoordinateError;
-
-    fn from_str(str_coords: &str) -> Result<Self, Self::Err> {
-        let mut result: Result<Coordinate, CoordinateError> = Err(CoordinateError::MissingParser);
+impl Coordinate {
+    /// Parse a string and report which format matched and how precise it is
+    ///
+    /// Formats are tried in the same order as by [`FromStr`].
+    ///
+    /// ```
+    /// # use recoord::{Coordinate, ResolutionSource};
+    /// # #[cfg(feature = "format_dd")]
+    /// # {
+    /// use recoord::formats::Format;
+    /// let parsed = Coordinate::parse_detailed("50.94,6.96").unwrap();
+    /// assert_eq!(parsed.source, ResolutionSource::Parsed(Format::DD));
+    /// assert!((parsed.precision.unwrap().lat - 0.005).abs() < 1e-12);
+    /// # }
+    /// ```
+    pub fn parse_detailed(str_coords: &str) -> Result<Resolution, CoordinateError> {
+        let mut result = Err(CoordinateError::MissingParser);
 
         #[cfg(feature = "format_dd")]
         {
-            result = result
-                .or_else(|_| formats::dd::DDCoordinate::from_str(str_coords).map(Coordinate::from));
+            result = result.or_else(|_| {
+                formats::dd::DDCoordinate::from_str(str_coords)
+                    .map(|dd| Resolution::parsed(formats::Format::DD, dd.precision(), dd.into()))
+            });
         }
         #[cfg(feature = "format_dms")]
         {
             result = result.or_else(|_| {
-                formats::dms::DMSCoordinate::from_str(str_coords).map(Coordinate::from)
+                formats::dms::DMSCoordinate::from_str(str_coords).map(|dms| {
+                    Resolution::parsed(formats::Format::DMS, dms.precision(), dms.into())
+                })
             });
         }
+        #[cfg(feature = "format_gauss_krueger")]
+        {
+            result = result.or_else(|_| {
+                formats::gauss_krueger::GaussKrueger::from_str(str_coords).map(|gk| {
+                    Resolution::parsed(formats::Format::GaussKrueger, gk.precision(), gk.into())
+                })
+            });
+        }
+        // Compact grid references (e.g. "tq30") are valid geohashes too, so only
+        // those written in capitals or with spaces are tried before geohashes
+        #[cfg(feature = "format_osgb")]
+        let osgb = |_| {
+            formats::osgb::GridReference::from_str(str_coords).map(|grid| {
+                Resolution::parsed(formats::Format::Osgb, grid.precision(), grid.into())
+            })
+        };
+        #[cfg(feature = "format_osgb")]
+        let osgb_first = formats::osgb::GridReference::is_unambiguous(str_coords);
+        #[cfg(feature = "format_osgb")]
+        if osgb_first {
+            result = result.or_else(osgb);
+        }
         #[cfg(feature = "format_geohash")]
         {
-            result = result
-                .or_else(|_| formats::geohash::Geohash::from_str(str_coords).map(Coordinate::from));
+            result = result.or_else(|_| {
+                formats::geohash::Geohash::from_str(str_coords).map(|hash| {
+                    Resolution::parsed(formats::Format::Geohash, hash.precision(), hash.into())
+                })
+            });
+        }
+        #[cfg(feature = "format_osgb")]
+        if !osgb_first {
+            result = result.or_else(osgb);
+        }
+        // Bare S2 tokens are valid geohashes too, so only prefixed ones are tried
+        #[cfg(feature = "format_s2")]
+        {
+            result = result.or_else(|_| {
+                let token = str_coords
+                    .trim()
+                    .strip_prefix("s2:")
+                    .ok_or(CoordinateError::Malformed)?;
+                formats::s2::S2CellId::from_str(token).map(|cell| {
+                    Resolution::parsed(formats::Format::S2, cell.precision(), cell.into())
+                })
+            });
+        }
+        #[cfg(feature = "format_geojson")]
+        {
+            result = result.or_else(|_| {
+                formats::geojson::GeoJson::from_str(str_coords).and_then(|json| {
+                    let precision = json.precision();
+                    Ok(Resolution::parsed(formats::Format::GeoJson, precision, json.try_into()?))
+                })
+            });
+        }
+        #[cfg(feature = "format_geouri")]
+        {
+            result = result.or_else(|_| {
+                formats::geo_uri::GeoUri::from_str(str_coords).map(|uri| {
+                    Resolution::parsed(formats::Format::GeoUri, uri.precision(), uri.into())
+                })
+            });
+        }
+        #[cfg(feature = "format_iso6709")]
+        {
+            result = result.or_else(|_| {
+                formats::iso6709::Iso6709::from_str(str_coords).map(|point| {
+                    Resolution::parsed(formats::Format::Iso6709, point.precision(), point.into())
+                })
+            });
+        }
+        #[cfg(feature = "format_lv95")]
+        {
+            result = result.or_else(|_| {
+                formats::lv95::Lv95::from_str(str_coords).map(|lv95| {
+                    Resolution::parsed(formats::Format::Lv95, lv95.precision(), lv95.into())
+                })
+            });
+        }
+        #[cfg(feature = "format_nmea")]
+        {
+            result = result.or_else(|_| {
+                formats::nmea::NmeaSentence::from_str(str_coords).map(|sentence| {
+                    Resolution::parsed(formats::Format::Nmea, sentence.precision(), sentence.into())
+                })
+            });
+        }
+        #[cfg(feature = "format_tile")]
+        {
+            result = result.or_else(|_| {
+                formats::tile::Tile::from_str(str_coords).map(|tile| {
+                    Resolution::parsed(formats::Format::Tile, tile.precision(), tile.into())
+                })
+            });
+        }
+        #[cfg(feature = "format_wkt")]
+        {
+            result = result.or_else(|_| {
+                formats::wkt::Wkt::from_str(str_coords).and_then(|wkt| {
+                    let precision = wkt.precision();
+                    Ok(Resolution::parsed(formats::Format::Wkt, precision, wkt.try_into()?))
+                })
+            });
         }
 
         result
     }
 }
 
-// /// Resolver for strings to Coordinates - this should be used for more expensive (and async) resolving
-// pub trait Resolver {
-//     /// Resolve a &str to a Coordinate
-//     fn resolve(s: &str) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Coordinate, CoordinateError>> + Send + '_>>;
-// }
+#[cfg(feature = "format_any")]
+impl FromStr for Coordinate {
+    type Err = CoordinateError;
+
+    fn from_str(str_coords: &str) -> Result<Self, Self::Err> {
+        Coordinate::parse_detailed(str_coords).map(|parsed| parsed.coordinate)
+    }
+}
+
+#[cfg(test)]
+mod antimeridian_tests {
+    use crate::Coordinate;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            Coordinate::new(-95., -10.).normalize(),
+            Coordinate::new(-85., 170.)
+        );
+        assert_eq!(
+            Coordinate::new(270., 0.).normalize(),
+            Coordinate::new(-90., 0.)
+        );
+        assert_eq!(
+            Coordinate::new(0., -540.).normalize(),
+            Coordinate::new(0., -180.)
+        );
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let start = Coordinate::new(0., 179.);
+        let end = Coordinate::new(0., -177.);
+        let quarter = start.interpolate(&end, 0.25);
+        assert!((quarter.lng - 180.).abs() < 1e-9 || (quarter.lng + 180.).abs() < 1e-9);
+        assert_eq!(start.interpolate(&end, 0.), start);
+        let end_point = start.interpolate(&end, 1.);
+        assert!(end_point.distance(&end) < 1e-3);
+
+        let north_pole = Coordinate::new(90., 0.);
+        let south_pole = Coordinate::new(-90., 0.);
+        assert!(north_pole.midpoint(&south_pole).lat.abs() < 1e-9);
+    }
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use crate::{Coordinate, CoordinateError, ValidCoordinate, Validation};
+
+    #[test]
+    fn test_strict() {
+        assert!(Coordinate::try_new(90., -180.).is_ok());
+        assert!(Coordinate::try_new(90.1, 0.).is_err());
+        assert!(Coordinate::try_new(0., 180.1).is_err());
+        assert!(Coordinate::try_new(0., f64::INFINITY).is_err());
+        assert!(!Coordinate::new(f64::NAN, 0.).is_valid());
+    }
+
+    #[test]
+    fn test_normalization() {
+        let wrap = Validation {
+            wrap_longitude: true,
+            clamp_latitude: false,
+        };
+        let coordinate = wrap.apply(10., -190.).unwrap();
+        assert!((coordinate.lng - 170.).abs() < 1e-9);
+        assert_eq!(wrap.apply(10., 540.).unwrap().lng, -180.);
+        assert!(matches!(
+            wrap.apply(-91., 0.),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert_eq!(Validation::LENIENT.apply(-91., 0.).unwrap().lat, -90.);
+        assert!(Validation::LENIENT.apply(f64::NAN, 0.).is_err());
+    }
+
+    #[test]
+    fn test_valid_coordinate() {
+        let valid = ValidCoordinate::new_with(100., 0., Validation::LENIENT).unwrap();
+        assert_eq!(valid.lat, 90.);
+        assert_eq!(Coordinate::from(valid), Coordinate::new(90., 0.));
+        assert!(ValidCoordinate::new(0., 181.).is_err());
+    }
+
+    #[cfg(feature = "format_dd")]
+    #[test]
+    fn test_parsers_validate() {
+        use crate::formats::dd::DDCoordinate;
+        assert!(matches!(
+            "200,500".parse::<DDCoordinate>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!("200,500".parse::<Coordinate>().is_err());
+        assert!("-90,180".parse::<Coordinate>().is_ok());
+    }
+
+    #[cfg(feature = "format_dms")]
+    #[test]
+    fn test_dms_validates() {
+        use crate::formats::dms::DMSCoordinate;
+        assert!("50°10'20\"N 10°25'30\"E".parse::<DMSCoordinate>().is_ok());
+        assert!(matches!(
+            "91°0'0\"N 10°0'0\"E".parse::<DMSCoordinate>(),
+            Err(CoordinateError::InvalidValue)
+        ));
+        assert!("50°61'0\"N 10°0'0\"E".parse::<DMSCoordinate>().is_err());
+    }
+}
+
+#[cfg(all(test, feature = "format_any"))]
+mod precision_tests {
+    use crate::Coordinate;
+
+    /// Precision of a parsed string
+    #[allow(dead_code)]
+    fn precision(str: &str) -> (f64, f64) {
+        let precision = Coordinate::parse_detailed(str).unwrap().precision.unwrap();
+        (precision.lat, precision.lng)
+    }
+
+    #[cfg(feature = "format_dd")]
+    #[test]
+    fn test_trailing_zeros() {
+        assert_eq!(precision("50.1,7.2"), (0.05, 0.05));
+        assert_eq!(precision("50.10,7.20"), (0.005, 0.005));
+        assert_eq!(precision("50.100000,7.2"), (0.0000005, 0.05));
+    }
+
+    #[cfg(feature = "format_dms")]
+    #[test]
+    fn test_written_seconds() {
+        let (lat, lng) = precision("50°10'0\"N 10°25'0\"E");
+        assert!((lat - 0.5 / 3600.).abs() < 1e-15 && (lng - 0.5 / 3600.).abs() < 1e-15);
+    }
+
+    #[cfg(feature = "format_geouri")]
+    #[test]
+    fn test_geo_uri_decimals() {
+        assert_eq!(precision("geo:50.9400,6.96"), (0.00005, 0.005));
+    }
+
+    #[cfg(feature = "format_wkt")]
+    #[test]
+    fn test_wkt_decimals() {
+        assert_eq!(precision("POINT(6.960 50.9400)"), (0.00005, 0.0005));
+    }
+
+    #[cfg(feature = "format_geojson")]
+    #[test]
+    fn test_geojson_decimals() {
+        let feature = r#"{"type": "Feature", "properties": {"coordinates": [1, 2]},
+            "geometry": {"type": "Point", "coordinates": [6.960, 50.9400]}}"#;
+        assert_eq!(precision(feature), (0.00005, 0.0005));
+    }
+
+    #[cfg(feature = "format_dd")]
+    #[test]
+    fn test_dd_equality_and_serde() {
+        use crate::formats::dd::DDCoordinate;
+        let written: DDCoordinate = "50.940, 6.960".parse().unwrap();
+        assert_eq!(written, "50.94, 6.96".parse().unwrap());
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&written).unwrap();
+            assert_eq!(json, r#"{"lat":50.94,"lng":6.96}"#);
+            let read: DDCoordinate = serde_json::from_str(&json).unwrap();
+            assert_eq!(read, written);
+            assert_eq!(read.precision().lat, 0.005);
+        }
+    }
+
+    #[cfg(feature = "format_dms")]
+    #[test]
+    fn test_dms_equality_and_serde() {
+        use crate::formats::dms::DMSCoordinate;
+        let written: DMSCoordinate = "50°10'0.0\"N 10°25'0\"E".parse().unwrap();
+        assert_eq!(written, "50°10'0\"N 10°25'0\"E".parse().unwrap());
+        let converted = DMSCoordinate::from(Coordinate::new(50.1, 10.3));
+        assert_eq!(converted.precision().lat, 0.);
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&written).unwrap();
+            assert!(!json.contains("precision"), "{json}");
+            let read: DMSCoordinate = serde_json::from_str(&json).unwrap();
+            assert_eq!(read, written);
+        }
+    }
+
+    #[cfg(feature = "format_wkt")]
+    #[test]
+    fn test_wkt_equality() {
+        use crate::formats::wkt::Wkt;
+        let written: Wkt = "POINT(1.0 2.0)".parse().unwrap();
+        assert_eq!(written, Wkt::from(Coordinate::new(2., 1.)));
+        #[cfg(feature = "serde")]
+        assert!(!serde_json::to_string(&written)
+            .unwrap()
+            .contains("precision"));
+    }
+
+    #[cfg(feature = "format_geojson")]
+    #[test]
+    fn test_geojson_equality() {
+        use crate::formats::geojson::GeoJson;
+        let written: GeoJson = r#"{"type": "Point", "coordinates": [1.0, 2.0]}"#.parse().unwrap();
+        assert_eq!(written, GeoJson::from(Coordinate::new(2., 1.)));
+    }
+}
 
 // #[cfg(test)]
 // mod tests {
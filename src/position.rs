@@ -0,0 +1,200 @@
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "format_any")]
+use std::str::FromStr;
+
+use crate::Coordinate;
+
+#[cfg(feature = "format_any")]
+use crate::CoordinateError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Surface an altitude is measured from
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AltitudeReference {
+    /// Height above the WGS 84 ellipsoid (as measured by GPS)
+    Ellipsoid,
+    /// Height above mean sea level (the geoid)
+    MeanSeaLevel,
+}
+
+/// Height of a position in meters above a reference surface
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Altitude {
+    /// Height in meters (negative below the reference surface)
+    pub meters: f64,
+    /// Surface the height is measured from
+    pub reference: AltitudeReference,
+}
+
+impl Altitude {
+    /// Height above the WGS 84 ellipsoid
+    pub fn ellipsoidal(meters: f64) -> Self {
+        Self {
+            meters,
+            reference: AltitudeReference::Ellipsoid,
+        }
+    }
+
+    /// Height above mean sea level
+    pub fn above_sea_level(meters: f64) -> Self {
+        Self {
+            meters,
+            reference: AltitudeReference::MeanSeaLevel,
+        }
+    }
+}
+
+/// A coordinate with an optional altitude
+///
+/// ```
+/// # use recoord::{Altitude, Coordinate, Position};
+/// let summit = Position::new(Coordinate::new(47.4211, 10.9849))
+///     .with_altitude(Altitude::above_sea_level(2962.));
+/// let valley = Position::new(Coordinate::new(47.4211, 10.9849))
+///     .with_altitude(Altitude::above_sea_level(962.));
+/// assert_eq!(summit.distance_3d(&valley), Some(2000.));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    /// Location on the surface
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub coordinate: Coordinate,
+    /// Height of the position (if known)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub altitude: Option<Altitude>,
+}
+
+impl Position {
+    /// Create a position without altitude
+    pub fn new(coordinate: Coordinate) -> Self {
+        Self {
+            coordinate,
+            altitude: None,
+        }
+    }
+
+    /// Set the altitude of the position
+    pub fn with_altitude(mut self, altitude: Altitude) -> Self {
+        self.altitude = Some(altitude);
+        self
+    }
+
+    /// Straight line distance in meters, including the height difference
+    ///
+    /// The surface distance is the great circle distance at the mean height of
+    /// both positions. Returns None, if an altitude is missing or both are
+    /// measured from different reference surfaces.
+    pub fn distance_3d(&self, other: &Position) -> Option<f64> {
+        let (own, other_altitude) = (self.altitude?, other.altitude?);
+        if own.reference != other_altitude.reference {
+            return None;
+        }
+        let mean_height = (own.meters + other_altitude.meters) / 2.;
+        let surface = self.coordinate.distance(&other.coordinate)
+            * (crate::EARTH_RADIUS + mean_height)
+            / crate::EARTH_RADIUS;
+        Some(surface.hypot(own.meters - other_altitude.meters))
+    }
+}
+
+impl From<Coordinate> for Position {
+    fn from(coordinate: Coordinate) -> Self {
+        Self::new(coordinate)
+    }
+}
+
+impl From<Position> for Coordinate {
+    fn from(position: Position) -> Self {
+        position.coordinate
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.altitude {
+            Some(altitude) => write!(f, "{},{}", self.coordinate, altitude.meters),
+            None => write!(f, "{}", self.coordinate),
+        }
+    }
+}
+
+#[cfg(feature = "format_any")]
+impl FromStr for Position {
+    type Err = CoordinateError;
+
+    /// Parse a position, keeping the altitude of formats which carry one
+    ///
+    /// Formats without altitude result in a position without altitude.
+    fn from_str(str_position: &str) -> Result<Self, Self::Err> {
+        #[cfg(feature = "format_geouri")]
+        if let Ok(uri) = crate::formats::geo_uri::GeoUri::from_str(str_position) {
+            return Ok(uri.into());
+        }
+        #[cfg(feature = "format_iso6709")]
+        if let Ok(point) = crate::formats::iso6709::Iso6709::from_str(str_position) {
+            return Ok(point.into());
+        }
+        #[cfg(feature = "format_nmea")]
+        if let Ok(sentence) = crate::formats::nmea::NmeaSentence::from_str(str_position) {
+            return Ok(sentence.into());
+        }
+
+        Coordinate::from_str(str_position).map(Position::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_3d() {
+        let ground =
+            Position::new(Coordinate::new(0., 0.)).with_altitude(Altitude::ellipsoidal(0.));
+        let drone =
+            Position::new(Coordinate::new(0., 0.001)).with_altitude(Altitude::ellipsoidal(100.));
+        let distance = ground.distance_3d(&drone).unwrap();
+        let surface = ground.coordinate.distance(&drone.coordinate);
+        assert!((distance - surface.hypot(100.)).abs() < 0.01);
+
+        let sea_level = drone.with_altitude(Altitude::above_sea_level(100.));
+        assert_eq!(ground.distance_3d(&sea_level), None);
+        assert_eq!(
+            ground.distance_3d(&Position::new(Coordinate::new(0., 0.))),
+            None
+        );
+    }
+
+    #[cfg(all(feature = "format_geouri", feature = "format_dd"))]
+    #[test]
+    fn test_from_str() {
+        let position: Position = "geo:50.9,6.9,54".parse().unwrap();
+        assert_eq!(position.altitude, Some(Altitude::ellipsoidal(54.)));
+        let position: Position = "50.9,6.9".parse().unwrap();
+        assert_eq!(position, Position::new(Coordinate::new(50.9, 6.9)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let position = Position::new(Coordinate::new(1., 2.));
+        let json = serde_json::to_string(&position).unwrap();
+        assert_eq!(json, r#"{"lat":1.0,"lng":2.0}"#);
+        let position = position.with_altitude(Altitude::above_sea_level(3.));
+        let json = serde_json::to_string(&position).unwrap();
+        assert_eq!(
+            json,
+            r#"{"lat":1.0,"lng":2.0,"altitude":{"meters":3.0,"reference":"MeanSeaLevel"}}"#
+        );
+        assert_eq!(serde_json::from_str::<Position>(&json).unwrap(), position);
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+use crate::{
+    resolvers::{BlockingResolver, Place, Resolver},
+    CoordinateError,
+};
+use futures_util::{stream, Stream, StreamExt};
+
+/// Result of a single query of a batch
+pub type BatchResult = Result<Place, CoordinateError>;
+
+/// Progress of a running batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of finished queries
+    pub done: usize,
+    /// Number of finished queries which failed
+    pub failed: usize,
+    /// Total number of queries (if known up front)
+    pub total: Option<usize>,
+}
+
+/// Resolves many queries with bounded concurrency
+///
+/// Results are returned in the order of the queries, each with its own
+/// error. Concurrency only overlaps the waiting for answers: all requests
+/// still go through the resolver, so its rate limiter stays in charge of
+/// how many requests per second are sent.
+///
+/// ```
+/// # use recoord::{Coordinate, CoordinateError};
+/// # use recoord::resolvers::{batch::Batch, BlockingResolver, Place};
+/// # struct Home;
+/// # impl BlockingResolver for Home {
+/// #     fn search_sync(&self, q: &str, _: usize) -> Result<Vec<Place>, CoordinateError> {
+/// #         Ok(if q == "home" { vec![Place::new(Coordinate::new(50.9, 7.5), "Home")] } else { vec![] })
+/// #     }
+/// # }
+/// let results = Batch::new(&Home)
+///     .with_concurrency(4)
+///     .on_progress(|progress| println!("{}/{:?}", progress.done, progress.total))
+///     .run_sync(["home", "work"]);
+/// assert!(results[0].is_ok());
+/// assert!(matches!(results[1], Err(CoordinateError::Unresolveable)));
+/// ```
+pub struct Batch<'r, R> {
+    /// Resolver used for all queries
+    resolver: &'r R,
+    /// Maximum number of queries in flight
+    concurrency: usize,
+    /// Called whenever a query finished
+    progress: Option<Box<dyn FnMut(Progress) + Send + 'r>>,
+}
+
+impl<'r, R> Batch<'r, R> {
+    /// Create a batch running one query at a time
+    pub fn new(resolver: &'r R) -> Self {
+        Self {
+            resolver,
+            concurrency: 1,
+            progress: None,
+        }
+    }
+
+    /// Maximum number of queries in flight (at least 1)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Call `callback` whenever a query finished (e.g. to update a progress bar)
+    pub fn on_progress(mut self, callback: impl FnMut(Progress) + Send + 'r) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+}
+
+/// Put results back into the order of the queries
+fn in_order(results: Vec<(usize, BatchResult)>) -> Vec<BatchResult> {
+    let mut ordered: Vec<Option<BatchResult>> = (0..results.len()).map(|_| None).collect();
+    for (index, result) in results {
+        ordered[index] = Some(result);
+    }
+    ordered
+        .into_iter()
+        .map(|result| result.unwrap_or(Err(CoordinateError::Unresolveable)))
+        .collect()
+}
+
+impl<R: Resolver> Batch<'_, R> {
+    /// Resolve all queries of a stream
+    pub async fn run<S: AsRef<str>>(mut self, queries: impl Stream<Item = S>) -> Vec<BatchResult> {
+        let (lower, upper) = queries.size_hint();
+        let mut progress = Progress {
+            done: 0,
+            failed: 0,
+            total: (Some(lower) == upper).then_some(lower),
+        };
+        let resolver = self.resolver;
+        let mut running = std::pin::pin!(queries
+            .enumerate()
+            .map(|(index, query)| async move { (index, resolver.resolve(query.as_ref()).await) })
+            .buffer_unordered(self.concurrency));
+
+        let mut results = vec![];
+        while let Some((index, result)) = running.next().await {
+            progress.done += 1;
+            if result.is_err() {
+                progress.failed += 1;
+            }
+            if let Some(callback) = &mut self.progress {
+                callback(progress);
+            }
+            results.push((index, result));
+        }
+        in_order(results)
+    }
+
+    /// Resolve all queries of an iterator
+    pub async fn run_iter<S: AsRef<str>>(
+        self,
+        queries: impl IntoIterator<Item = S>,
+    ) -> Vec<BatchResult> {
+        self.run(stream::iter(queries)).await
+    }
+}
+
+impl<R: BlockingResolver + Sync> Batch<'_, R> {
+    /// Resolve all queries, using up to `concurrency` threads
+    pub fn run_sync<S: AsRef<str> + Sync>(
+        self,
+        queries: impl IntoIterator<Item = S>,
+    ) -> Vec<BatchResult> {
+        let queries: Vec<S> = queries.into_iter().collect();
+        let total = queries.len();
+        let next = AtomicUsize::new(0);
+        let progress = Mutex::new((
+            Progress {
+                done: 0,
+                failed: 0,
+                total: Some(total),
+            },
+            self.progress,
+        ));
+
+        let results = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.concurrency.min(total))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = vec![];
+                        loop {
+                            let index = next.fetch_add(1, Ordering::SeqCst);
+                            let Some(query) = queries.get(index) else {
+                                break;
+                            };
+                            let result = self.resolver.resolve_sync(query.as_ref());
+                            let mut progress =
+                                progress.lock().unwrap_or_else(|err| err.into_inner());
+                            let (state, callback) = &mut *progress;
+                            state.done += 1;
+                            if result.is_err() {
+                                state.failed += 1;
+                            }
+                            if let Some(callback) = callback {
+                                callback(*state);
+                            }
+                            results.push((index, result));
+                        }
+                        results
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+        in_order(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{resolvers::ResolveFuture, Coordinate};
+
+    /// Resolver knowing places named by numbers and tracking concurrency
+    #[derive(Default)]
+    struct Numbers {
+        /// Queries currently running
+        in_flight: AtomicUsize,
+        /// Maximum of queries running at the same time
+        max_in_flight: AtomicUsize,
+    }
+
+    impl Numbers {
+        /// Answer a query
+        fn answer(query: &str) -> Result<Vec<Place>, CoordinateError> {
+            let number: f64 = query.parse().map_err(|_| CoordinateError::InvalidValue)?;
+            Ok(vec![Place::new(Coordinate::new(number, number), query)])
+        }
+    }
+
+    impl BlockingResolver for Numbers {
+        fn search_sync(&self, query: &str, _limit: usize) -> Result<Vec<Place>, CoordinateError> {
+            let running = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Self::answer(query)
+        }
+    }
+
+    impl Resolver for Numbers {
+        fn search<'a>(&'a self, query: &'a str, _limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+            Box::pin(async move {
+                let running = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(running, Ordering::SeqCst);
+                for _ in 0..3 {
+                    tokio::task::yield_now().await;
+                }
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Self::answer(query)
+            })
+        }
+    }
+
+    fn queries() -> Vec<String> {
+        (0..20)
+            .map(|i| {
+                if i == 7 {
+                    "x".to_string()
+                } else {
+                    i.to_string()
+                }
+            })
+            .collect()
+    }
+
+    fn check(results: &[BatchResult]) {
+        assert_eq!(results.len(), 20);
+        for (i, result) in results.iter().enumerate() {
+            if i == 7 {
+                assert!(matches!(result, Err(CoordinateError::InvalidValue)));
+            } else {
+                assert_eq!(result.as_ref().unwrap().name, i.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_sync() {
+        let resolver = Numbers::default();
+        let mut last = None;
+        let results = Batch::new(&resolver)
+            .with_concurrency(4)
+            .on_progress(|progress| last = Some(progress))
+            .run_sync(queries());
+        check(&results);
+        // How many workers overlap depends on scheduling, only the limit is certain
+        assert!(resolver.max_in_flight.load(Ordering::SeqCst) <= 4);
+        assert_eq!(
+            last,
+            Some(Progress {
+                done: 20,
+                failed: 1,
+                total: Some(20)
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let resolver = Numbers::default();
+        let mut calls = 0;
+        let results = Batch::new(&resolver)
+            .with_concurrency(3)
+            .on_progress(|_| calls += 1)
+            .run_iter(queries())
+            .await;
+        check(&results);
+        assert_eq!(resolver.max_in_flight.load(Ordering::SeqCst), 3);
+        assert_eq!(calls, 20);
+    }
+}
@@ -0,0 +1,622 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    resolvers::{BlockingResolver, Place, ResolveFuture, Resolver},
+    Coordinate, CoordinateError,
+};
+use serde::{Deserialize, Serialize};
+
+/// A cached answer of a resolver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Found places (empty if the query was unresolveable)
+    places: Vec<Place>,
+    /// Unix timestamp (seconds) after which the entry is stale
+    expires: Option<u64>,
+}
+
+impl CacheEntry {
+    /// Check if the entry is stale
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+/// A line of the file store
+#[derive(Serialize, Deserialize)]
+struct FileRecord {
+    /// Cache key
+    key: String,
+    /// Cached answer
+    #[serde(flatten)]
+    entry: CacheEntry,
+}
+
+/// Current unix timestamp in seconds
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+/// Normalize a query, so different spellings share a cache entry
+fn normalize(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Cache key of a search
+fn search_key(query: &str, limit: usize) -> String {
+    format!("search:{limit}:{}", normalize(query))
+}
+
+/// Cache key of a resolve
+fn resolve_key(query: &str) -> String {
+    format!("resolve:{}", normalize(query))
+}
+
+/// Cache key of a reverse lookup (rounded to about 10 cm)
+fn reverse_key(coordinate: &Coordinate) -> String {
+    format!("reverse:{:.6},{:.6}", coordinate.lat, coordinate.lng)
+}
+
+/// In-memory store which evicts the least recently used entry when full
+#[derive(Debug)]
+struct MemoryStore {
+    /// Maximum number of entries
+    capacity: usize,
+    /// Entries with the tick they were last used at
+    entries: HashMap<String, (CacheEntry, u64)>,
+    /// Keys by the tick they were last used at
+    usage: BTreeMap<u64, String>,
+    /// Counter increased on every access
+    tick: u64,
+}
+
+impl MemoryStore {
+    /// Create an empty store
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Get an entry and mark it as recently used
+    fn get(&mut self, key: &str) -> Option<CacheEntry> {
+        self.tick += 1;
+        let (entry, used) = self.entries.get_mut(key)?;
+        self.usage.remove(used);
+        *used = self.tick;
+        self.usage.insert(self.tick, key.to_string());
+        Some(entry.clone())
+    }
+
+    /// Insert an entry, evicting the least recently used one if full
+    fn insert(&mut self, key: String, entry: CacheEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.remove(&key) {
+            self.usage.remove(&used);
+        }
+        while self.entries.len() >= self.capacity {
+            let Some((_, oldest)) = self.usage.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        self.usage.insert(self.tick, key.clone());
+        self.entries.insert(key, (entry, self.tick));
+    }
+
+    /// Remove an entry
+    fn remove(&mut self, key: &str) {
+        if let Some((_, used)) = self.entries.remove(key) {
+            self.usage.remove(&used);
+        }
+    }
+}
+
+/// Persistent store in a JSON lines file
+///
+/// Every answer is appended as one line, so the file survives crashes.
+/// When the same key appears multiple times, the last line wins. The file is
+/// compacted when it's opened, dropping expired entries and replaced lines.
+#[derive(Debug)]
+pub struct FileStore {
+    /// All entries of the file
+    entries: HashMap<String, CacheEntry>,
+    /// File opened for appending
+    file: File,
+}
+
+impl FileStore {
+    /// Open (or create) a cache file
+    ///
+    /// Expired entries and lines which can't be parsed (e.g. a line cut off
+    /// by a crash) are skipped. If there are any, the file is rewritten with
+    /// just the valid entries.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CoordinateError> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let now = now();
+        let mut entries = HashMap::new();
+        let mut lines = 0;
+        for line in BufReader::new(&file).lines() {
+            lines += 1;
+            if let Ok(record) = serde_json::from_str::<FileRecord>(&line?) {
+                if record.entry.is_expired(now) {
+                    entries.remove(&record.key);
+                } else {
+                    entries.insert(record.key, record.entry);
+                }
+            }
+        }
+        if lines == entries.len() {
+            return Ok(Self { entries, file });
+        }
+
+        // Write the compacted file next to the old one and swap them, so a
+        // crash keeps either of them intact
+        let mut compacted = path.as_os_str().to_owned();
+        compacted.push(".tmp");
+        let mut writer = File::create(&compacted)?;
+        for (key, entry) in &entries {
+            let mut line = serde_json::to_string(&FileRecord {
+                key: key.clone(),
+                entry: entry.clone(),
+            })?;
+            line.push('\n');
+            writer.write_all(line.as_bytes())?;
+        }
+        writer.sync_all()?;
+        // Windows doesn't replace files which are still open
+        drop(writer);
+        drop(file);
+        fs::rename(&compacted, path)?;
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(Self { entries, file })
+    }
+
+    /// Number of entries in the file
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the file contains no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get an entry
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        self.entries.get(key).cloned()
+    }
+
+    /// Insert an entry and append it to the file
+    fn insert(&mut self, key: String, entry: CacheEntry) -> Result<(), CoordinateError> {
+        let record = FileRecord { key, entry };
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.entries.insert(record.key, record.entry);
+        Ok(())
+    }
+}
+
+/// Caching wrapper around any resolver
+///
+/// Answers are kept in an in-memory LRU store and, optionally, in a
+/// [`FileStore`]. Queries are normalized (whitespace and case), so
+/// `"Köln "` and `"köln"` share an entry. Unresolveable queries are cached
+/// as well, but expire after the (usually shorter) negative TTL.
+/// Other errors are never cached.
+///
+/// ```
+/// # use recoord::{Coordinate, resolvers::{cache::CachedResolver, Place, BlockingResolver}};
+/// # struct Slow;
+/// # impl BlockingResolver for Slow {
+/// #     fn search_sync(&self, _: &str, _: usize) -> Result<Vec<Place>, recoord::CoordinateError> {
+/// #         Ok(vec![Place::new(Coordinate::new(50.9, 7.5), "Home")])
+/// #     }
+/// # }
+/// # use std::time::Duration;
+/// let resolver = CachedResolver::new(Slow)
+///     .with_capacity(10_000)
+///     .with_ttl(Duration::from_secs(30 * 24 * 60 * 60));
+/// let first = resolver.resolve_sync("Home").unwrap();
+/// // This one is answered from the cache
+/// let second = resolver.resolve_sync("  home").unwrap();
+/// assert_eq!(first, second);
+/// ```
+#[derive(Debug)]
+pub struct CachedResolver<R> {
+    /// The wrapped resolver
+    inner: R,
+    /// In-memory store
+    memory: Mutex<MemoryStore>,
+    /// Optional persistent store
+    file: Option<Mutex<FileStore>>,
+    /// How long answers are valid (forever if None)
+    ttl: Option<Duration>,
+    /// How long unresolveable queries are remembered (forever if None)
+    negative_ttl: Option<Duration>,
+}
+
+impl<R> CachedResolver<R> {
+    /// Wrap a resolver with a memory store of 1024 entries
+    ///
+    /// Answers never expire, unresolveable queries are remembered for a day.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            memory: Mutex::new(MemoryStore::new(1024)),
+            file: None,
+            ttl: None,
+            negative_ttl: Some(Duration::from_secs(24 * 60 * 60)),
+        }
+    }
+
+    /// Maximum number of entries kept in memory
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.memory = Mutex::new(MemoryStore::new(capacity));
+        self
+    }
+
+    /// How long answers are valid
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// How long unresolveable queries are remembered
+    ///
+    /// Use [`Duration::ZERO`] to disable negative caching.
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = Some(ttl);
+        self
+    }
+
+    /// Additionally persist all answers in a file
+    pub fn with_file_store(mut self, store: FileStore) -> Self {
+        self.file = Some(Mutex::new(store));
+        self
+    }
+
+    /// The wrapped resolver
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Forget all entries in memory (the file store is kept)
+    pub fn clear_memory(&self) {
+        let mut memory = self.memory.lock().unwrap_or_else(|err| err.into_inner());
+        *memory = MemoryStore::new(memory.capacity);
+    }
+
+    /// Look up a key in memory and then in the file store
+    fn get(&self, key: &str) -> Option<Vec<Place>> {
+        let now = now();
+        let mut memory = self.memory.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(entry) = memory.get(key) {
+            if !entry.is_expired(now) {
+                return Some(entry.places);
+            }
+            memory.remove(key);
+        }
+        let entry = self
+            .file
+            .as_ref()?
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))?;
+        memory.insert(key.to_string(), entry.clone());
+        Some(entry.places)
+    }
+
+    /// Store an answer in all stores
+    ///
+    /// Failing to write the file store only loses persistence, so the error
+    /// is ignored rather than discarding the answer of the resolver.
+    fn store(&self, key: String, places: Vec<Place>) {
+        let ttl = if places.is_empty() {
+            self.negative_ttl
+        } else {
+            self.ttl
+        };
+        if ttl.is_some_and(|ttl| ttl.is_zero()) {
+            return;
+        }
+        let entry = CacheEntry {
+            places,
+            expires: ttl.map(|ttl| now() + ttl.as_secs()),
+        };
+        self.memory
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(key.clone(), entry.clone());
+        if let Some(file) = &self.file {
+            let _ = file
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .insert(key, entry);
+        }
+    }
+
+    /// Store the answer of a lookup which returns a single place
+    fn store_single(
+        &self,
+        key: String,
+        result: Result<Place, CoordinateError>,
+    ) -> Result<Place, CoordinateError> {
+        match result {
+            Ok(place) => {
+                self.store(key, vec![place.clone()]);
+                Ok(place)
+            }
+            Err(CoordinateError::Unresolveable) => {
+                self.store(key, vec![]);
+                Err(CoordinateError::Unresolveable)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Take the single place of a cached answer
+fn first(places: Vec<Place>) -> Result<Place, CoordinateError> {
+    places
+        .into_iter()
+        .next()
+        .ok_or(CoordinateError::Unresolveable)
+}
+
+impl<R: BlockingResolver> BlockingResolver for CachedResolver<R> {
+    fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError> {
+        let key = search_key(query, limit);
+        if let Some(places) = self.get(&key) {
+            return Ok(places);
+        }
+        let places = match self.inner.search_sync(query, limit) {
+            Err(CoordinateError::Unresolveable) => vec![],
+            result => result?,
+        };
+        self.store(key, places.clone());
+        Ok(places)
+    }
+
+    fn resolve_sync(&self, query: &str) -> Result<Place, CoordinateError> {
+        let key = resolve_key(query);
+        if let Some(places) = self.get(&key) {
+            return first(places);
+        }
+        self.store_single(key, self.inner.resolve_sync(query))
+    }
+
+    fn reverse_sync(&self, coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+        let key = reverse_key(coordinate);
+        if let Some(places) = self.get(&key) {
+            return first(places);
+        }
+        self.store_single(key, self.inner.reverse_sync(coordinate))
+    }
+}
+
+impl<R: Resolver> Resolver for CachedResolver<R> {
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+        Box::pin(async move {
+            let key = search_key(query, limit);
+            if let Some(places) = self.get(&key) {
+                return Ok(places);
+            }
+            let places = match self.inner.search(query, limit).await {
+                Err(CoordinateError::Unresolveable) => vec![],
+                result => result?,
+            };
+            self.store(key, places.clone());
+            Ok(places)
+        })
+    }
+
+    fn resolve<'a>(&'a self, query: &'a str) -> ResolveFuture<'a, Place> {
+        Box::pin(async move {
+            let key = resolve_key(query);
+            if let Some(places) = self.get(&key) {
+                return first(places);
+            }
+            self.store_single(key, self.inner.resolve(query).await)
+        })
+    }
+
+    fn reverse<'a>(&'a self, coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+        Box::pin(async move {
+            let key = reverse_key(coordinate);
+            if let Some(places) = self.get(&key) {
+                return first(places);
+            }
+            self.store_single(key, self.inner.reverse(coordinate).await)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Resolver which knows one place and counts its calls
+    #[derive(Default)]
+    struct Counting {
+        /// Number of calls
+        calls: AtomicUsize,
+    }
+
+    impl BlockingResolver for Counting {
+        fn search_sync(&self, query: &str, _limit: usize) -> Result<Vec<Place>, CoordinateError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(match query {
+                "Köln" => vec![Place::new(Coordinate::new(50.93, 6.95), "Köln")],
+                _ => vec![],
+            })
+        }
+    }
+
+    impl Resolver for Counting {
+        fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+            Box::pin(async move { self.search_sync(query, limit) })
+        }
+    }
+
+    fn calls<R>(resolver: &CachedResolver<R>) -> usize
+    where
+        R: std::ops::Deref<Target = Counting>,
+    {
+        resolver.inner().calls.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn test_normalized_hits() {
+        let resolver = CachedResolver::new(Box::new(Counting::default()));
+        assert_eq!(resolver.resolve_sync("Köln").unwrap().name, "Köln");
+        assert_eq!(resolver.resolve_sync("  KÖLN ").unwrap().name, "Köln");
+        assert_eq!(calls(&resolver), 1);
+    }
+
+    #[test]
+    fn test_negative_caching() {
+        let resolver = CachedResolver::new(Box::new(Counting::default()));
+        for _ in 0..3 {
+            assert!(matches!(
+                resolver.resolve_sync("Nowhere"),
+                Err(CoordinateError::Unresolveable)
+            ));
+        }
+        assert_eq!(calls(&resolver), 1);
+
+        let resolver =
+            CachedResolver::new(Box::new(Counting::default())).with_negative_ttl(Duration::ZERO);
+        let _ = resolver.resolve_sync("Nowhere");
+        let _ = resolver.resolve_sync("Nowhere");
+        assert_eq!(calls(&resolver), 2);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut store = MemoryStore::new(2);
+        let entry = CacheEntry {
+            places: vec![],
+            expires: None,
+        };
+        store.insert("a".to_string(), entry.clone());
+        store.insert("b".to_string(), entry.clone());
+        assert!(store.get("a").is_some());
+        store.insert("c".to_string(), entry);
+        assert!(store.get("a").is_some());
+        assert!(store.get("b").is_none());
+        assert!(store.get("c").is_some());
+    }
+
+    #[test]
+    fn test_expired_entries() {
+        let entry = CacheEntry {
+            places: vec![],
+            expires: Some(10),
+        };
+        assert!(!entry.is_expired(9));
+        assert!(entry.is_expired(10));
+    }
+
+    #[test]
+    fn test_file_store() {
+        let path = std::env::temp_dir().join(format!("recoord-cache-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let resolver = CachedResolver::new(Box::new(Counting::default()))
+                .with_file_store(FileStore::open(&path).unwrap());
+            resolver.resolve_sync("Köln").unwrap();
+            resolver.search_sync("Köln", 5).unwrap();
+        }
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.len(), 2);
+        let resolver = CachedResolver::new(Box::new(Counting::default())).with_file_store(store);
+        assert_eq!(resolver.resolve_sync("köln").unwrap().name, "Köln");
+        assert_eq!(calls(&resolver), 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_compaction() {
+        let path =
+            std::env::temp_dir().join(format!("recoord-compact-{}.jsonl", std::process::id()));
+        let line = |key: &str, expires| {
+            serde_json::to_string(&FileRecord {
+                key: key.to_string(),
+                entry: CacheEntry {
+                    places: vec![Place::new(Coordinate::new(50.9, 6.9), "Köln")],
+                    expires,
+                },
+            })
+            .unwrap()
+        };
+        let lines = [
+            line("a", None),
+            line("b", Some(1)),
+            line("a", None),
+            r#"{"key":"c","pla"#.to_string(),
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        drop(store);
+        assert_eq!(FileStore::open(&path).unwrap().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_write_error() {
+        let path =
+            std::env::temp_dir().join(format!("recoord-readonly-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "").unwrap();
+        // A file opened read only fails every write
+        let store = FileStore {
+            entries: HashMap::new(),
+            file: File::open(&path).unwrap(),
+        };
+        let resolver = CachedResolver::new(Box::new(Counting::default())).with_file_store(store);
+        assert_eq!(resolver.resolve_sync("Köln").unwrap().name, "Köln");
+        assert_eq!(resolver.search_sync("Köln", 5).unwrap().len(), 1);
+        assert_eq!(resolver.resolve_sync("köln").unwrap().name, "Köln");
+        assert_eq!(calls(&resolver), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_hits() {
+        let resolver = CachedResolver::new(Box::new(Counting::default()));
+        resolver.search("Köln", 3).await.unwrap();
+        resolver.search("köln", 3).await.unwrap();
+        resolver.search("köln", 4).await.unwrap();
+        assert_eq!(calls(&resolver), 2);
+    }
+}
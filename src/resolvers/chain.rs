@@ -0,0 +1,466 @@
+use crate::{
+    resolvers::{BlockingResolver, Place, ResolveFuture, Resolver},
+    Coordinate, CoordinateError,
+};
+
+/// Places closer than this (in meters) are considered the same when merging
+const MERGE_DISTANCE: f64 = 100.;
+
+/// What a chain does when a resolver fails with a hard error
+///
+/// `Unresolveable` (or no results) and `Unsupported` aren't hard errors,
+/// they always lead to the next resolver being tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Return the error immediately
+    #[default]
+    Stop,
+    /// Try the next resolver and only return the first error if no resolver answered
+    Skip,
+}
+
+/// The answer of a chain together with the resolver which gave it
+#[derive(Debug)]
+pub struct Answer<T> {
+    /// Name of the resolver which answered
+    pub backend: String,
+    /// The answer itself
+    pub value: T,
+    /// Hard errors of resolvers tried before (only with [`ErrorPolicy::Skip`])
+    pub skipped: Vec<(String, CoordinateError)>,
+}
+
+/// Outcome of asking a single resolver
+enum Step<T> {
+    /// The resolver answered
+    Answered(T),
+    /// The resolver didn't know an answer
+    Next,
+    /// The resolver failed
+    Failed(CoordinateError),
+}
+
+impl<T> Step<T> {
+    /// Classify the result of a resolver
+    fn from_result(result: Result<T, CoordinateError>, is_empty: impl Fn(&T) -> bool) -> Self {
+        match result {
+            Ok(value) if is_empty(&value) => Step::Next,
+            Ok(value) => Step::Answered(value),
+            Err(CoordinateError::Unresolveable | CoordinateError::Unsupported) => Step::Next,
+            Err(err) => Step::Failed(err),
+        }
+    }
+}
+
+/// Bookkeeping while walking through a chain
+struct Walk {
+    /// Hard errors of skipped resolvers
+    skipped: Vec<(String, CoordinateError)>,
+    /// Did every resolver so far answer with `Unsupported`
+    all_unsupported: bool,
+}
+
+impl Walk {
+    /// Start a new walk
+    fn new() -> Self {
+        Self {
+            skipped: vec![],
+            all_unsupported: true,
+        }
+    }
+
+    /// Handle the outcome of one resolver, returning the final result if the walk ends
+    fn step<T>(
+        &mut self,
+        policy: ErrorPolicy,
+        name: &str,
+        result: Result<T, CoordinateError>,
+        is_empty: impl Fn(&T) -> bool,
+    ) -> Option<Result<Answer<T>, CoordinateError>> {
+        if !matches!(result, Err(CoordinateError::Unsupported)) {
+            self.all_unsupported = false;
+        }
+        match Step::from_result(result, is_empty) {
+            Step::Answered(value) => Some(Ok(Answer {
+                backend: name.to_string(),
+                value,
+                skipped: std::mem::take(&mut self.skipped),
+            })),
+            Step::Next => None,
+            Step::Failed(err) if policy == ErrorPolicy::Stop => Some(Err(err)),
+            Step::Failed(err) => {
+                self.skipped.push((name.to_string(), err));
+                None
+            }
+        }
+    }
+
+    /// The result if no resolver answered
+    fn finish<T>(self) -> Result<Answer<T>, CoordinateError> {
+        match self.skipped.into_iter().next() {
+            Some((_, err)) => Err(err),
+            None if self.all_unsupported => Err(CoordinateError::Unsupported),
+            None => Err(CoordinateError::Unresolveable),
+        }
+    }
+}
+
+/// Add places to a merged list, skipping ones close to a place already in it
+fn merge_into(merged: &mut Vec<(String, Place)>, backend: &str, places: Vec<Place>, limit: usize) {
+    for place in places {
+        if merged.len() >= limit {
+            return;
+        }
+        if !merged
+            .iter()
+            .any(|(_, kept)| kept.coordinate.distance(&place.coordinate) < MERGE_DISTANCE)
+        {
+            merged.push((backend.to_string(), place));
+        }
+    }
+}
+
+/// Tries resolvers in order until one of them answers
+///
+/// To chain different kinds of resolvers, box them:
+///
+/// ```
+/// # use recoord::{Coordinate, CoordinateError};
+/// # use recoord::resolvers::{chain::ResolverChain, BlockingResolver, Place};
+/// # struct Home;
+/// # impl BlockingResolver for Home {
+/// #     fn search_sync(&self, q: &str, _: usize) -> Result<Vec<Place>, CoordinateError> {
+/// #         Ok(if q == "home" { vec![Place::new(Coordinate::new(50.9, 7.5), "Home")] } else { vec![] })
+/// #     }
+/// # }
+/// # struct Nothing;
+/// # impl BlockingResolver for Nothing {
+/// #     fn search_sync(&self, _: &str, _: usize) -> Result<Vec<Place>, CoordinateError> {
+/// #         Err(CoordinateError::Unresolveable)
+/// #     }
+/// # }
+/// let chain = ResolverChain::<Box<dyn BlockingResolver + Send + Sync>>::new()
+///     .then("online", Box::new(Nothing))
+///     .then("offline", Box::new(Home));
+/// let answer = chain.resolve_traced_sync("home").unwrap();
+/// assert_eq!(answer.backend, "offline");
+/// assert_eq!(answer.value.name, "Home");
+/// ```
+#[derive(Debug)]
+pub struct ResolverChain<R> {
+    /// Resolvers with their names, in the order they are tried
+    resolvers: Vec<(String, R)>,
+    /// How hard errors are handled
+    policy: ErrorPolicy,
+}
+
+impl<R> Default for ResolverChain<R> {
+    fn default() -> Self {
+        Self {
+            resolvers: vec![],
+            policy: ErrorPolicy::default(),
+        }
+    }
+}
+
+impl<R> ResolverChain<R> {
+    /// Create an empty chain
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a resolver to the chain
+    pub fn then(mut self, name: impl Into<String>, resolver: R) -> Self {
+        self.resolvers.push((name.into(), resolver));
+        self
+    }
+
+    /// Change how hard errors are handled
+    pub fn with_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Names of all resolvers in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.resolvers.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+impl<R: Resolver> ResolverChain<R> {
+    /// Walk the chain with an async operation
+    async fn walk<'a, T>(
+        &'a self,
+        operation: impl Fn(&'a R) -> ResolveFuture<'a, T>,
+        is_empty: impl Fn(&T) -> bool,
+    ) -> Result<Answer<T>, CoordinateError> {
+        let mut walk = Walk::new();
+        for (name, resolver) in &self.resolvers {
+            let result = operation(resolver).await;
+            if let Some(result) = walk.step(self.policy, name, result, &is_empty) {
+                return result;
+            }
+        }
+        walk.finish()
+    }
+
+    /// Search and report which resolver answered
+    pub async fn search_traced<'a>(
+        &'a self,
+        query: &'a str,
+        limit: usize,
+    ) -> Result<Answer<Vec<Place>>, CoordinateError> {
+        self.walk(|resolver| resolver.search(query, limit), Vec::is_empty)
+            .await
+    }
+
+    /// Resolve and report which resolver answered
+    pub async fn resolve_traced<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> Result<Answer<Place>, CoordinateError> {
+        self.walk(|resolver| resolver.resolve(query), |_| false)
+            .await
+    }
+
+    /// Reverse resolve and report which resolver answered
+    pub async fn reverse_traced<'a>(
+        &'a self,
+        coordinate: &'a Coordinate,
+    ) -> Result<Answer<Place>, CoordinateError> {
+        self.walk(|resolver| resolver.reverse(coordinate), |_| false)
+            .await
+    }
+
+    /// Search all resolvers in order until `limit` distinct places were found
+    ///
+    /// Places closer than 100 m to an already found place are dropped.
+    /// Every place is returned with the name of the resolver which found it.
+    pub async fn search_merged(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, Place)>, CoordinateError> {
+        let mut merged = vec![];
+        for (name, resolver) in &self.resolvers {
+            if merged.len() >= limit {
+                break;
+            }
+            match Step::from_result(resolver.search(query, limit).await, Vec::is_empty) {
+                Step::Answered(places) => merge_into(&mut merged, name, places, limit),
+                Step::Next => {}
+                Step::Failed(err) if self.policy == ErrorPolicy::Stop => return Err(err),
+                Step::Failed(_) => {}
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl<R: BlockingResolver> ResolverChain<R> {
+    /// Walk the chain with a blocking operation
+    fn walk_sync<T>(
+        &self,
+        operation: impl Fn(&R) -> Result<T, CoordinateError>,
+        is_empty: impl Fn(&T) -> bool,
+    ) -> Result<Answer<T>, CoordinateError> {
+        let mut walk = Walk::new();
+        for (name, resolver) in &self.resolvers {
+            if let Some(result) = walk.step(self.policy, name, operation(resolver), &is_empty) {
+                return result;
+            }
+        }
+        walk.finish()
+    }
+
+    /// Search and report which resolver answered, blocking
+    pub fn search_traced_sync(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Answer<Vec<Place>>, CoordinateError> {
+        self.walk_sync(|resolver| resolver.search_sync(query, limit), Vec::is_empty)
+    }
+
+    /// Resolve and report which resolver answered, blocking
+    pub fn resolve_traced_sync(&self, query: &str) -> Result<Answer<Place>, CoordinateError> {
+        self.walk_sync(|resolver| resolver.resolve_sync(query), |_| false)
+    }
+
+    /// Reverse resolve and report which resolver answered, blocking
+    pub fn reverse_traced_sync(
+        &self,
+        coordinate: &Coordinate,
+    ) -> Result<Answer<Place>, CoordinateError> {
+        self.walk_sync(|resolver| resolver.reverse_sync(coordinate), |_| false)
+    }
+
+    /// Search all resolvers in order until `limit` distinct places were found, blocking
+    ///
+    /// Places closer than 100 m to an already found place are dropped.
+    /// Every place is returned with the name of the resolver which found it.
+    pub fn search_merged_sync(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, Place)>, CoordinateError> {
+        let mut merged = vec![];
+        for (name, resolver) in &self.resolvers {
+            if merged.len() >= limit {
+                break;
+            }
+            match Step::from_result(resolver.search_sync(query, limit), Vec::is_empty) {
+                Step::Answered(places) => merge_into(&mut merged, name, places, limit),
+                Step::Next => {}
+                Step::Failed(err) if self.policy == ErrorPolicy::Stop => return Err(err),
+                Step::Failed(_) => {}
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl<R: Resolver> Resolver for ResolverChain<R> {
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+        Box::pin(async move {
+            match self.search_traced(query, limit).await {
+                Ok(answer) => Ok(answer.value),
+                Err(CoordinateError::Unresolveable) => Ok(vec![]),
+                Err(err) => Err(err),
+            }
+        })
+    }
+
+    fn resolve<'a>(&'a self, query: &'a str) -> ResolveFuture<'a, Place> {
+        Box::pin(async move { Ok(self.resolve_traced(query).await?.value) })
+    }
+
+    fn reverse<'a>(&'a self, coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+        Box::pin(async move { Ok(self.reverse_traced(coordinate).await?.value) })
+    }
+}
+
+impl<R: BlockingResolver> BlockingResolver for ResolverChain<R> {
+    fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError> {
+        match self.search_traced_sync(query, limit) {
+            Ok(answer) => Ok(answer.value),
+            Err(CoordinateError::Unresolveable) => Ok(vec![]),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn resolve_sync(&self, query: &str) -> Result<Place, CoordinateError> {
+        Ok(self.resolve_traced_sync(query)?.value)
+    }
+
+    fn reverse_sync(&self, coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+        Ok(self.reverse_traced_sync(coordinate)?.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolver with a fixed behavior
+    enum Fixed {
+        /// Knows exactly one place
+        Knows(&'static str, Coordinate),
+        /// Fails with a hard error
+        Broken,
+    }
+
+    impl BlockingResolver for Fixed {
+        fn search_sync(&self, query: &str, _limit: usize) -> Result<Vec<Place>, CoordinateError> {
+            match self {
+                Fixed::Knows(name, coordinate) if *name == query => {
+                    Ok(vec![Place::new(coordinate.clone(), *name)])
+                }
+                Fixed::Knows(..) => Ok(vec![]),
+                Fixed::Broken => Err(CoordinateError::InvalidValue),
+            }
+        }
+    }
+
+    impl Resolver for Fixed {
+        fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+            Box::pin(async move { self.search_sync(query, limit) })
+        }
+    }
+
+    fn chain(policy: ErrorPolicy) -> ResolverChain<Fixed> {
+        ResolverChain::new()
+            .then("a", Fixed::Knows("Köln", Coordinate::new(50.93, 6.95)))
+            .then("broken", Fixed::Broken)
+            .then("b", Fixed::Knows("Bonn", Coordinate::new(50.73, 7.09)))
+            .with_policy(policy)
+    }
+
+    #[test]
+    fn test_first_answer_wins() {
+        let answer = chain(ErrorPolicy::Stop)
+            .resolve_traced_sync("Köln")
+            .unwrap();
+        assert_eq!(answer.backend, "a");
+        assert!(answer.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_stop_on_hard_error() {
+        assert!(matches!(
+            chain(ErrorPolicy::Stop).resolve_traced_sync("Bonn"),
+            Err(CoordinateError::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn test_skip_hard_error() {
+        let answer = chain(ErrorPolicy::Skip)
+            .resolve_traced_sync("Bonn")
+            .unwrap();
+        assert_eq!(answer.backend, "b");
+        assert_eq!(answer.skipped.len(), 1);
+        assert_eq!(answer.skipped[0].0, "broken");
+        assert!(matches!(
+            chain(ErrorPolicy::Skip).resolve_traced_sync("Paris"),
+            Err(CoordinateError::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn test_nothing_found() {
+        let chain = ResolverChain::new()
+            .then("a", Fixed::Knows("Köln", Coordinate::new(50.93, 6.95)))
+            .then("b", Fixed::Knows("Bonn", Coordinate::new(50.73, 7.09)));
+        assert!(matches!(
+            chain.resolve_sync("Paris"),
+            Err(CoordinateError::Unresolveable)
+        ));
+        assert!(chain.search_sync("Paris", 3).unwrap().is_empty());
+        assert!(matches!(
+            chain.reverse_sync(&Coordinate::new(0., 0.)),
+            Err(CoordinateError::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_search_merged() {
+        let chain = ResolverChain::new()
+            .then("a", Fixed::Knows("Köln", Coordinate::new(50.93, 6.95)))
+            .then("b", Fixed::Knows("Köln", Coordinate::new(50.9301, 6.9501)))
+            .then("c", Fixed::Knows("Köln", Coordinate::new(50.94, 6.96)));
+        let merged = chain.search_merged_sync("Köln", 5).unwrap();
+        let backends: Vec<_> = merged.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(backends, ["a", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_async_chain() {
+        let answer = chain(ErrorPolicy::Skip)
+            .search_traced("Bonn", 1)
+            .await
+            .unwrap();
+        assert_eq!(answer.backend, "b");
+        assert_eq!(answer.value[0].name, "Bonn");
+    }
+}
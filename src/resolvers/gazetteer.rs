@@ -0,0 +1,420 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{
+    resolvers::{BlockingResolver, Place, ResolveFuture, Resolver},
+    Coordinate, CoordinateError,
+};
+
+/// Length of one degree of latitude in meters
+const METERS_PER_DEGREE: f64 = crate::EARTH_RADIUS * std::f64::consts::PI / 180.;
+
+/// A single place of a GeoNames dump
+#[derive(Debug, Clone, PartialEq)]
+pub struct GazetteerEntry {
+    /// GeoNames id
+    pub id: u64,
+    /// Name of the place (UTF-8)
+    pub name: String,
+    /// Name of the place in plain ASCII
+    pub ascii_name: String,
+    /// Other names of the place (e.g. in other languages)
+    pub alternate_names: Vec<String>,
+    /// Location of the place
+    pub coordinate: Coordinate,
+    /// GeoNames feature class (e.g. 'P' for populated places)
+    pub feature_class: char,
+    /// GeoNames feature code (e.g. "PPLC" for capitals)
+    pub feature_code: String,
+    /// ISO 3166-1 alpha-2 country code in uppercase
+    pub country_code: String,
+    /// Number of inhabitants (0 if unknown)
+    pub population: u64,
+}
+
+impl GazetteerEntry {
+    /// Parse a line of a GeoNames dump (e.g. `allCountries.txt` or `cities500.txt`)
+    ///
+    /// `line_number` is only used for the error.
+    fn parse(line: &str, line_number: usize) -> Result<Self, CoordinateError> {
+        let malformed = || CoordinateError::MalformedRecord(line_number);
+        let columns: Vec<&str> = line.split('\t').collect();
+        if columns.len() < 15 {
+            return Err(malformed());
+        }
+        Ok(Self {
+            id: columns[0].parse().map_err(|_| malformed())?,
+            name: columns[1].to_string(),
+            ascii_name: columns[2].to_string(),
+            alternate_names: columns[3]
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+            coordinate: Coordinate::try_new(
+                columns[4].parse().map_err(|_| malformed())?,
+                columns[5].parse().map_err(|_| malformed())?,
+            )
+            .map_err(|_| malformed())?,
+            feature_class: columns[6].chars().next().unwrap_or(' '),
+            feature_code: columns[7].to_string(),
+            country_code: columns[8].to_string(),
+            population: if columns[14].is_empty() {
+                0
+            } else {
+                columns[14].parse().map_err(|_| malformed())?
+            },
+        })
+    }
+}
+
+impl From<&GazetteerEntry> for Place {
+    fn from(entry: &GazetteerEntry) -> Self {
+        Place {
+            coordinate: entry.coordinate.clone(),
+            name: entry.name.clone(),
+            category: Some(entry.feature_code.clone()),
+            source: Some("geonames".to_string()),
+            confidence: None,
+            country_code: Some(entry.country_code.to_ascii_lowercase()),
+            bounding_box: None,
+        }
+    }
+}
+
+/// Normalize a name for lookups
+fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Grid cell (one degree in each direction) containing a coordinate
+fn cell(coordinate: &Coordinate) -> (i32, i32) {
+    let coordinate = coordinate.normalize();
+    (
+        coordinate.lat.floor().clamp(-90., 89.) as i32,
+        coordinate.lng.floor() as i32,
+    )
+}
+
+/// In-memory index of a GeoNames dump for offline resolving
+///
+/// Forward lookups match the name, ASCII name and alternate names
+/// (case insensitive) and rank the results by population.
+/// Reverse lookups return the nearest place.
+///
+/// ```
+/// # use recoord::{Coordinate, resolvers::{gazetteer::Gazetteer, BlockingResolver}};
+/// let dump = "2761369\tVienna\tVienna\tWien,Vienne\t48.20849\t16.37208\tP\tPPLC\tAT\t\t09\t900\t\t\t1691468\t\t193\tEurope/Vienna\t2024-01-01\n";
+/// let gazetteer = Gazetteer::from_reader(dump.as_bytes()).unwrap();
+/// let place = gazetteer.resolve_sync("wien").unwrap();
+/// assert_eq!(place.name, "Vienna");
+/// let place = gazetteer.reverse_sync(&Coordinate::new(48.2, 16.4)).unwrap();
+/// assert_eq!(place.name, "Vienna");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Gazetteer {
+    /// All places
+    entries: Vec<GazetteerEntry>,
+    /// Normalized names to indices of entries, sorted by population
+    names: HashMap<String, Vec<usize>>,
+    /// One degree grid cells to indices of entries
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    /// Only answer with places in these countries (uppercase codes)
+    countries: Vec<String>,
+}
+
+impl Gazetteer {
+    /// Build the index from entries
+    pub fn from_entries(entries: impl IntoIterator<Item = GazetteerEntry>) -> Self {
+        let mut gazetteer = Self {
+            entries: entries.into_iter().collect(),
+            ..Self::default()
+        };
+        for (index, entry) in gazetteer.entries.iter().enumerate() {
+            let mut names: Vec<String> = [&entry.name, &entry.ascii_name]
+                .into_iter()
+                .chain(&entry.alternate_names)
+                .map(|name| normalize(name))
+                .collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                gazetteer.names.entry(name).or_default().push(index);
+            }
+            gazetteer
+                .cells
+                .entry(cell(&entry.coordinate))
+                .or_default()
+                .push(index);
+        }
+        let entries = &gazetteer.entries;
+        for indices in gazetteer.names.values_mut() {
+            indices.sort_by_key(|&index| std::cmp::Reverse(entries[index].population));
+        }
+        gazetteer
+    }
+
+    /// Load a GeoNames dump (tab separated, one place per line)
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, CoordinateError> {
+        Self::from_reader_filtered(reader, |_| true)
+    }
+
+    /// Load only the places of a GeoNames dump for which `filter` returns true
+    ///
+    /// This keeps the index small when loading `allCountries.txt`, e.g. by
+    /// only keeping populated places (`entry.feature_class == 'P'`).
+    pub fn from_reader_filtered(
+        reader: impl BufRead,
+        mut filter: impl FnMut(&GazetteerEntry) -> bool,
+    ) -> Result<Self, CoordinateError> {
+        let mut entries = vec![];
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = GazetteerEntry::parse(&line, index + 1)?;
+            if filter(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Load a GeoNames dump from a file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CoordinateError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Only answer with places in these countries (ISO 3166-1 alpha-2 codes)
+    pub fn with_countries<S: AsRef<str>>(mut self, countries: impl IntoIterator<Item = S>) -> Self {
+        self.countries = countries
+            .into_iter()
+            .map(|country| country.as_ref().to_ascii_uppercase())
+            .collect();
+        self
+    }
+
+    /// Number of places in the index
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the index contains no places
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Check if an entry passes the country filter
+    fn in_countries(&self, entry: &GazetteerEntry) -> bool {
+        self.countries.is_empty() || self.countries.contains(&entry.country_code)
+    }
+
+    /// Find all places with this name, most populated first
+    ///
+    /// If `country` is set, only places in that country are returned
+    /// (in addition to the filter set by [`Self::with_countries`]).
+    pub fn lookup(&self, name: &str, country: Option<&str>) -> Vec<&GazetteerEntry> {
+        self.names
+            .get(&normalize(name))
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.entries[index])
+            .filter(|entry| self.in_countries(entry))
+            .filter(|entry| {
+                country.is_none_or(|country| entry.country_code.eq_ignore_ascii_case(country))
+            })
+            .collect()
+    }
+
+    /// Find the place closest to a coordinate
+    pub fn nearest(&self, coordinate: &Coordinate) -> Option<&GazetteerEntry> {
+        if !self.entries.iter().any(|entry| self.in_countries(entry)) {
+            return None;
+        }
+        let (lat_cell, lng_cell) = cell(coordinate);
+        let mut best: Option<(f64, &GazetteerEntry)> = None;
+        // Cells searched on either side in the rows of earlier rings
+        let mut width = -1;
+        // Rows up to 180 cells away, with all longitudes, cover the globe
+        for ring in 0..=180i32 {
+            // Everything not searched yet is at least this far away
+            let ring_distance = (ring as f64 - 1.).max(0.) * METERS_PER_DEGREE;
+            if best.is_some_and(|(distance, _)| distance <= ring_distance) {
+                break;
+            }
+            // Meridians converge towards the poles, so the rows are searched
+            // over as many longitudes as needed to get this far away too
+            let max_lat = (coordinate.lat.abs() + ring as f64 + 1.).min(90.);
+            let ratio = (ring as f64 / 2.).to_radians().sin() / max_lat.to_radians().cos();
+            let ring_width = if ratio < 1. {
+                ((2. * ratio.asin().to_degrees()).ceil() as i32).min(180)
+            } else {
+                180
+            };
+            for d_lat in -ring..=ring {
+                let lat = lat_cell + d_lat;
+                if !(-90..90).contains(&lat) {
+                    continue;
+                }
+                // New rows are searched completely, earlier ones only further out
+                let known = if d_lat.abs() == ring { -1 } else { width };
+                let columns = (-ring_width..-known).chain((known + 1).max(1)..=ring_width.min(179));
+                for d_lng in columns {
+                    let lng = (lng_cell + d_lng + 180).rem_euclid(360) - 180;
+                    self.closest_in_cell(coordinate, (lat, lng), &mut best);
+                }
+            }
+            width = ring_width;
+        }
+        best.map(|(_, entry)| entry)
+    }
+
+    /// Replace the closest entry found so far by a closer one in a cell
+    fn closest_in_cell<'a>(
+        &'a self,
+        coordinate: &Coordinate,
+        cell: (i32, i32),
+        best: &mut Option<(f64, &'a GazetteerEntry)>,
+    ) {
+        for &index in self.cells.get(&cell).into_iter().flatten() {
+            let entry = &self.entries[index];
+            if !self.in_countries(entry) {
+                continue;
+            }
+            let distance = coordinate.distance(&entry.coordinate);
+            if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+                *best = Some((distance, entry));
+            }
+        }
+    }
+}
+
+impl BlockingResolver for Gazetteer {
+    fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError> {
+        Ok(self
+            .lookup(query, None)
+            .into_iter()
+            .take(limit)
+            .map(Place::from)
+            .collect())
+    }
+
+    fn reverse_sync(&self, coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+        self.nearest(coordinate)
+            .map(Place::from)
+            .ok_or(CoordinateError::Unresolveable)
+    }
+}
+
+impl Resolver for Gazetteer {
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+        Box::pin(async move { self.search_sync(query, limit) })
+    }
+
+    fn reverse<'a>(&'a self, coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+        Box::pin(async move { self.reverse_sync(coordinate) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CITIES: &str = "\
+2950159\tBerlin\tBerlin\tBerlijn,Berlín,Berline\t52.52437\t13.41053\tP\tPPLC\tDE\t\t16\t00\t11000\t11000000\t3426354\t74\t43\tEurope/Berlin\t2022-03-09
+4930956\tBerlin\tBerlin\t\t42.38148\t-71.63701\tP\tPPL\tUS\t\tMA\t027\t\t\t2422\t\t196\tAmerica/New_York\t2017-05-23
+2886242\tKöln\tKoeln\tCologne,Colonia\t50.93333\t6.95\tP\tPPLA2\tDE\t\t07\t053\t05315\t05315000\t963395\t\t56\tEurope/Berlin\t2019-09-05
+2193733\tAuckland\tAuckland\t\t-36.84853\t174.76349\tP\tPPLA\tNZ\t\tE7\t\t\t\t417910\t\t26\tPacific/Auckland\t2011-08-01
+4032243\tNuku'alofa\tNuku'alofa\t\t-21.13938\t-175.2018\tP\tPPLC\tTO\t\t04\t\t\t\t22400\t\t5\tPacific/Tongatapu\t2012-01-18
+";
+
+    fn gazetteer() -> Gazetteer {
+        Gazetteer::from_reader(CITIES.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_lookup_ranks_by_population() {
+        let gazetteer = gazetteer();
+        assert_eq!(gazetteer.len(), 5);
+        let berlins = gazetteer.lookup("berlin", None);
+        assert_eq!(berlins.len(), 2);
+        assert_eq!(berlins[0].country_code, "DE");
+        assert_eq!(berlins[1].country_code, "US");
+        assert_eq!(gazetteer.lookup("BERLIN", Some("us"))[0].id, 4930956);
+    }
+
+    #[test]
+    fn test_lookup_alternate_names() {
+        let gazetteer = gazetteer();
+        assert_eq!(gazetteer.lookup("Cologne", None)[0].name, "Köln");
+        assert_eq!(gazetteer.lookup("koeln", None)[0].name, "Köln");
+        assert!(gazetteer.lookup("Bonn", None).is_empty());
+    }
+
+    #[test]
+    fn test_country_filter() {
+        let gazetteer = gazetteer().with_countries(["us"]);
+        let place = gazetteer.resolve_sync("Berlin").unwrap();
+        assert_eq!(place.country_code.as_deref(), Some("us"));
+        assert!(matches!(
+            gazetteer.resolve_sync("Köln"),
+            Err(CoordinateError::Unresolveable)
+        ));
+    }
+
+    #[test]
+    fn test_nearest() {
+        let gazetteer = gazetteer();
+        let potsdam = Coordinate::new(52.39886, 13.06566);
+        assert_eq!(gazetteer.nearest(&potsdam).unwrap().name, "Berlin");
+        let bonn = Coordinate::new(50.73438, 7.09549);
+        assert_eq!(gazetteer.nearest(&bonn).unwrap().name, "Köln");
+    }
+
+    #[test]
+    fn test_nearest_across_antimeridian() {
+        let gazetteer = gazetteer();
+        let fiji = Coordinate::new(-18.1, 179.9);
+        assert_eq!(gazetteer.nearest(&fiji).unwrap().name, "Nuku'alofa");
+    }
+
+    #[test]
+    fn test_nearest_everywhere() {
+        let gazetteer = gazetteer();
+        for lat in (-89..=89).step_by(22) {
+            for lng in (-180..180).step_by(45) {
+                let coordinate = Coordinate::new(lat as f64 + 0.5, lng as f64);
+                let closest = gazetteer.entries.iter().min_by(|a, b| {
+                    let distance = |entry: &GazetteerEntry| coordinate.distance(&entry.coordinate);
+                    distance(a).total_cmp(&distance(b))
+                });
+                assert_eq!(gazetteer.nearest(&coordinate), closest, "{coordinate}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_nearest_without_places() {
+        let empty = Gazetteer::from_entries(vec![]);
+        assert!(empty.nearest(&Coordinate::new(52.5, 13.4)).is_none());
+        let filtered = gazetteer().with_countries(["FR"]);
+        assert!(filtered.nearest(&Coordinate::new(89.9, 13.4)).is_none());
+    }
+
+    #[test]
+    fn test_malformed_line() {
+        let dump = format!("{CITIES}1\tBroken\tBroken\t\tnorth\teast\tP\tPPL\tDE\n");
+        assert!(matches!(
+            Gazetteer::from_reader(dump.as_bytes()),
+            Err(CoordinateError::MalformedRecord(6))
+        ));
+    }
+}
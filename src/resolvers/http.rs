@@ -0,0 +1,98 @@
+use std::sync::OnceLock;
+
+use crate::{
+    resolvers::rate_limit::{RateLimiter, RetryPolicy},
+    CoordinateError,
+};
+use reqwest::header::{HeaderName, USER_AGENT};
+use serde::de::DeserializeOwned;
+
+/// Default user agent for all web API resolvers
+const DEFAULT_USER_AGENT: &str = concat!("recoord/", env!("CARGO_PKG_VERSION"));
+
+/// Shared plumbing of resolvers talking to a JSON web API
+#[derive(Debug, Clone)]
+pub(crate) struct HttpBackend {
+    /// Base url of the API (without trailing slash)
+    base_url: String,
+    /// User agent sent with every request
+    pub user_agent: String,
+    /// Limiter for all requests
+    pub limiter: RateLimiter,
+    /// Retry behavior on failed requests
+    pub retry: RetryPolicy,
+    /// Client for async requests
+    client: reqwest::Client,
+    /// Client for blocking requests
+    ///
+    /// It's created lazily, since it can't be created inside an async runtime.
+    blocking_client: OnceLock<reqwest::blocking::Client>,
+}
+
+impl HttpBackend {
+    /// Create a backend for an API
+    pub fn new(base_url: &str, limiter: RateLimiter) -> Self {
+        let mut backend = Self {
+            base_url: String::new(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            limiter,
+            retry: RetryPolicy::default(),
+            client: reqwest::Client::new(),
+            blocking_client: OnceLock::new(),
+        };
+        backend.set_base_url(base_url);
+        backend
+    }
+
+    /// Change the base url of the API
+    pub fn set_base_url(&mut self, base_url: &str) {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+    }
+
+    /// Send a blocking GET request to an endpoint and parse the JSON response
+    pub fn get_sync<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+        headers: &[(HeaderName, String)],
+    ) -> Result<T, CoordinateError> {
+        let client = self
+            .blocking_client
+            .get_or_init(reqwest::blocking::Client::new);
+        Ok(self
+            .retry
+            .send_blocking(&self.limiter, || {
+                headers.iter().fold(
+                    client
+                        .get(format!("{}/{endpoint}", self.base_url))
+                        .header(USER_AGENT, &self.user_agent)
+                        .query(query),
+                    |request, (name, value)| request.header(name, value),
+                )
+            })?
+            .json()?)
+    }
+
+    /// Send a GET request to an endpoint and parse the JSON response
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, String)],
+        headers: &[(HeaderName, String)],
+    ) -> Result<T, CoordinateError> {
+        Ok(self
+            .retry
+            .send(&self.limiter, || {
+                headers.iter().fold(
+                    self.client
+                        .get(format!("{}/{endpoint}", self.base_url))
+                        .header(USER_AGENT, &self.user_agent)
+                        .query(query),
+                    |request, (name, value)| request.header(name, value),
+                )
+            })
+            .await?
+            .json()
+            .await?)
+    }
+}
@@ -1,3 +1,358 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use crate::{formats::Format, BoundingBox, Coordinate, CoordinateError, Precision};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Resolving many queries at once
+#[cfg(feature = "resolve_batch")]
+pub mod batch;
+/// Caching wrapper around resolvers
+#[cfg(feature = "resolve_cache")]
+pub mod cache;
+/// Fallback chains of resolvers
+pub mod chain;
+/// Offline resolver backed by a GeoNames dump
+#[cfg(feature = "resolve_gazetteer")]
+pub mod gazetteer;
+/// Shared plumbing for resolvers backed by web APIs
+#[cfg(feature = "resolve_http")]
+mod http;
 /// Resolver for the Nominatim Open Street Maps API
 #[cfg(feature = "resolve_osm")]
 pub mod nominatim;
+/// Resolver for self hosted Pelias geocoders
+#[cfg(feature = "resolve_pelias")]
+pub mod pelias;
+/// Resolver for the Photon geocoder by Komoot
+#[cfg(feature = "resolve_photon")]
+pub mod photon;
+/// Rate limiting and retries for resolvers backed by web APIs
+#[cfg(feature = "resolve_http")]
+pub mod rate_limit;
+
+/// Minimal HTTP server for testing resolvers without network access
+#[cfg(all(test, feature = "resolve_http"))]
+mod test_server;
+
+/// The future returned by [`Resolver`] methods
+pub type ResolveFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, CoordinateError>> + Send + 'a>>;
+
+/// A place found by a resolver
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    /// Location of the place
+    pub coordinate: Coordinate,
+    /// Human readable name of the place
+    pub name: String,
+    /// Kind of the place as reported by the backend (e.g. "city" or "street")
+    pub category: Option<String>,
+    /// Dataset the place originates from (e.g. "openstreetmap")
+    pub source: Option<String>,
+    /// How well the place matches the query (0 - 1)
+    pub confidence: Option<f64>,
+    /// ISO 3166-1 alpha-2 country code in lowercase
+    pub country_code: Option<String>,
+    /// Area covered by the place (if reported by the backend)
+    pub bounding_box: Option<BoundingBox>,
+}
+
+impl Place {
+    /// Create a place with only a coordinate and a name
+    pub fn new(coordinate: Coordinate, name: impl Into<String>) -> Self {
+        Self {
+            coordinate,
+            name: name.into(),
+            category: None,
+            source: None,
+            confidence: None,
+            country_code: None,
+            bounding_box: None,
+        }
+    }
+}
+
+impl From<Place> for Coordinate {
+    fn from(place: Place) -> Self {
+        place.coordinate
+    }
+}
+
+/// Where the coordinate of a [`Resolution`] came from
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolutionSource {
+    /// The input was a coordinate in the given format
+    Parsed(Format),
+    /// The input was looked up by a resolver
+    Geocoded(Place),
+}
+
+/// A coordinate from user input, either parsed or geocoded
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    /// The resolved coordinate
+    pub coordinate: Coordinate,
+    /// Where the coordinate came from
+    pub source: ResolutionSource,
+    /// How exact the coordinate is, if known
+    pub precision: Option<Precision>,
+}
+
+impl Resolution {
+    /// Resolution of a parsed coordinate string
+    #[cfg(feature = "format_any")]
+    pub(crate) fn parsed(format: Format, precision: Precision, coordinate: Coordinate) -> Self {
+        Self {
+            coordinate,
+            source: ResolutionSource::Parsed(format),
+            precision: Some(precision),
+        }
+    }
+
+    /// Resolution of a place found by a resolver
+    fn geocoded(place: Place) -> Self {
+        Self {
+            coordinate: place.coordinate.clone(),
+            source: ResolutionSource::Geocoded(place),
+            precision: None,
+        }
+    }
+
+    /// Area the coordinate stands for
+    ///
+    /// This is the area covered by a geocoded place or the area given
+    /// by the precision of a parsed coordinate.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        match &self.source {
+            ResolutionSource::Geocoded(place) => place.bounding_box,
+            ResolutionSource::Parsed(_) => self
+                .precision
+                .map(|precision| BoundingBox::from_center(&self.coordinate, precision)),
+        }
+    }
+
+    /// Whether a resolver had to be asked for the coordinate
+    pub fn is_geocoded(&self) -> bool {
+        matches!(self.source, ResolutionSource::Geocoded(_))
+    }
+}
+
+impl Coordinate {
+    /// Turn user input into a coordinate
+    ///
+    /// The input is parsed with all enabled formats first and only looked up
+    /// with the resolver when none of them matches.
+    ///
+    /// ```
+    /// # use recoord::{Coordinate, CoordinateError};
+    /// # use recoord::resolvers::{Place, ResolveFuture, Resolver};
+    /// # struct Home;
+    /// # impl Resolver for Home {
+    /// #     fn search<'a>(&'a self, _: &'a str, _: usize) -> ResolveFuture<'a, Vec<Place>> {
+    /// #         Box::pin(async { Ok(vec![Place::new(Coordinate::new(50.9, 7.5), "Home")]) })
+    /// #     }
+    /// # }
+    /// # async fn run() -> Result<(), CoordinateError> {
+    /// let resolution = Coordinate::resolve("home", &Home).await?;
+    /// assert!(resolution.is_geocoded());
+    /// assert_eq!(resolution.coordinate, Coordinate::new(50.9, 7.5));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve<R: Resolver + ?Sized>(
+        input: &str,
+        resolver: &R,
+    ) -> Result<Resolution, CoordinateError> {
+        #[cfg(feature = "format_any")]
+        if let Ok(parsed) = Coordinate::parse_detailed(input) {
+            return Ok(parsed);
+        }
+        resolver.resolve(input).await.map(Resolution::geocoded)
+    }
+
+    /// Turn user input into a coordinate, blocking the current thread
+    ///
+    /// See [`Coordinate::resolve`].
+    pub fn resolve_sync<R: BlockingResolver + ?Sized>(
+        input: &str,
+        resolver: &R,
+    ) -> Result<Resolution, CoordinateError> {
+        #[cfg(feature = "format_any")]
+        if let Ok(parsed) = Coordinate::parse_detailed(input) {
+            return Ok(parsed);
+        }
+        resolver.resolve_sync(input).map(Resolution::geocoded)
+    }
+}
+
+/// Resolver for strings to Coordinates - this should be used for more expensive (and async) resolving
+///
+/// Implementors only have to provide [`Resolver::search`].
+///
+/// ```
+/// # use recoord::{Coordinate, CoordinateError};
+/// # use recoord::resolvers::{Place, ResolveFuture, Resolver};
+/// /// A resolver which only knows one place
+/// struct Home;
+///
+/// impl Resolver for Home {
+///     fn search<'a>(&'a self, query: &'a str, _limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+///         Box::pin(async move {
+///             Ok(match query {
+///                 "home" => vec![Place::new(Coordinate::new(50.9, 7.5), "Home")],
+///                 _ => vec![],
+///             })
+///         })
+///     }
+/// }
+/// ```
+pub trait Resolver: Send + Sync {
+    /// Search for places matching the query, best match first
+    ///
+    /// An empty list means nothing was found.
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>>;
+
+    /// Resolve a query to the best matching place
+    fn resolve<'a>(&'a self, query: &'a str) -> ResolveFuture<'a, Place> {
+        Box::pin(async move {
+            self.search(query, 1)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or(CoordinateError::Unresolveable)
+        })
+    }
+
+    /// Find the place at a coordinate
+    fn reverse<'a>(&'a self, _coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+        Box::pin(async { Err(CoordinateError::Unsupported) })
+    }
+}
+
+/// Resolver for strings to Coordinates, which blocks the current thread
+///
+/// Implementors only have to provide [`BlockingResolver::search_sync`].
+pub trait BlockingResolver {
+    /// Search for places matching the query, best match first
+    ///
+    /// An empty list means nothing was found.
+    fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError>;
+
+    /// Resolve a query to the best matching place
+    fn resolve_sync(&self, query: &str) -> Result<Place, CoordinateError> {
+        self.search_sync(query, 1)?
+            .into_iter()
+            .next()
+            .ok_or(CoordinateError::Unresolveable)
+    }
+
+    /// Find the place at a coordinate
+    fn reverse_sync(&self, _coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+        Err(CoordinateError::Unsupported)
+    }
+}
+
+/// Forward all methods of a resolver trait through a pointer type
+macro_rules! forward_resolver {
+    ($($pointer:ty),*) => {$(
+        impl<R: Resolver + ?Sized> Resolver for $pointer {
+            fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+                (**self).search(query, limit)
+            }
+            fn resolve<'a>(&'a self, query: &'a str) -> ResolveFuture<'a, Place> {
+                (**self).resolve(query)
+            }
+            fn reverse<'a>(&'a self, coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+                (**self).reverse(coordinate)
+            }
+        }
+
+        impl<R: BlockingResolver + ?Sized> BlockingResolver for $pointer {
+            fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError> {
+                (**self).search_sync(query, limit)
+            }
+            fn resolve_sync(&self, query: &str) -> Result<Place, CoordinateError> {
+                (**self).resolve_sync(query)
+            }
+            fn reverse_sync(&self, coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+                (**self).reverse_sync(coordinate)
+            }
+        }
+    )*};
+}
+
+forward_resolver!(&R, Box<R>, Arc<R>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolver which knows a single place and counts its queries
+    #[derive(Default)]
+    struct Home {
+        /// Number of queries answered
+        queries: std::sync::atomic::AtomicUsize,
+    }
+
+    impl BlockingResolver for Home {
+        fn search_sync(&self, query: &str, _limit: usize) -> Result<Vec<Place>, CoordinateError> {
+            self.queries
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(match query {
+                "home" => vec![Place::new(Coordinate::new(50.9, 7.5), "Home")],
+                _ => vec![],
+            })
+        }
+    }
+
+    impl Resolver for Home {
+        fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+            Box::pin(async move { self.search_sync(query, limit) })
+        }
+    }
+
+    #[test]
+    fn test_resolve_geocoded() {
+        let home = Home::default();
+        let resolution = Coordinate::resolve_sync("home", &home).unwrap();
+        assert!(resolution.is_geocoded());
+        assert_eq!(resolution.coordinate, Coordinate::new(50.9, 7.5));
+        assert_eq!(resolution.precision, None);
+        assert_eq!(resolution.bounding_box(), None);
+        assert!(matches!(
+            Coordinate::resolve_sync("work", &home),
+            Err(CoordinateError::Unresolveable)
+        ));
+    }
+
+    #[cfg(feature = "format_dd")]
+    #[tokio::test]
+    async fn test_resolve_parsed() {
+        let home = Home::default();
+        let resolution = Coordinate::resolve("12.5,-7", &home).await.unwrap();
+        assert_eq!(resolution.source, ResolutionSource::Parsed(Format::DD));
+        assert_eq!(resolution.coordinate, Coordinate::new(12.5, -7.));
+        let precision = resolution.precision.unwrap();
+        assert!((precision.lat - 0.05).abs() < 1e-12);
+        assert!((precision.lng - 0.5).abs() < 1e-12);
+        let bbox = resolution.bounding_box().unwrap();
+        assert!((bbox.south() - 12.45).abs() < 1e-9);
+        assert!((bbox.east() + 6.5).abs() < 1e-9);
+        assert_eq!(home.queries.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[cfg(feature = "format_geohash")]
+    #[test]
+    fn test_resolve_geohash_precision() {
+        let resolution = Coordinate::resolve_sync("u1h", &Home::default()).unwrap();
+        assert_eq!(resolution.source, ResolutionSource::Parsed(Format::Geohash));
+        let precision = resolution.precision.unwrap();
+        assert!((precision.lat - 45. / 64.).abs() < 1e-9);
+        assert!((precision.lng - 45. / 64.).abs() < 1e-9);
+    }
+}
@@ -1,6 +1,25 @@
-use crate::{Coordinate, CoordinateError};
+use std::{sync::LazyLock, time::Duration};
+
+use crate::{
+    resolvers::{
+        http::HttpBackend,
+        rate_limit::{RateLimiter, RetryPolicy},
+        BlockingResolver, Place, ResolveFuture, Resolver,
+    },
+    BoundingBox, Coordinate, CoordinateError,
+};
+use reqwest::header::{HeaderName, ACCEPT_LANGUAGE};
 use serde::Deserialize;
 
+/// Base url of the public Nominatim API
+pub const PUBLIC_URL: &str = "https://nominatim.openstreetmap.org";
+
+/// Rate limiter shared by all requests to the public Nominatim API
+///
+/// The usage policy allows at most one request per second.
+pub static RATE_LIMITER: LazyLock<RateLimiter> =
+    LazyLock::new(|| RateLimiter::new(Duration::from_secs(1)));
+
 /// Location of Open Street Maps
 #[derive(Deserialize)]
 struct OSMLocation {
@@ -8,42 +27,302 @@ struct OSMLocation {
     lat: String,
     /// Longitude
     lon: String,
+    /// Full name of the location
+    #[serde(default)]
+    display_name: String,
+    /// Main OSM tag of the location (e.g. "place")
+    class: Option<String>,
+    /// Value of the main OSM tag (e.g. "city")
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    /// Relevance of the location (0 - 1)
+    importance: Option<f64>,
+    /// Address details (only sent with `addressdetails=1`)
+    address: Option<OSMAddress>,
+    /// Extent of the location as [south, north, west, east]
+    boundingbox: Option<[String; 4]>,
 }
 
-/// Resolve a location synchronously name to a coordinate
-pub fn resolve_sync(location: &str) -> Result<Coordinate, CoordinateError> {
-    let locations = reqwest::blocking::Client::new()
-        .get("https://nominatim.openstreetmap.org/search")
-        .header(reqwest::header::USER_AGENT, "tanker_price")
-        .query(&[("format", "json"), ("q", location)])
-        .send()?
-        .json::<Vec<OSMLocation>>()?;
-    if let Some(location) = locations.get(0) {
-        Ok(Coordinate {
-            lng: location.lon.parse()?,
-            lat: location.lat.parse()?,
+/// Address details of a location
+#[derive(Deserialize)]
+struct OSMAddress {
+    /// ISO 3166-1 alpha-2 country code
+    country_code: Option<String>,
+}
+
+/// Response of the reverse endpoint
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OSMReverse {
+    /// A location was found
+    Found(Box<OSMLocation>),
+    /// Nothing was found
+    Error {
+        /// Reason why nothing was found
+        #[allow(dead_code)]
+        error: String,
+    },
+}
+
+impl TryFrom<OSMLocation> for Place {
+    type Error = CoordinateError;
+    fn try_from(location: OSMLocation) -> Result<Self, Self::Error> {
+        let bounding_box = match location.boundingbox {
+            Some([south, north, west, east]) => {
+                BoundingBox::new(south.parse()?, west.parse()?, north.parse()?, east.parse()?).ok()
+            }
+            None => None,
+        };
+        Ok(Place {
+            coordinate: Coordinate::try_new(location.lat.parse()?, location.lon.parse()?)?,
+            name: location.display_name,
+            category: location.kind.or(location.class),
+            source: Some("openstreetmap".to_string()),
+            confidence: location.importance,
+            country_code: location.address.and_then(|address| address.country_code),
+            bounding_box,
         })
-    } else {
-        Err(CoordinateError::Unresolveable)
     }
 }
 
+impl OSMReverse {
+    /// Convert the response into a place
+    fn into_place(self) -> Result<Place, CoordinateError> {
+        match self {
+            OSMReverse::Found(location) => Place::try_from(*location),
+            OSMReverse::Error { .. } => Err(CoordinateError::Unresolveable),
+        }
+    }
+}
+
+/// Resolver using the Nominatim API
+///
+/// By default the public instance is used and shares [`RATE_LIMITER`]
+/// with all other resolvers talking to it.
+///
+/// ```no_run
+/// # use recoord::resolvers::{nominatim::NominatimResolver, BlockingResolver};
+/// let resolver = NominatimResolver::new().with_user_agent("my-app/1.0");
+/// let place = resolver.resolve_sync("Cologne").unwrap();
+/// println!("{} is at {}", place.name, place.coordinate);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NominatimResolver {
+    /// Connection to the API
+    http: HttpBackend,
+    /// Language for names in results (`Accept-Language`)
+    language: Option<String>,
+}
+
+impl Default for NominatimResolver {
+    fn default() -> Self {
+        Self {
+            http: HttpBackend::new(PUBLIC_URL, RATE_LIMITER.clone()),
+            language: None,
+        }
+    }
+}
+
+impl NominatimResolver {
+    /// Create a resolver for the public Nominatim API
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use another Nominatim instance (e.g. a self hosted one)
+    ///
+    /// Self hosted instances usually don't need the public rate limit,
+    /// so you might want to combine this with [`Self::with_rate_limiter`].
+    pub fn with_base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        self.http.set_base_url(base_url.as_ref());
+        self
+    }
+
+    /// Identify your application to the API
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = user_agent.into();
+        self
+    }
+
+    /// Preferred language for names in results (e.g. "de" or "en-US")
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Use a different rate limiter
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.http.limiter = limiter;
+        self
+    }
+
+    /// Use a different retry policy
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.http.retry = retry;
+        self
+    }
+
+    /// The rate limiter used for requests
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.http.limiter
+    }
+
+    /// Query parameters for a search request
+    fn search_query(query: &str, limit: usize) -> Vec<(&'static str, String)> {
+        vec![
+            ("format", "json".to_string()),
+            ("addressdetails", "1".to_string()),
+            ("limit", limit.to_string()),
+            ("q", query.to_string()),
+        ]
+    }
+
+    /// Query parameters for a reverse request
+    fn reverse_query(coordinate: &Coordinate) -> Vec<(&'static str, String)> {
+        vec![
+            ("format", "json".to_string()),
+            ("addressdetails", "1".to_string()),
+            ("lat", coordinate.lat.to_string()),
+            ("lon", coordinate.lng.to_string()),
+        ]
+    }
+
+    /// Headers sent with every request
+    fn headers(&self) -> Vec<(HeaderName, String)> {
+        self.language
+            .iter()
+            .map(|language| (ACCEPT_LANGUAGE, language.clone()))
+            .collect()
+    }
+}
+
+impl Resolver for NominatimResolver {
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+        Box::pin(async move {
+            self.http
+                .get::<Vec<OSMLocation>>(
+                    "search",
+                    &Self::search_query(query, limit),
+                    &self.headers(),
+                )
+                .await?
+                .into_iter()
+                .map(Place::try_from)
+                .collect()
+        })
+    }
+
+    fn reverse<'a>(&'a self, coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+        Box::pin(async move {
+            self.http
+                .get::<OSMReverse>("reverse", &Self::reverse_query(coordinate), &self.headers())
+                .await?
+                .into_place()
+        })
+    }
+}
+
+impl BlockingResolver for NominatimResolver {
+    fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError> {
+        self.http
+            .get_sync::<Vec<OSMLocation>>(
+                "search",
+                &Self::search_query(query, limit),
+                &self.headers(),
+            )?
+            .into_iter()
+            .map(Place::try_from)
+            .collect()
+    }
+
+    fn reverse_sync(&self, coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+        self.http
+            .get_sync::<OSMReverse>("reverse", &Self::reverse_query(coordinate), &self.headers())?
+            .into_place()
+    }
+}
+
+/// Resolve a location synchronously name to a coordinate
+pub fn resolve_sync(location: &str) -> Result<Coordinate, CoordinateError> {
+    NominatimResolver::new()
+        .resolve_sync(location)
+        .map(Coordinate::from)
+}
+
 /// Resolve a location name to a coordinate
 pub async fn resolve(location: &str) -> Result<Coordinate, CoordinateError> {
-    let locations = reqwest::Client::new()
-        .get("https://nominatim.openstreetmap.org/search")
-        .header(reqwest::header::USER_AGENT, "tanker_price")
-        .query(&[("format", "json"), ("q", location)])
-        .send()
-        .await?
-        .json::<Vec<OSMLocation>>()
-        .await?;
-    if let Some(location) = locations.get(0) {
-        Ok(Coordinate {
-            lng: location.lon.parse()?,
-            lat: location.lat.parse()?,
-        })
-    } else {
-        Err(CoordinateError::Unresolveable)
+    NominatimResolver::new()
+        .resolve(location)
+        .await
+        .map(Coordinate::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolvers::test_server::TestServer;
+
+    const COLOGNE: &str = r#"[{"place_id":1,"lat":"50.938361","lon":"6.959974","display_name":"Köln, Nordrhein-Westfalen, Deutschland","class":"boundary","type":"administrative","importance":0.83,"boundingbox":["50.8304399","51.0849743","6.7725303","7.162028"],"address":{"city":"Köln","country_code":"de"}}]"#;
+
+    fn resolver(server: &TestServer) -> NominatimResolver {
+        NominatimResolver::new()
+            .with_base_url(&server.url)
+            .with_rate_limiter(RateLimiter::unlimited())
+    }
+
+    #[test]
+    fn test_search_sync() {
+        let server = TestServer::start(|_| (200, COLOGNE.to_string()));
+        let place = resolver(&server).resolve_sync("Köln").unwrap();
+        assert_eq!(place.coordinate, Coordinate::new(50.938361, 6.959974));
+        assert_eq!(place.category.as_deref(), Some("administrative"));
+        assert_eq!(place.country_code.as_deref(), Some("de"));
+        let bbox = place.bounding_box.unwrap();
+        assert_eq!((bbox.south(), bbox.north()), (50.8304399, 51.0849743));
+        assert_eq!((bbox.west(), bbox.east()), (6.7725303, 7.162028));
+        let requests = server.requests.lock().unwrap();
+        assert!(requests[0].starts_with("/search?format=json"));
+        assert!(requests[0].contains("limit=1"));
+    }
+
+    #[test]
+    fn test_search_sync_empty() {
+        let server = TestServer::start(|_| (200, "[]".to_string()));
+        assert!(matches!(
+            resolver(&server).resolve_sync("Nowhere"),
+            Err(CoordinateError::Unresolveable)
+        ));
+    }
+
+    #[test]
+    fn test_reverse_sync_error() {
+        let server = TestServer::start(|_| (200, r#"{"error":"Unable to geocode"}"#.to_string()));
+        assert!(matches!(
+            resolver(&server).reverse_sync(&Coordinate::new(0., 0.)),
+            Err(CoordinateError::Unresolveable)
+        ));
+    }
+
+    #[test]
+    fn test_retry_on_server_error() {
+        let server = TestServer::start(|_| (503, "".to_string()));
+        let resolver = resolver(&server).with_retry_policy(RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        });
+        assert!(matches!(
+            resolver.resolve_sync("Köln"),
+            Err(CoordinateError::HttpStatus(503))
+        ));
+        assert_eq!(server.requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let server = TestServer::start(|_| (200, COLOGNE.to_string()));
+        let places = resolver(&server).search("Köln", 5).await.unwrap();
+        assert_eq!(places.len(), 1);
+        assert_eq!(places[0].name, "Köln, Nordrhein-Westfalen, Deutschland");
     }
 }
@@ -0,0 +1,379 @@
+use crate::{
+    resolvers::{
+        http::HttpBackend,
+        rate_limit::{RateLimiter, RetryPolicy},
+        BlockingResolver, Place, ResolveFuture, Resolver,
+    },
+    BoundingBox, Coordinate, CoordinateError,
+};
+use serde::Deserialize;
+
+/// GeoJSON FeatureCollection returned by Pelias
+#[derive(Deserialize)]
+struct PeliasResponse {
+    /// Found features, best match first
+    #[serde(default)]
+    features: Vec<PeliasFeature>,
+}
+
+/// A single GeoJSON Feature
+#[derive(Deserialize)]
+struct PeliasFeature {
+    /// Point geometry of the feature
+    geometry: PeliasGeometry,
+    /// Details of the feature
+    properties: PeliasProperties,
+    /// Extent of the feature as [west, south, east, north]
+    bbox: Option<[f64; 4]>,
+}
+
+/// A GeoJSON Point geometry
+#[derive(Deserialize)]
+struct PeliasGeometry {
+    /// Position as [longitude, latitude]
+    coordinates: (f64, f64),
+}
+
+/// Properties of a Pelias feature
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PeliasProperties {
+    /// Short name of the feature
+    name: Option<String>,
+    /// Full, human readable name of the feature
+    label: Option<String>,
+    /// Kind of the feature (e.g. "venue", "address" or "locality")
+    layer: Option<String>,
+    /// Dataset of the feature (e.g. "openstreetmap" or "whosonfirst")
+    source: Option<String>,
+    /// How well the feature matches the query (0 - 1)
+    confidence: Option<f64>,
+    /// ISO 3166-1 alpha-2 country code in uppercase
+    country_code: Option<String>,
+}
+
+impl TryFrom<PeliasFeature> for Place {
+    type Error = CoordinateError;
+    fn try_from(feature: PeliasFeature) -> Result<Self, Self::Error> {
+        let (lng, lat) = feature.geometry.coordinates;
+        let properties = feature.properties;
+        let bounding_box = feature
+            .bbox
+            .and_then(|[west, south, east, north]| BoundingBox::new(south, west, north, east).ok());
+        Ok(Place {
+            coordinate: Coordinate::try_new(lat, lng)?,
+            name: properties.label.or(properties.name).unwrap_or_default(),
+            category: properties.layer,
+            source: properties.source,
+            confidence: properties.confidence,
+            country_code: properties
+                .country_code
+                .map(|code| code.to_ascii_lowercase()),
+            bounding_box,
+        })
+    }
+}
+
+impl PeliasResponse {
+    /// Convert all features into places
+    fn into_places(self) -> Result<Vec<Place>, CoordinateError> {
+        self.features.into_iter().map(Place::try_from).collect()
+    }
+
+    /// Take the first feature as place
+    fn into_place(self) -> Result<Place, CoordinateError> {
+        self.features
+            .into_iter()
+            .next()
+            .ok_or(CoordinateError::Unresolveable)
+            .and_then(Place::try_from)
+    }
+}
+
+/// Resolver using a Pelias instance
+///
+/// Pelias has no public instance, so the base url (e.g. `http://localhost:4000`)
+/// always has to be provided. Requests aren't rate limited by default.
+///
+/// ```no_run
+/// # use recoord::resolvers::{pelias::PeliasResolver, BlockingResolver};
+/// let resolver = PeliasResolver::new("http://localhost:4000");
+/// let place = resolver.resolve_sync("Kölner Dom").unwrap();
+/// let suggestions = resolver.autocomplete_sync("Kölner D", 5).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PeliasResolver {
+    /// Connection to the API
+    http: HttpBackend,
+    /// Key for hosted instances (e.g. geocode.earth)
+    api_key: Option<String>,
+    /// Language for names in results
+    language: Option<String>,
+    /// Prefer results close to this location
+    focus_point: Option<Coordinate>,
+    /// Only return results from these layers (e.g. "venue", "address")
+    layers: Vec<String>,
+    /// Only return results from these sources (e.g. "osm", "wof")
+    sources: Vec<String>,
+}
+
+impl PeliasResolver {
+    /// Create a resolver for the Pelias instance at `base_url`
+    pub fn new(base_url: impl AsRef<str>) -> Self {
+        Self {
+            http: HttpBackend::new(base_url.as_ref(), RateLimiter::unlimited()),
+            api_key: None,
+            language: None,
+            focus_point: None,
+            layers: vec![],
+            sources: vec![],
+        }
+    }
+
+    /// Identify your application to the API
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = user_agent.into();
+        self
+    }
+
+    /// Send an api key with every request (needed for hosted instances)
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Preferred language for names in results (e.g. "de")
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Prefer results close to a location
+    pub fn with_focus_point(mut self, location: Coordinate) -> Self {
+        self.focus_point = Some(location);
+        self
+    }
+
+    /// Only return results from these layers (e.g. "venue", "address", "locality")
+    pub fn with_layers<S: Into<String>>(mut self, layers: impl IntoIterator<Item = S>) -> Self {
+        self.layers = layers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only return results from these sources (e.g. "osm", "oa", "wof", "gn")
+    pub fn with_sources<S: Into<String>>(mut self, sources: impl IntoIterator<Item = S>) -> Self {
+        self.sources = sources.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Use a rate limiter
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.http.limiter = limiter;
+        self
+    }
+
+    /// Use a different retry policy
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.http.retry = retry;
+        self
+    }
+
+    /// The rate limiter used for requests
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.http.limiter
+    }
+
+    /// Parameters shared by all requests
+    fn common_query(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![];
+        if let Some(api_key) = &self.api_key {
+            params.push(("api_key", api_key.clone()));
+        }
+        if let Some(language) = &self.language {
+            params.push(("lang", language.clone()));
+        }
+        if !self.layers.is_empty() {
+            params.push(("layers", self.layers.join(",")));
+        }
+        if !self.sources.is_empty() {
+            params.push(("sources", self.sources.join(",")));
+        }
+        params
+    }
+
+    /// Query parameters for a search or autocomplete request
+    fn search_query(&self, text: &str, limit: Option<usize>) -> Vec<(&'static str, String)> {
+        let mut params = vec![("text", text.to_string())];
+        if let Some(limit) = limit {
+            params.push(("size", limit.to_string()));
+        }
+        if let Some(location) = &self.focus_point {
+            params.push(("focus.point.lat", location.lat.to_string()));
+            params.push(("focus.point.lon", location.lng.to_string()));
+        }
+        params.extend(self.common_query());
+        params
+    }
+
+    /// Query parameters for a reverse request
+    fn reverse_query(&self, coordinate: &Coordinate) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("point.lat", coordinate.lat.to_string()),
+            ("point.lon", coordinate.lng.to_string()),
+            ("size", "1".to_string()),
+        ];
+        params.extend(self.common_query());
+        params
+    }
+
+    /// Suggest places while the user is typing (`/v1/autocomplete`)
+    ///
+    /// Autocomplete doesn't support a result size, so `limit` is applied locally.
+    pub async fn autocomplete(
+        &self,
+        text: &str,
+        limit: usize,
+    ) -> Result<Vec<Place>, CoordinateError> {
+        let mut places = self
+            .http
+            .get::<PeliasResponse>("v1/autocomplete", &self.search_query(text, None), &[])
+            .await?
+            .into_places()?;
+        places.truncate(limit);
+        Ok(places)
+    }
+
+    /// Suggest places while the user is typing (`/v1/autocomplete`), blocking
+    ///
+    /// Autocomplete doesn't support a result size, so `limit` is applied locally.
+    pub fn autocomplete_sync(
+        &self,
+        text: &str,
+        limit: usize,
+    ) -> Result<Vec<Place>, CoordinateError> {
+        let mut places = self
+            .http
+            .get_sync::<PeliasResponse>("v1/autocomplete", &self.search_query(text, None), &[])?
+            .into_places()?;
+        places.truncate(limit);
+        Ok(places)
+    }
+}
+
+impl Resolver for PeliasResolver {
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+        Box::pin(async move {
+            self.http
+                .get::<PeliasResponse>("v1/search", &self.search_query(query, Some(limit)), &[])
+                .await?
+                .into_places()
+        })
+    }
+
+    fn reverse<'a>(&'a self, coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+        Box::pin(async move {
+            self.http
+                .get::<PeliasResponse>("v1/reverse", &self.reverse_query(coordinate), &[])
+                .await?
+                .into_place()
+        })
+    }
+}
+
+impl BlockingResolver for PeliasResolver {
+    fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError> {
+        self.http
+            .get_sync::<PeliasResponse>("v1/search", &self.search_query(query, Some(limit)), &[])?
+            .into_places()
+    }
+
+    fn reverse_sync(&self, coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+        self.http
+            .get_sync::<PeliasResponse>("v1/reverse", &self.reverse_query(coordinate), &[])?
+            .into_place()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolvers::test_server::TestServer;
+
+    const DOM: &str = r#"{"geocoding":{"version":"0.2"},"type":"FeatureCollection","features":[{"type":"Feature","geometry":{"type":"Point","coordinates":[6.958123,50.941278]},"properties":{"id":"way/4532022","gid":"openstreetmap:venue:way/4532022","layer":"venue","source":"openstreetmap","name":"Kölner Dom","country":"Germany","country_a":"DEU","country_code":"DE","confidence":0.9,"label":"Kölner Dom, Köln, Germany"}},{"type":"Feature","geometry":{"type":"Point","coordinates":[6.95,50.94]},"properties":{"layer":"locality","source":"whosonfirst","name":"Köln"},"bbox":[6.772531,50.830443,7.162028,51.084974]}],"bbox":[6.95,50.94,6.958123,50.941278]}"#;
+
+    #[test]
+    fn test_search_sync() {
+        let server = TestServer::start(|_| (200, DOM.to_string()));
+        let resolver = PeliasResolver::new(&server.url)
+            .with_api_key("secret")
+            .with_layers(["venue", "locality"]);
+        let places = resolver.search_sync("Kölner Dom", 2).unwrap();
+        assert_eq!(places[0].coordinate, Coordinate::new(50.941278, 6.958123));
+        assert_eq!(places[0].name, "Kölner Dom, Köln, Germany");
+        assert_eq!(places[0].category.as_deref(), Some("venue"));
+        assert_eq!(places[0].source.as_deref(), Some("openstreetmap"));
+        assert_eq!(places[0].confidence, Some(0.9));
+        assert_eq!(places[0].country_code.as_deref(), Some("de"));
+        assert_eq!(places[0].bounding_box, None);
+        assert_eq!(places[1].name, "Köln");
+        assert_eq!(places[1].confidence, None);
+        let bbox = places[1].bounding_box.unwrap();
+        assert!(bbox.contains(&places[0].coordinate));
+        assert_eq!(
+            server.requests.lock().unwrap()[0],
+            "/v1/search?text=K%C3%B6lner+Dom&size=2&api_key=secret&layers=venue%2Clocality"
+        );
+    }
+
+    #[test]
+    fn test_autocomplete_sync() {
+        let server = TestServer::start(|_| (200, DOM.to_string()));
+        let places = PeliasResolver::new(&server.url)
+            .autocomplete_sync("Kölner D", 1)
+            .unwrap();
+        assert_eq!(places.len(), 1);
+        assert!(server.requests.lock().unwrap()[0].starts_with("/v1/autocomplete?text="));
+    }
+
+    #[test]
+    fn test_reverse_sync_empty() {
+        let server = TestServer::start(|_| {
+            (
+                200,
+                r#"{"type":"FeatureCollection","features":[]}"#.to_string(),
+            )
+        });
+        assert!(matches!(
+            PeliasResolver::new(&server.url).reverse_sync(&Coordinate::new(0., 0.)),
+            Err(CoordinateError::Unresolveable)
+        ));
+        assert_eq!(
+            server.requests.lock().unwrap()[0],
+            "/v1/reverse?point.lat=0&point.lon=0&size=1"
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_response() {
+        let server = TestServer::start(|_| {
+            (
+                200,
+                r#"{"type":"FeatureCollection","features":[{"type":"Feature","geometry":{"type":"Point","coordinates":[13.4,152.5]},"properties":{"name":"Nowhere"}}]}"#.to_string(),
+            )
+        });
+        assert!(matches!(
+            PeliasResolver::new(&server.url).search_sync("Nowhere", 1),
+            Err(CoordinateError::InvalidValue)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reverse() {
+        let server = TestServer::start(|_| (200, DOM.to_string()));
+        let place = PeliasResolver::new(&server.url)
+            .reverse(&Coordinate::new(50.941278, 6.958123))
+            .await
+            .unwrap();
+        assert_eq!(place.category.as_deref(), Some("venue"));
+    }
+}
@@ -0,0 +1,355 @@
+use std::{sync::LazyLock, time::Duration};
+
+use crate::{
+    resolvers::{
+        http::HttpBackend,
+        rate_limit::{RateLimiter, RetryPolicy},
+        BlockingResolver, Place, ResolveFuture, Resolver,
+    },
+    BoundingBox, Coordinate, CoordinateError,
+};
+use serde::Deserialize;
+
+/// Base url of the public Photon API hosted by Komoot
+pub const PUBLIC_URL: &str = "https://photon.komoot.io";
+
+/// Rate limiter shared by all requests to the public Photon API
+///
+/// The public instance is provided on a fair use basis, so we stay
+/// at one request per second.
+pub static RATE_LIMITER: LazyLock<RateLimiter> =
+    LazyLock::new(|| RateLimiter::new(Duration::from_secs(1)));
+
+/// GeoJSON FeatureCollection returned by Photon
+#[derive(Deserialize)]
+struct PhotonResponse {
+    /// Found features, best match first
+    features: Vec<PhotonFeature>,
+}
+
+/// A single GeoJSON Feature
+#[derive(Deserialize)]
+struct PhotonFeature {
+    /// Point geometry of the feature
+    geometry: PhotonGeometry,
+    /// OSM details of the feature
+    properties: PhotonProperties,
+}
+
+/// A GeoJSON Point geometry
+#[derive(Deserialize)]
+struct PhotonGeometry {
+    /// Position as [longitude, latitude]
+    coordinates: (f64, f64),
+}
+
+/// Properties of a Photon feature
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PhotonProperties {
+    /// Name of the feature itself
+    name: Option<String>,
+    /// Street name
+    street: Option<String>,
+    /// House number
+    housenumber: Option<String>,
+    /// Postal code
+    postcode: Option<String>,
+    /// City the feature is in
+    city: Option<String>,
+    /// State the feature is in
+    state: Option<String>,
+    /// Country the feature is in
+    country: Option<String>,
+    /// ISO 3166-1 alpha-2 country code in uppercase
+    countrycode: Option<String>,
+    /// Value of the main OSM tag (e.g. "city")
+    osm_value: Option<String>,
+    /// Extent of the feature as [west, north, east, south]
+    extent: Option<[f64; 4]>,
+}
+
+impl PhotonProperties {
+    /// Build a human readable name like Nominatim's display_name
+    fn display_name(&self) -> String {
+        let street = match (&self.street, &self.housenumber) {
+            (Some(street), Some(number)) => Some(format!("{street} {number}")),
+            (street, _) => street.clone(),
+        };
+        let city = match (&self.postcode, &self.city) {
+            (Some(postcode), Some(city)) => Some(format!("{postcode} {city}")),
+            (_, city) => city.clone(),
+        };
+        let mut parts: Vec<String> = vec![];
+        for part in [
+            self.name.clone(),
+            street,
+            city,
+            self.state.clone(),
+            self.country.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !parts.contains(&part) {
+                parts.push(part);
+            }
+        }
+        parts.join(", ")
+    }
+}
+
+impl TryFrom<PhotonFeature> for Place {
+    type Error = CoordinateError;
+    fn try_from(feature: PhotonFeature) -> Result<Self, Self::Error> {
+        let (lng, lat) = feature.geometry.coordinates;
+        let properties = feature.properties;
+        Ok(Place {
+            coordinate: Coordinate::try_new(lat, lng)?,
+            name: properties.display_name(),
+            category: properties.osm_value,
+            source: Some("openstreetmap".to_string()),
+            confidence: None,
+            country_code: properties.countrycode.map(|code| code.to_ascii_lowercase()),
+            bounding_box: properties.extent.and_then(|[west, north, east, south]| {
+                BoundingBox::new(south, west, north, east).ok()
+            }),
+        })
+    }
+}
+
+/// Resolver using the Photon API
+///
+/// Photon is made for search-as-you-type, so results can be biased
+/// towards a location (e.g. the center of the visible map).
+///
+/// ```no_run
+/// # use recoord::{Coordinate, resolvers::{photon::PhotonResolver, BlockingResolver}};
+/// let resolver = PhotonResolver::new()
+///     .with_location_bias(Coordinate::new(50.94, 6.96))
+///     .with_language("de");
+/// let places = resolver.search_sync("Domk", 5).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PhotonResolver {
+    /// Connection to the API
+    http: HttpBackend,
+    /// Language for names in results
+    language: Option<String>,
+    /// Prefer results close to this location
+    location_bias: Option<Coordinate>,
+}
+
+impl Default for PhotonResolver {
+    fn default() -> Self {
+        Self {
+            http: HttpBackend::new(PUBLIC_URL, RATE_LIMITER.clone()),
+            language: None,
+            location_bias: None,
+        }
+    }
+}
+
+impl PhotonResolver {
+    /// Create a resolver for the public Photon API
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use another Photon instance (e.g. a self hosted one)
+    ///
+    /// Self hosted instances usually don't need the public rate limit,
+    /// so you might want to combine this with [`Self::with_rate_limiter`].
+    pub fn with_base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        self.http.set_base_url(base_url.as_ref());
+        self
+    }
+
+    /// Identify your application to the API
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.http.user_agent = user_agent.into();
+        self
+    }
+
+    /// Language for names in results (Photon supports "default", "de", "en" and "fr")
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Prefer results close to a location
+    pub fn with_location_bias(mut self, location: Coordinate) -> Self {
+        self.location_bias = Some(location);
+        self
+    }
+
+    /// Use a different rate limiter
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.http.limiter = limiter;
+        self
+    }
+
+    /// Use a different retry policy
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.http.retry = retry;
+        self
+    }
+
+    /// The rate limiter used for requests
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.http.limiter
+    }
+
+    /// Query parameters for a search request
+    fn search_query(&self, query: &str, limit: usize) -> Vec<(&'static str, String)> {
+        let mut params = vec![("q", query.to_string()), ("limit", limit.to_string())];
+        if let Some(language) = &self.language {
+            params.push(("lang", language.clone()));
+        }
+        if let Some(location) = &self.location_bias {
+            params.push(("lat", location.lat.to_string()));
+            params.push(("lon", location.lng.to_string()));
+        }
+        params
+    }
+
+    /// Query parameters for a reverse request
+    fn reverse_query(&self, coordinate: &Coordinate) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("lat", coordinate.lat.to_string()),
+            ("lon", coordinate.lng.to_string()),
+            ("limit", "1".to_string()),
+        ];
+        if let Some(language) = &self.language {
+            params.push(("lang", language.clone()));
+        }
+        params
+    }
+}
+
+impl PhotonResponse {
+    /// Convert all features into places
+    fn into_places(self) -> Result<Vec<Place>, CoordinateError> {
+        self.features.into_iter().map(Place::try_from).collect()
+    }
+
+    /// Take the first feature as place
+    fn into_place(self) -> Result<Place, CoordinateError> {
+        self.features
+            .into_iter()
+            .next()
+            .ok_or(CoordinateError::Unresolveable)
+            .and_then(Place::try_from)
+    }
+}
+
+impl Resolver for PhotonResolver {
+    fn search<'a>(&'a self, query: &'a str, limit: usize) -> ResolveFuture<'a, Vec<Place>> {
+        Box::pin(async move {
+            self.http
+                .get::<PhotonResponse>("api", &self.search_query(query, limit), &[])
+                .await?
+                .into_places()
+        })
+    }
+
+    fn reverse<'a>(&'a self, coordinate: &'a Coordinate) -> ResolveFuture<'a, Place> {
+        Box::pin(async move {
+            self.http
+                .get::<PhotonResponse>("reverse", &self.reverse_query(coordinate), &[])
+                .await?
+                .into_place()
+        })
+    }
+}
+
+impl BlockingResolver for PhotonResolver {
+    fn search_sync(&self, query: &str, limit: usize) -> Result<Vec<Place>, CoordinateError> {
+        self.http
+            .get_sync::<PhotonResponse>("api", &self.search_query(query, limit), &[])?
+            .into_places()
+    }
+
+    fn reverse_sync(&self, coordinate: &Coordinate) -> Result<Place, CoordinateError> {
+        self.http
+            .get_sync::<PhotonResponse>("reverse", &self.reverse_query(coordinate), &[])?
+            .into_place()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolvers::test_server::TestServer;
+
+    const BERLIN: &str = r#"{"type":"FeatureCollection","features":[{"type":"Feature","geometry":{"type":"Point","coordinates":[13.3888599,52.5170365]},"properties":{"osm_id":62422,"osm_type":"R","osm_key":"place","osm_value":"city","name":"Berlin","country":"Deutschland","countrycode":"DE","state":"Berlin","type":"city","extent":[13.088345,52.6755087,13.7611609,52.3382448]}},{"type":"Feature","geometry":{"type":"Point","coordinates":[-72.7834,41.6215]},"properties":{"osm_value":"town","name":"Berlin","state":"Connecticut","country":"United States","countrycode":"US"}}]}"#;
+
+    fn resolver(server: &TestServer) -> PhotonResolver {
+        PhotonResolver::new()
+            .with_base_url(&server.url)
+            .with_rate_limiter(RateLimiter::unlimited())
+    }
+
+    #[test]
+    fn test_search_sync() {
+        let server = TestServer::start(|_| (200, BERLIN.to_string()));
+        let places = resolver(&server)
+            .with_language("de")
+            .with_location_bias(Coordinate::new(52.5, 13.4))
+            .search_sync("Berlin", 2)
+            .unwrap();
+        assert_eq!(places.len(), 2);
+        assert_eq!(
+            places[0].coordinate,
+            Coordinate::new(52.5170365, 13.3888599)
+        );
+        assert_eq!(places[0].name, "Berlin, Deutschland");
+        assert_eq!(places[0].category.as_deref(), Some("city"));
+        assert_eq!(places[0].country_code.as_deref(), Some("de"));
+        let bbox = places[0].bounding_box.unwrap();
+        assert_eq!((bbox.south(), bbox.west()), (52.3382448, 13.088345));
+        assert_eq!(places[1].bounding_box, None);
+        assert_eq!(places[1].name, "Berlin, Connecticut, United States");
+        let requests = server.requests.lock().unwrap();
+        assert_eq!(
+            requests[0],
+            "/api?q=Berlin&limit=2&lang=de&lat=52.5&lon=13.4"
+        );
+    }
+
+    #[test]
+    fn test_reverse_sync_empty() {
+        let server = TestServer::start(|_| {
+            (
+                200,
+                r#"{"type":"FeatureCollection","features":[]}"#.to_string(),
+            )
+        });
+        assert!(matches!(
+            resolver(&server).reverse_sync(&Coordinate::new(0., 0.)),
+            Err(CoordinateError::Unresolveable)
+        ));
+        assert!(server.requests.lock().unwrap()[0].starts_with("/reverse?lat=0&lon=0"));
+    }
+
+    #[test]
+    fn test_out_of_range_response() {
+        let server = TestServer::start(|_| {
+            (
+                200,
+                r#"{"type":"FeatureCollection","features":[{"type":"Feature","geometry":{"type":"Point","coordinates":[13.4,152.5]},"properties":{"name":"Nowhere"}}]}"#.to_string(),
+            )
+        });
+        assert!(matches!(
+            resolver(&server).search_sync("Nowhere", 1),
+            Err(CoordinateError::InvalidValue)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let server = TestServer::start(|_| (200, BERLIN.to_string()));
+        let place = resolver(&server).resolve("Berlin").await.unwrap();
+        assert_eq!(place.coordinate, Coordinate::new(52.5170365, 13.3888599));
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::CoordinateError;
+use reqwest::{header::HeaderMap, StatusCode};
+
+/// Limits how often requests may be sent to an API.
+///
+/// The limiter hands out time slots which are at least `interval` apart.
+/// Clones share the same budget, so one limiter can be handed to several
+/// resolvers and is used by both the sync and the async functions.
+///
+/// ```
+/// # use recoord::resolvers::rate_limit::RateLimiter;
+/// # use std::time::{Duration, Instant};
+/// let limiter = RateLimiter::new(Duration::from_millis(20));
+/// let start = Instant::now();
+/// limiter.wait();
+/// limiter.wait();
+/// assert!(start.elapsed() >= Duration::from_millis(20));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    /// Minimum time between two requests
+    interval: Duration,
+    /// The earliest point in time the next request may be sent
+    next_slot: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter which allows one request per `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Create a limiter which doesn't limit at all
+    pub fn unlimited() -> Self {
+        Self::new(Duration::ZERO)
+    }
+
+    /// Minimum time between two requests
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Block the current thread until the next request may be sent
+    pub fn wait(&self) {
+        let delay = self.reserve();
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+
+    /// Wait asynchronously until the next request may be sent
+    pub async fn wait_async(&self) {
+        let delay = self.reserve();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Hold back all further requests for at least `delay`
+    ///
+    /// This is used when the API asks us to back off (e.g. via `Retry-After`).
+    pub fn hold_off(&self, delay: Duration) {
+        let earliest = Instant::now() + delay;
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|err| err.into_inner());
+        if next_slot.is_none_or(|next_slot| next_slot < earliest) {
+            *next_slot = Some(earliest);
+        }
+    }
+
+    /// Reserve the next free slot and return how long to wait for it
+    fn reserve(&self) -> Duration {
+        let now = Instant::now();
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|err| err.into_inner());
+        let slot = next_slot.map_or(now, |next_slot| next_slot.max(now));
+        *next_slot = Some(slot + self.interval);
+        slot - now
+    }
+}
+
+/// Decides if and when a failed request is retried
+///
+/// Requests are retried when the API answers with `429 Too Many Requests` or a
+/// `5xx` server error. The delay doubles on every attempt, unless the API sends
+/// a `Retry-After` header, which takes precedence. Both are capped at
+/// `max_delay`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How often a request is retried before the error is returned
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound for the exponential backoff and `Retry-After`
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy which never retries
+    pub fn never() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Check if a response with this status should be retried
+    pub fn is_retryable(status: StatusCode) -> bool {
+        status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+    }
+
+    /// Delay before retry number `attempt` (starting at 0)
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after
+            .unwrap_or_else(|| self.base_delay.saturating_mul(2u32.saturating_pow(attempt)))
+            .min(self.max_delay)
+    }
+
+    /// Send a blocking request, respecting the limiter and retrying on failure
+    ///
+    /// `request` is called once per attempt to build a fresh request.
+    pub fn send_blocking(
+        &self,
+        limiter: &RateLimiter,
+        request: impl Fn() -> reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response, CoordinateError> {
+        let mut attempt = 0;
+        loop {
+            limiter.wait();
+            let response = request().send()?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            if attempt >= self.max_retries || !Self::is_retryable(status) {
+                return Err(CoordinateError::HttpStatus(status.as_u16()));
+            }
+            limiter.hold_off(self.backoff(attempt, retry_after(response.headers())));
+            attempt += 1;
+        }
+    }
+
+    /// Send a request, respecting the limiter and retrying on failure
+    ///
+    /// `request` is called once per attempt to build a fresh request.
+    pub async fn send(
+        &self,
+        limiter: &RateLimiter,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, CoordinateError> {
+        let mut attempt = 0;
+        loop {
+            limiter.wait_async().await;
+            let response = request().send().await?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            if attempt >= self.max_retries || !Self::is_retryable(status) {
+                return Err(CoordinateError::HttpStatus(status.as_u16()));
+            }
+            limiter.hold_off(self.backoff(attempt, retry_after(response.headers())));
+            attempt += 1;
+        }
+    }
+}
+
+/// Read the `Retry-After` header, which is either in seconds or a HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, RETRY_AFTER};
+
+    #[test]
+    fn test_limiter_spaces_slots() {
+        let limiter = RateLimiter::new(Duration::from_secs(1));
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        let second = limiter.reserve();
+        assert!(second > Duration::from_millis(900) && second <= Duration::from_secs(1));
+        let third = limiter.reserve();
+        assert!(third > Duration::from_millis(1900) && third <= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_limiter_hold_off() {
+        let limiter = RateLimiter::new(Duration::from_millis(10));
+        limiter.hold_off(Duration::from_secs(5));
+        assert!(limiter.reserve() > Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0, None), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2, None), Duration::from_secs(4));
+        assert_eq!(policy.backoff(20, None), Duration::from_secs(60));
+        assert_eq!(
+            policy.backoff(2, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            policy.backoff(0, Some(Duration::from_secs(86400))),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(RetryPolicy::is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(RetryPolicy::is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!RetryPolicy::is_retryable(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+}